@@ -12,6 +12,7 @@ use reqwest::Request;
 use tailcall::core::config::Batch;
 use tailcall::core::http::{DataLoaderRequest, HttpDataLoader, Response};
 use tailcall::core::ir::model::IoId;
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{cache, EnvIO, FileIO, HttpIO};
 
@@ -67,9 +68,21 @@ impl tailcall::core::Cache for Cache {
         unimplemented!("Not needed for this bench")
     }
 
+    async fn tag<'a>(&'a self, _: &'a Self::Key, _: &'a [String]) -> Result<(), cache::Error> {
+        unimplemented!("Not needed for this bench")
+    }
+
+    async fn purge<'a>(&'a self, _: &'a str) -> Result<(), cache::Error> {
+        unimplemented!("Not needed for this bench")
+    }
+
     fn hit_rate(&self) -> Option<f64> {
         unimplemented!("Not needed for this bench")
     }
+
+    fn stats(&self) -> Option<cache::Stats> {
+        unimplemented!("Not needed for this bench")
+    }
 }
 
 pub fn benchmark_data_loader(c: &mut Criterion) {
@@ -81,9 +94,11 @@ pub fn benchmark_data_loader(c: &mut Criterion) {
                 let rt = TargetRuntime {
                     http: client_clone.clone(),
                     http2_only: client_clone,
+                    upstreams: Default::default(),
                     env: Arc::new(Env {}),
                     file: Arc::new(File {}),
                     cache: Arc::new(Cache {}),
+                    rate_limit: Arc::new(InMemoryRateLimitStore::default()),
                     extensions: Arc::new(vec![]),
                     cmd_worker: None,
                     worker: None,
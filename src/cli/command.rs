@@ -26,6 +26,11 @@ pub enum Command {
         /// separated by spaces if more than one
         #[arg(required = true)]
         file_paths: Vec<String>,
+
+        /// Reloads the server whenever one of the configuration files, or any
+        /// file linked from them, changes
+        #[arg(short, long)]
+        watch: bool,
     },
 
     /// Validate a composition spec
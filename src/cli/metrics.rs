@@ -10,11 +10,49 @@ fn cache_metrics(runtime: &TargetRuntime) -> Result<()> {
         .with_description("Cache hit rate ratio")
         .init();
 
-    meter.register_callback(&[counter.as_any()], move |observer| {
-        if let Some(hit_rate) = cache.hit_rate() {
-            observer.observe_f64(&counter, hit_rate, &[]);
-        }
-    })?;
+    let hits = meter
+        .u64_observable_counter("cache.hits")
+        .with_description("Cache hits")
+        .init();
+    let misses = meter
+        .u64_observable_counter("cache.misses")
+        .with_description("Cache misses")
+        .init();
+    let evictions = meter
+        .u64_observable_counter("cache.evictions")
+        .with_description("Entries evicted from the cache to make room for others")
+        .init();
+    let entries = meter
+        .u64_observable_gauge("cache.entries")
+        .with_description("Entries in the cache")
+        .init();
+    let size = meter
+        .u64_observable_gauge("cache.size")
+        .with_description("Estimated size of the entries in the cache, in bytes")
+        .init();
+
+    meter.register_callback(
+        &[
+            counter.as_any(),
+            hits.as_any(),
+            misses.as_any(),
+            evictions.as_any(),
+            entries.as_any(),
+            size.as_any(),
+        ],
+        move |observer| {
+            if let Some(hit_rate) = cache.hit_rate() {
+                observer.observe_f64(&counter, hit_rate, &[]);
+            }
+            if let Some(stats) = cache.stats() {
+                observer.observe_u64(&hits, stats.hits, &[]);
+                observer.observe_u64(&misses, stats.misses, &[]);
+                observer.observe_u64(&evictions, stats.evictions, &[]);
+                observer.observe_u64(&entries, stats.entries, &[]);
+                observer.observe_u64(&size, stats.size, &[]);
+            }
+        },
+    )?;
 
     Ok(())
 }
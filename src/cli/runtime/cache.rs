@@ -0,0 +1,379 @@
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use async_graphql_value::ConstValue;
+use redis::aio::ConnectionManager;
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::core::cache::{self, Stats};
+use crate::core::config::CacheEncoding;
+use crate::core::ir::model::IoId;
+use crate::core::{blueprint, Cache};
+
+/// Most keys deleted by a single `DEL` command while purging a tag.
+const PURGE_BATCH_SIZE: usize = 1000;
+
+/// Adds the key, the first argument, to the sets of the tags, the keys, as
+/// long as it's still cached. The sets expire along with the last of their
+/// keys.
+const TAG_SCRIPT: &str = r#"
+local ttl = redis.call('PTTL', ARGV[1])
+if ttl < 0 then
+    return 0
+end
+for _, tag_key in ipairs(KEYS) do
+    redis.call('SADD', tag_key, ARGV[1])
+    if redis.call('PTTL', tag_key) < ttl then
+        redis.call('PEXPIRE', tag_key, ttl)
+    end
+end
+return 1
+"#;
+
+/// Cache storing the values in a Redis server, or any server speaking its
+/// protocol, so that they're shared between the instances of the gateway.
+pub struct RedisCache {
+    url: Url,
+    connections: Vec<OnceCell<ConnectionManager>>,
+    next: AtomicUsize,
+    encoding: CacheEncoding,
+    key_prefix: String,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RedisCache {
+    /// The connections are opened once they're first used, and reopened
+    /// whenever they're lost.
+    pub fn new(redis: &blueprint::RedisCache) -> Self {
+        Self {
+            url: redis.url.clone(),
+            connections: (0..redis.pool_size).map(|_| OnceCell::new()).collect(),
+            next: AtomicUsize::new(0),
+            encoding: redis.encoding,
+            key_prefix: redis.key_prefix.clone(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Picks the connections of the pool in turn.
+    async fn connection(&self) -> Result<ConnectionManager, cache::Error> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        let connection = self.connections[index]
+            .get_or_try_init(|| async {
+                redis::Client::open(self.url.as_str())?
+                    .get_connection_manager()
+                    .await
+            })
+            .await
+            .map_err(redis_error)?;
+
+        Ok(connection.clone())
+    }
+
+    fn key(&self, key: &IoId) -> String {
+        format!("{}{}", self.key_prefix, key.as_u64())
+    }
+
+    /// Key of the set of the keys associated with a tag
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}tag:{}", self.key_prefix, tag)
+    }
+
+    fn encode(&self, value: &ConstValue) -> Result<Vec<u8>, cache::Error> {
+        match self.encoding {
+            CacheEncoding::JSON => Ok(serde_json::to_vec(value)?),
+            CacheEncoding::MessagePack => {
+                rmp_serde::to_vec(value).map_err(|e| cache::Error::Encoding(e.to_string()))
+            }
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<ConstValue, cache::Error> {
+        match self.encoding {
+            CacheEncoding::JSON => Ok(serde_json::from_slice(bytes)?),
+            CacheEncoding::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| cache::Error::Encoding(e.to_string()))
+            }
+        }
+    }
+}
+
+fn redis_error(error: redis::RedisError) -> cache::Error {
+    cache::Error::Redis(error.to_string())
+}
+
+#[async_trait::async_trait]
+impl Cache for RedisCache {
+    type Key = IoId;
+    type Value = ConstValue;
+
+    async fn set<'a>(
+        &'a self,
+        key: IoId,
+        value: ConstValue,
+        ttl: NonZeroU64,
+    ) -> Result<(), cache::Error> {
+        let value = self.encode(&value)?;
+        redis::cmd("SET")
+            .arg(self.key(&key))
+            .arg(value)
+            .arg("PX")
+            .arg(ttl.get())
+            .query_async::<_, ()>(&mut self.connection().await?)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn get<'a>(&'a self, key: &'a IoId) -> Result<Option<ConstValue>, cache::Error> {
+        let value = redis::cmd("GET")
+            .arg(self.key(key))
+            .query_async::<_, Option<Vec<u8>>>(&mut self.connection().await?)
+            .await
+            .map_err(redis_error)?;
+
+        match value {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(Some(self.decode(&value)?))
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn tag<'a>(&'a self, key: &'a IoId, tags: &'a [String]) -> Result<(), cache::Error> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+
+        let tag_keys = tags.iter().map(|tag| self.tag_key(tag)).collect::<Vec<_>>();
+        redis::cmd("EVAL")
+            .arg(TAG_SCRIPT)
+            .arg(tag_keys.len())
+            .arg(tag_keys)
+            .arg(self.key(key))
+            .query_async::<_, ()>(&mut self.connection().await?)
+            .await
+            .map_err(redis_error)
+    }
+
+    async fn purge<'a>(&'a self, tag: &'a str) -> Result<(), cache::Error> {
+        let tag_key = self.tag_key(tag);
+        let mut connection = self.connection().await?;
+        let keys = redis::cmd("SMEMBERS")
+            .arg(&tag_key)
+            .query_async::<_, Vec<String>>(&mut connection)
+            .await
+            .map_err(redis_error)?;
+
+        let mut pipe = redis::pipe();
+        for keys in keys.chunks(PURGE_BATCH_SIZE) {
+            pipe.cmd("DEL").arg(keys).ignore();
+        }
+        pipe.cmd("DEL").arg(tag_key).ignore();
+        pipe.query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(redis_error)
+    }
+
+    fn hit_rate(&self) -> Option<f64> {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+
+        if hits + misses > 0 {
+            return Some(hits as f64 / (hits + misses) as f64);
+        }
+
+        None
+    }
+
+    fn stats(&self) -> Option<Stats> {
+        // the entries are counted by the server, across all the instances
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+
+    enum Value {
+        String(Vec<u8>),
+        Set(BTreeSet<Vec<u8>>),
+    }
+
+    type Store = Arc<Mutex<HashMap<Vec<u8>, Value>>>;
+
+    /// Serves the few commands used by the cache over the Redis protocol,
+    /// ignoring the expiration of the keys. The only script evaluated is the
+    /// one tagging keys.
+    async fn start_server() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("redis://{}", listener.local_addr().unwrap())).unwrap();
+        let store = Store::default();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve(stream, store.clone()));
+            }
+        });
+
+        url
+    }
+
+    async fn serve(stream: TcpStream, store: Store) {
+        let mut stream = BufReader::new(stream);
+        while let Some(command) = read_command(&mut stream).await {
+            let reply = execute(command, &store);
+            stream.get_mut().write_all(&reply).await.unwrap();
+        }
+    }
+
+    async fn read_line(stream: &mut BufReader<TcpStream>) -> Option<String> {
+        let mut line = String::new();
+        match stream.read_line(&mut line).await {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end().to_string()),
+        }
+    }
+
+    async fn read_command(stream: &mut BufReader<TcpStream>) -> Option<Vec<Vec<u8>>> {
+        let len = read_line(stream).await?;
+        let len = len.strip_prefix('*')?.parse::<usize>().ok()?;
+        let mut command = Vec::with_capacity(len);
+        for _ in 0..len {
+            let len = read_line(stream).await?;
+            let len = len.strip_prefix('$')?.parse::<usize>().ok()?;
+            let mut arg = vec![0; len + 2];
+            stream.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            command.push(arg);
+        }
+
+        Some(command)
+    }
+
+    fn bulk(value: &[u8]) -> Vec<u8> {
+        let mut reply = format!("${}\r\n", value.len()).into_bytes();
+        reply.extend_from_slice(value);
+        reply.extend_from_slice(b"\r\n");
+        reply
+    }
+
+    fn execute(command: Vec<Vec<u8>>, store: &Store) -> Vec<u8> {
+        let mut store = store.lock().unwrap();
+        let name = String::from_utf8_lossy(&command[0]).to_uppercase();
+        match (name.as_str(), &command[1..]) {
+            ("SET", [key, value, ..]) => {
+                store.insert(key.clone(), Value::String(value.clone()));
+                b"+OK\r\n".to_vec()
+            }
+            ("GET", [key]) => match store.get(key) {
+                Some(Value::String(value)) => bulk(value),
+                _ => b"$-1\r\n".to_vec(),
+            },
+            ("SADD", [key, members @ ..]) => {
+                let entry = store
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Set(BTreeSet::new()));
+                if let Value::Set(set) = entry {
+                    set.extend(members.iter().cloned());
+                }
+                format!(":{}\r\n", members.len()).into_bytes()
+            }
+            ("SMEMBERS", [key]) => match store.get(key) {
+                Some(Value::Set(set)) => {
+                    let mut reply = format!("*{}\r\n", set.len()).into_bytes();
+                    set.iter().for_each(|member| reply.extend(bulk(member)));
+                    reply
+                }
+                _ => b"*0\r\n".to_vec(),
+            },
+            ("EVAL", [_, count, args @ ..]) => {
+                let count = String::from_utf8_lossy(count).parse::<usize>().unwrap();
+                let (tag_keys, [key]) = args.split_at(count) else {
+                    return b"-ERR wrong number of arguments\r\n".to_vec();
+                };
+                if !store.contains_key(key) {
+                    return b":0\r\n".to_vec();
+                }
+                for tag_key in tag_keys {
+                    let entry = store
+                        .entry(tag_key.clone())
+                        .or_insert_with(|| Value::Set(BTreeSet::new()));
+                    if let Value::Set(set) = entry {
+                        set.insert(key.clone());
+                    }
+                }
+                b":1\r\n".to_vec()
+            }
+            ("DEL", keys) => {
+                let removed = keys.iter().filter(|key| store.remove(*key).is_some());
+                format!(":{}\r\n", removed.count()).into_bytes()
+            }
+            // e.g. the client setup
+            _ => b"+OK\r\n".to_vec(),
+        }
+    }
+
+    async fn init(encoding: CacheEncoding) -> RedisCache {
+        RedisCache::new(&blueprint::RedisCache {
+            url: start_server().await,
+            encoding,
+            key_prefix: "test:".to_string(),
+            pool_size: 2,
+        })
+    }
+
+    fn value() -> ConstValue {
+        ConstValue::from_json(serde_json::json!({"id": 1, "name": "Leanne", "tags": [true, 1.5]}))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_get() {
+        for encoding in [CacheEncoding::JSON, CacheEncoding::MessagePack] {
+            let cache = init(encoding).await;
+            let ttl = NonZeroU64::new(1000).unwrap();
+
+            assert_eq!(cache.get(&IoId::new(1)).await.unwrap(), None);
+            cache.set(IoId::new(1), value(), ttl).await.unwrap();
+
+            assert_eq!(cache.get(&IoId::new(1)).await.unwrap(), Some(value()));
+            assert_eq!(cache.hit_rate(), Some(0.5));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge() {
+        let cache = init(CacheEncoding::JSON).await;
+        let ttl = NonZeroU64::new(1000).unwrap();
+
+        cache.set(IoId::new(1), value(), ttl).await.unwrap();
+        cache.set(IoId::new(2), value(), ttl).await.unwrap();
+        cache
+            .tag(&IoId::new(1), &["user:1".to_string()])
+            .await
+            .unwrap();
+        cache
+            .tag(&IoId::new(3), &["user:1".to_string()])
+            .await
+            .unwrap();
+        cache.purge("user:1").await.unwrap();
+
+        assert_eq!(cache.get(&IoId::new(1)).await.unwrap(), None);
+        assert_eq!(cache.get(&IoId::new(2)).await.unwrap(), Some(value()));
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use anyhow::Result;
+use futures_util::{StreamExt, TryStreamExt};
 use http_cache_reqwest::{Cache, CacheMode, HttpCache, HttpCacheOptions};
 use hyper::body::Bytes;
 use once_cell::sync::Lazy;
@@ -13,13 +14,14 @@ use opentelemetry_semantic_conventions::trace::{
 };
 use reqwest::Client;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use tailcall_http_cache::HttpCacheManager;
+use tailcall_http_cache::{DiskStore, HttpCacheManager};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use super::HttpIO;
 use crate::core::blueprint::telemetry::Telemetry;
 use crate::core::blueprint::Upstream;
 use crate::core::http::Response;
+use crate::core::ByteStream;
 
 static HTTP_CLIENT_REQUEST_COUNT: Lazy<Counter<u64>> = Lazy::new(|| {
     let meter = opentelemetry::global::meter("http_request");
@@ -103,6 +105,23 @@ impl NativeHttp {
             builder = builder.http2_prior_knowledge();
         }
 
+        // Add the client certificate and the trusted CA certificates
+        if let Some(ref tls) = upstream.tls {
+            if let Some(ref identity) = tls.identity {
+                builder = builder.identity(
+                    reqwest::Identity::from_pem(identity)
+                        .expect("Failed to set client certificate in http client"),
+                );
+            }
+            // every certificate of a bundle is trusted
+            for ca in tls.ca.iter() {
+                builder = builder.add_root_certificate(
+                    reqwest::Certificate::from_pem(ca)
+                        .expect("Failed to set CA certificate in http client"),
+                );
+            }
+        }
+
         // Add Http Proxy
         if let Some(ref proxy) = upstream.proxy {
             builder = builder.proxy(
@@ -114,9 +133,16 @@ impl NativeHttp {
         let mut client = ClientBuilder::new(builder.build().expect("Failed to build client"));
 
         if upstream.http_cache > 0 {
+            let mut manager = HttpCacheManager::new(upstream.http_cache);
+            if let Some(disk) = &upstream.http_cache_disk {
+                match DiskStore::new(&disk.path, disk.max_size) {
+                    Ok(store) => manager = manager.with_disk(store),
+                    Err(e) => tracing::warn!("HTTP cache kept in memory only: {}", e),
+                }
+            }
             client = client.with(Cache(HttpCache {
                 mode: CacheMode::Default,
-                manager: HttpCacheManager::new(upstream.http_cache),
+                manager,
                 options: HttpCacheOptions::default(),
             }))
         }
@@ -183,6 +209,31 @@ impl HttpIO for NativeHttp {
         )
         .await?)
     }
+
+    async fn execute_stream(&self, mut request: reqwest::Request) -> Result<Response<ByteStream>> {
+        if self.http2_only {
+            *request.version_mut() = reqwest::Version::HTTP_2;
+        }
+
+        tracing::info!(
+            "{} {} {:?} (stream)",
+            request.method(),
+            request.url(),
+            request.version()
+        );
+        let response = self
+            .client
+            .execute(request)
+            .await?
+            .error_for_status()
+            .map_err(|err| err.without_url())?;
+
+        Ok(Response {
+            status: response.status(),
+            headers: response.headers().to_owned(),
+            body: response.bytes_stream().map_err(anyhow::Error::from).boxed(),
+        })
+    }
 }
 
 #[cfg(test)]
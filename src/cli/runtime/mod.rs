@@ -1,19 +1,22 @@
+mod cache;
 mod env;
 mod file;
 mod http;
 
+use std::collections::HashMap;
 use std::fs;
-use std::hash::Hash;
 use std::sync::Arc;
 
 pub use http::NativeHttp;
 use inquire::{Confirm, Select};
 
 use crate::core::blueprint::Blueprint;
-use crate::core::cache::InMemoryCache;
-use crate::core::runtime::TargetRuntime;
+use crate::core::cache::{estimate_size, InMemoryCache};
+use crate::core::http::load_balancer::{BalancedHttp, Balancer};
+use crate::core::rate_limit::InMemoryRateLimitStore;
+use crate::core::runtime::{TargetRuntime, UpstreamIO};
 use crate::core::worker::{Command, Event};
-use crate::core::{blueprint, EnvIO, FileIO, HttpIO, WorkerIO};
+use crate::core::{blueprint, EntityCache, EnvIO, FileIO, HttpIO, WorkerIO};
 
 // Provides access to env in native rust environment
 fn init_env() -> Arc<dyn EnvIO> {
@@ -65,20 +68,68 @@ fn init_http2_only(blueprint: &Blueprint) -> Arc<dyn HttpIO> {
     ))
 }
 
-fn init_in_memory_cache<K: Hash + Eq, V: Clone>() -> InMemoryCache<K, V> {
-    InMemoryCache::new()
+// Provides the state shared by the load balanced clients of an upstream
+fn init_balancer(upstream: &blueprint::Upstream) -> Option<Arc<Balancer>> {
+    let load_balancer = upstream.load_balancer.as_ref()?;
+    Balancer::new(upstream.base_url.as_deref()?, load_balancer).map(Arc::new)
+}
+
+// Sends the requests of a client to the endpoints of the load balancer, if any
+fn balance(http: Arc<dyn HttpIO>, balancer: &Option<Arc<Balancer>>) -> Arc<dyn HttpIO> {
+    match balancer {
+        Some(balancer) => Arc::new(BalancedHttp::new(http, balancer.clone())),
+        None => http,
+    }
+}
+
+// Provides a pair of clients for every named upstream
+fn init_upstreams(blueprint: &Blueprint) -> Arc<HashMap<String, UpstreamIO>> {
+    let upstreams = blueprint.upstreams.iter().map(|(name, upstream)| {
+        let balancer = init_balancer(upstream);
+        let http = balance(
+            Arc::new(http::NativeHttp::init(upstream, &blueprint.telemetry)),
+            &balancer,
+        );
+        let http2_only = balance(
+            Arc::new(http::NativeHttp::init(
+                &upstream.clone().http2_only(true),
+                &blueprint.telemetry,
+            )),
+            &balancer,
+        );
+        (name.clone(), UpstreamIO { http, http2_only })
+    });
+
+    Arc::new(upstreams.collect())
+}
+
+// Provides the entity cache, stored in redis or in memory as configured
+fn init_entity_cache(entity_cache: &blueprint::EntityCache) -> Arc<EntityCache> {
+    if let Some(redis) = &entity_cache.redis {
+        return Arc::new(cache::RedisCache::new(redis));
+    }
+
+    let cache = InMemoryCache::with_capacity(entity_cache.max_entries, entity_cache.eviction);
+    match entity_cache.max_size {
+        Some(max_size) => Arc::new(cache.with_max_size(max_size, estimate_size)),
+        None => Arc::new(cache),
+    }
 }
 
 pub fn init(blueprint: &Blueprint) -> TargetRuntime {
     #[cfg(not(feature = "js"))]
     tracing::warn!("JS capabilities are disabled in this build");
 
+    let balancer = init_balancer(&blueprint.upstream);
+
     TargetRuntime {
-        http: init_http(blueprint),
-        http2_only: init_http2_only(blueprint),
+        http: balance(init_http(blueprint), &balancer),
+        http2_only: balance(init_http2_only(blueprint), &balancer),
+        upstreams: init_upstreams(blueprint),
         env: init_env(),
         file: init_file(),
-        cache: Arc::new(init_in_memory_cache()),
+        cache: init_entity_cache(&blueprint.server.entity_cache),
+        rate_limit: Arc::new(InMemoryRateLimitStore::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: init_http_worker_io(blueprint.server.script.clone()),
         worker: init_resolver_worker_io(blueprint.server.script.clone()),
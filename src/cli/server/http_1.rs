@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
+use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request};
 use tokio::sync::oneshot;
 
 use super::server_config::ServerConfig;
+use super::websocket::handle_request_or_upgrade;
 use crate::core::async_graphql_hyper::{GraphQLBatchRequest, GraphQLRequest};
-use crate::core::http::handle_request;
 use crate::core::Errata;
 
 pub async fn start_http_1(
@@ -13,26 +15,30 @@ pub async fn start_http_1(
     server_up_sender: Option<oneshot::Sender<()>>,
 ) -> anyhow::Result<()> {
     let addr = sc.addr();
-    let make_svc_single_req = make_service_fn(|_conn| {
+    let make_svc_single_req = make_service_fn(|conn: &AddrStream| {
         let state = Arc::clone(&sc);
+        let remote_addr = conn.remote_addr();
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                req.extensions_mut().insert(remote_addr);
+                handle_request_or_upgrade::<GraphQLRequest>(req, state.app_ctx())
             }))
         }
     });
 
-    let make_svc_batch_req = make_service_fn(|_conn| {
+    let make_svc_batch_req = make_service_fn(|conn: &AddrStream| {
         let state = Arc::clone(&sc);
+        let remote_addr = conn.remote_addr();
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                req.extensions_mut().insert(remote_addr);
+                handle_request_or_upgrade::<GraphQLBatchRequest>(req, state.app_ctx())
             }))
         }
     });
     let builder = hyper::Server::try_bind(&addr)
         .map_err(Errata::from)?
-        .http1_pipeline_flush(sc.app_ctx.blueprint.server.pipeline_flush);
+        .http1_pipeline_flush(sc.blueprint.server.pipeline_flush);
     super::log_launch(sc.as_ref());
 
     if let Some(sender) = server_up_sender {
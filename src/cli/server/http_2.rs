@@ -1,17 +1,18 @@
 #![allow(clippy::too_many_arguments)]
 use std::sync::Arc;
 
-use hyper::server::conn::AddrIncoming;
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use hyper::service::{make_service_fn, service_fn};
-use hyper::Server;
+use hyper::{Body, Request, Server};
+use hyper_rustls::acceptor::TlsStream;
 use hyper_rustls::TlsAcceptor;
 use rustls_pki_types::CertificateDer;
 use tokio::sync::oneshot;
 
 use super::server_config::ServerConfig;
+use super::websocket::handle_request_or_upgrade;
 use crate::core::async_graphql_hyper::{GraphQLBatchRequest, GraphQLRequest};
 use crate::core::config::PrivateKey;
-use crate::core::http::handle_request;
 use crate::core::Errata;
 
 pub async fn start_http_2(
@@ -26,20 +27,28 @@ pub async fn start_http_2(
         .with_single_cert(cert, key.into_inner())?
         .with_http2_alpn()
         .with_incoming(incoming);
-    let make_svc_single_req = make_service_fn(|_conn| {
+    let make_svc_single_req = make_service_fn(|conn: &TlsStream| {
         let state = Arc::clone(&sc);
+        let remote_addr = conn.io().map(AddrStream::remote_addr);
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                if let Some(remote_addr) = remote_addr {
+                    req.extensions_mut().insert(remote_addr);
+                }
+                handle_request_or_upgrade::<GraphQLRequest>(req, state.app_ctx())
             }))
         }
     });
 
-    let make_svc_batch_req = make_service_fn(|_conn| {
+    let make_svc_batch_req = make_service_fn(|conn: &TlsStream| {
         let state = Arc::clone(&sc);
+        let remote_addr = conn.io().map(AddrStream::remote_addr);
         async move {
-            Ok::<_, anyhow::Error>(service_fn(move |req| {
-                handle_request::<GraphQLBatchRequest>(req, state.app_ctx.clone())
+            Ok::<_, anyhow::Error>(service_fn(move |mut req: Request<Body>| {
+                if let Some(remote_addr) = remote_addr {
+                    req.extensions_mut().insert(remote_addr);
+                }
+                handle_request_or_upgrade::<GraphQLBatchRequest>(req, state.app_ctx())
             }))
         }
     });
@@ -6,6 +6,7 @@ use tokio::sync::oneshot::{self};
 
 use super::http_1::start_http_1;
 use super::http_2::start_http_2;
+use super::reload::Reloader;
 use super::server_config::ServerConfig;
 use crate::cli::telemetry::init_opentelemetry;
 use crate::core::blueprint::{Blueprint, Http};
@@ -15,11 +16,18 @@ use crate::core::Errata;
 pub struct Server {
     config_module: ConfigModule,
     server_up_sender: Option<oneshot::Sender<()>>,
+    reloader: Option<Reloader>,
 }
 
 impl Server {
     pub fn new(config_module: ConfigModule) -> Self {
-        Self { config_module, server_up_sender: None }
+        Self { config_module, server_up_sender: None, reloader: None }
+    }
+
+    /// Reloads the configuration while the server is running, see [Reloader].
+    pub fn reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = Some(reloader);
+        self
     }
 
     pub fn server_up_receiver(&mut self) -> oneshot::Receiver<()> {
@@ -36,7 +44,14 @@ impl Server {
         let endpoints = self.config_module.extensions().endpoint_set.clone();
         let server_config = Arc::new(ServerConfig::new(blueprint.clone(), endpoints).await?);
 
-        init_opentelemetry(blueprint.telemetry.clone(), &server_config.app_ctx.runtime)?;
+        init_opentelemetry(
+            blueprint.telemetry.clone(),
+            &server_config.app_ctx().runtime,
+        )?;
+
+        if let Some(reloader) = self.reloader {
+            tokio::spawn(reloader.run(server_config.clone()));
+        }
 
         match blueprint.server.http.clone() {
             Http::HTTP2 { cert, key } => {
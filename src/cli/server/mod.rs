@@ -2,9 +2,12 @@ pub mod http_1;
 pub mod http_2;
 pub mod http_server;
 pub mod playground;
+pub mod reload;
 pub mod server_config;
+pub mod websocket;
 
 pub use http_server::Server;
+pub use reload::Reloader;
 
 use self::server_config::ServerConfig;
 
@@ -16,7 +19,7 @@ fn log_launch(sc: &ServerConfig) {
         sc.http_version()
     );
 
-    let gql_slug = sc.app_ctx.blueprint.server.routes.graphql();
+    let gql_slug = sc.blueprint.server.routes.graphql();
 
     let graphiql_url = sc.graphiql_url() + gql_slug;
     let url = playground::build_url(&graphiql_url);
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::Mutex;
+
+use super::server_config::ServerConfig;
+use crate::cli::runtime::init;
+use crate::core::blueprint::Blueprint;
+use crate::core::config::reader::ConfigReader;
+use crate::core::Errata;
+
+/// How often the watched files are checked for modifications.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Last modification time of every watched file, `None` if the file could not
+/// be accessed.
+type Modified = BTreeMap<String, Option<SystemTime>>;
+
+/// Rebuilds the [AppContext](crate::core::app_context::AppContext) of a
+/// running server from its configuration files. A reload is triggered on
+/// SIGHUP and, when watching is enabled, whenever one of the files read while
+/// loading the configuration is modified.
+///
+/// If the new configuration fails to load or validate, the error is logged and
+/// the server keeps serving the previous one. Settings of the listener itself,
+/// such as the address or the HTTP version, can't be changed without a
+/// restart.
+pub struct Reloader {
+    file_paths: Vec<String>,
+    files: Vec<String>,
+    watch: bool,
+}
+
+impl Reloader {
+    /// Creates a reloader for the given config files. `files` are all the files
+    /// that were read to load them, including the linked ones.
+    pub fn new(file_paths: Vec<String>, files: Vec<String>) -> Self {
+        Self { file_paths, files, watch: false }
+    }
+
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    pub async fn run(self, sc: Arc<ServerConfig>) {
+        let modified = Arc::new(Mutex::new(modified_times(&self.files).await));
+        let reloader = Arc::new(self);
+
+        #[cfg(unix)]
+        tokio::spawn(reloader.clone().on_hangup(sc.clone(), modified.clone()));
+
+        if reloader.watch {
+            tracing::info!("👀 Watching {} file(s) for changes", reloader.files.len());
+            reloader.on_change(sc, modified).await;
+        }
+    }
+
+    #[cfg(unix)]
+    async fn on_hangup(self: Arc<Self>, sc: Arc<ServerConfig>, modified: Arc<Mutex<Modified>>) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(error) => {
+                tracing::warn!("Unable to listen for SIGHUP: {}", error);
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            tracing::info!("Received SIGHUP, reloading the configuration");
+            let mut watched = modified.lock().await;
+            *watched = self.reload(&sc, &watched).await;
+        }
+    }
+
+    async fn on_change(&self, sc: Arc<ServerConfig>, modified: Arc<Mutex<Modified>>) {
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+
+            let mut watched = modified.lock().await;
+            let paths = watched.keys().cloned().collect::<Vec<_>>();
+            if modified_times(&paths).await == *watched {
+                continue;
+            }
+
+            tracing::info!("Configuration changed, reloading");
+            *watched = self.reload(&sc, &watched).await;
+        }
+    }
+
+    /// Reloads the configuration and returns the modification times of the
+    /// files to watch from now on.
+    async fn reload(&self, sc: &ServerConfig, modified: &Modified) -> Modified {
+        // A new reader is required for every reload, since it caches the files
+        // it has read.
+        let reader = ConfigReader::init(init(&Blueprint::default()));
+
+        match self.load(&reader, sc).await {
+            Ok(()) => {
+                tracing::info!("🔄 Configuration reloaded");
+                modified_times(&reader.files()).await
+            }
+            Err(error) => {
+                tracing::error!(
+                    "Failed to reload the configuration, keeping the previous one: {}",
+                    Errata::from(error)
+                );
+                // also watch the files that were added before the failure, so that
+                // fixing any of them triggers a new reload
+                let mut paths = modified.keys().cloned().collect::<Vec<_>>();
+                paths.extend(reader.files());
+                modified_times(&paths).await
+            }
+        }
+    }
+
+    async fn load(&self, reader: &ConfigReader, sc: &ServerConfig) -> anyhow::Result<()> {
+        let config_module = reader.read_all(&self.file_paths).await?;
+        let blueprint = Blueprint::try_from(&config_module).map_err(Errata::from)?;
+
+        let server = &blueprint.server;
+        if (server.hostname, server.port)
+            != (sc.blueprint.server.hostname, sc.blueprint.server.port)
+        {
+            tracing::warn!("The server address can't be changed without a restart");
+        }
+
+        let endpoints = config_module.extensions().endpoint_set.clone();
+        let app_ctx = ServerConfig::create_app_ctx(&blueprint, endpoints).await?;
+        sc.set_app_ctx(app_ctx);
+
+        Ok(())
+    }
+}
+
+async fn modified_times(paths: &[String]) -> Modified {
+    let mut modified = Modified::new();
+    for path in paths {
+        let time = tokio::fs::metadata(path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        modified.insert(path.clone(), time);
+    }
+
+    modified
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Mutex as StdMutex;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::config::ConfigModule;
+
+    /// Collects the logs written while it's the default subscriber.
+    #[derive(Clone, Default)]
+    struct Logs(Arc<StdMutex<Vec<u8>>>);
+
+    impl std::io::Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Logs {
+        fn contains(&self, text: &str) -> bool {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).contains(text)
+        }
+    }
+
+    fn config(body: &str) -> String {
+        format!(
+            r#"
+            schema @server {{
+              query: Query
+            }}
+
+            type Query {{
+              hello: String @expr(body: "{body}")
+            }}
+            "#
+        )
+    }
+
+    async fn start(path: &Path) -> (Reloader, Arc<ServerConfig>) {
+        let path = path.to_string_lossy().to_string();
+        let reader = ConfigReader::init(init(&Blueprint::default()));
+        let config_module: ConfigModule = reader.read_all(&[path.clone()]).await.unwrap();
+        let blueprint = Blueprint::try_from(&config_module).unwrap();
+        let endpoints = config_module.extensions().endpoint_set.clone();
+        let sc = ServerConfig::new(blueprint, endpoints).await.unwrap();
+
+        (Reloader::new(vec![path], reader.files()), Arc::new(sc))
+    }
+
+    async fn hello(sc: &ServerConfig) -> serde_json::Value {
+        let response = sc
+            .app_ctx()
+            .execute(async_graphql::Request::new("{ hello }"))
+            .await;
+        serde_json::to_value(response.data).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.graphql");
+        std::fs::write(&path, config("v1")).unwrap();
+        let (reloader, sc) = start(&path).await;
+        let app_ctx = sc.app_ctx();
+
+        std::fs::write(&path, config("v2")).unwrap();
+        reloader.reload(&sc, &Modified::new()).await;
+
+        assert!(!Arc::ptr_eq(&app_ctx, &sc.app_ctx()));
+        assert_eq!(hello(&sc).await, json!({ "hello": "v2" }));
+    }
+
+    #[tokio::test]
+    async fn test_reload_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.graphql");
+        std::fs::write(&path, config("v1")).unwrap();
+        let (reloader, sc) = start(&path).await;
+        let app_ctx = sc.app_ctx();
+
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        std::fs::write(&path, "type Query {").unwrap();
+        reloader.reload(&sc, &Modified::new()).await;
+
+        assert!(Arc::ptr_eq(&app_ctx, &sc.app_ctx()));
+        assert_eq!(hello(&sc).await, json!({ "hello": "v1" }));
+        assert!(logs.contains("Failed to reload the configuration"));
+    }
+
+    #[tokio::test]
+    async fn test_linked_file_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.graphql");
+        let linked = dir.path().join("types.graphql");
+        std::fs::write(
+            &path,
+            r#"
+            schema @server @link(src: "types.graphql", type: Config) {
+              query: Query
+            }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            &linked,
+            "type Query {\n  hello: String @expr(body: \"v1\")\n}\n",
+        )
+        .unwrap();
+        let (reloader, sc) = start(&path).await;
+
+        let watched = reloader.reload(&sc, &Modified::new()).await;
+        let linked = linked.to_string_lossy().to_string();
+        assert!(watched.contains_key(&linked));
+
+        let paths = watched.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(modified_times(&paths).await, watched);
+
+        std::fs::File::options()
+            .write(true)
+            .open(&linked)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert_ne!(modified_times(&paths).await, watched);
+    }
+}
@@ -1,5 +1,5 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use async_graphql_extension_apollo_tracing::ApolloTracing;
 
@@ -11,8 +11,10 @@ use crate::core::rest::{EndpointSet, Unchecked};
 use crate::core::schema_extension::SchemaExtension;
 
 pub struct ServerConfig {
+    /// Blueprint the server was started with. Settings of the listener, such as
+    /// the address or the HTTP version, are always taken from it.
     pub blueprint: Blueprint,
-    pub app_ctx: Arc<AppContext>,
+    app_ctx: RwLock<Arc<AppContext>>,
 }
 
 impl ServerConfig {
@@ -20,7 +22,27 @@ impl ServerConfig {
         blueprint: Blueprint,
         endpoints: EndpointSet<Unchecked>,
     ) -> anyhow::Result<Self> {
-        let mut rt = init(&blueprint);
+        let app_ctx = Self::create_app_ctx(&blueprint, endpoints).await?;
+
+        Ok(Self { app_ctx: RwLock::new(app_ctx), blueprint })
+    }
+
+    /// Returns the context that new requests should be served with.
+    pub fn app_ctx(&self) -> Arc<AppContext> {
+        self.app_ctx.read().unwrap().clone()
+    }
+
+    /// Replaces the context used for new requests. Requests that are already
+    /// being served keep using the context they started with.
+    pub fn set_app_ctx(&self, app_ctx: Arc<AppContext>) {
+        *self.app_ctx.write().unwrap() = app_ctx;
+    }
+
+    pub async fn create_app_ctx(
+        blueprint: &Blueprint,
+        endpoints: EndpointSet<Unchecked>,
+    ) -> anyhow::Result<Arc<AppContext>> {
+        let mut rt = init(blueprint);
 
         let mut extensions = vec![];
 
@@ -36,10 +58,9 @@ impl ServerConfig {
         }
         rt.add_extensions(extensions);
 
-        let endpoints = endpoints.into_checked(&blueprint, rt.clone()).await?;
-        let app_context = Arc::new(AppContext::new(blueprint.clone(), rt, endpoints));
+        let endpoints = endpoints.into_checked(blueprint, rt.clone()).await?;
 
-        Ok(Self { app_ctx: app_context, blueprint })
+        Ok(Arc::new(AppContext::new(blueprint.clone(), rt, endpoints)))
     }
 
     pub fn addr(&self) -> SocketAddr {
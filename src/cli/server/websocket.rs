@@ -0,0 +1,432 @@
+use std::future::Future;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+use async_graphql::http::{WebSocket, WebSocketProtocols, WsMessage, ALL_WEBSOCKET_PROTOCOLS};
+use async_graphql::{Data, Executor, ServerError};
+use futures_util::stream::{BoxStream, Stream};
+use futures_util::{future, SinkExt, StreamExt};
+use hyper::header::{HeaderName, HeaderValue, SEC_WEBSOCKET_PROTOCOL};
+use hyper::{Body, HeaderMap, Request, Response, StatusCode};
+use hyper_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use hyper_tungstenite::tungstenite::protocol::CloseFrame;
+use hyper_tungstenite::tungstenite::Message;
+use hyper_tungstenite::HyperWebsocket;
+use serde::de::DeserializeOwned;
+
+use crate::core::app_context::AppContext;
+use crate::core::async_graphql_hyper::{GraphQLRequest, GraphQLRequestLike};
+use crate::core::http::{client_ip, create_request_context, handle_request, RequestContext};
+use crate::core::jit::JITExecutor;
+use crate::core::rate_limit;
+use crate::core::trusted_documents::NotTrusted;
+
+/// Serves GraphQL over a WebSocket when the request is an upgrade on the
+/// GraphQL endpoint, every other request is handled by [handle_request].
+pub async fn handle_request_or_upgrade<T: DeserializeOwned + GraphQLRequestLike>(
+    req: Request<Body>,
+    app_ctx: Arc<AppContext>,
+) -> anyhow::Result<Response<Body>> {
+    if req.uri().path() == app_ctx.blueprint.server.routes.graphql()
+        && hyper_tungstenite::is_upgrade_request(&req)
+    {
+        upgrade(req, app_ctx)
+    } else {
+        handle_request::<T>(req, app_ctx).await
+    }
+}
+
+fn upgrade(mut req: Request<Body>, app_ctx: Arc<AppContext>) -> anyhow::Result<Response<Body>> {
+    let protocol = req
+        .headers()
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .split(',')
+                .find_map(|protocol| WebSocketProtocols::from_str(protocol.trim()).ok())
+        });
+
+    let Some(protocol) = protocol else {
+        return Ok(Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!(
+                "Unsupported WebSocket sub-protocol, expected one of: {}",
+                ALL_WEBSOCKET_PROTOCOLS.join(", ")
+            )))?);
+    };
+
+    let headers = req.headers().clone();
+    let query = req.uri().query().map(str::to_owned);
+    let client_ip = client_ip(&req);
+    let (mut response, websocket) = hyper_tungstenite::upgrade(&mut req, None)?;
+    response.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(protocol.sec_websocket_protocol()),
+    );
+
+    tokio::spawn(async move {
+        if let Err(error) = serve(websocket, protocol, headers, query, client_ip, app_ctx).await {
+            tracing::error!("WebSocket connection failed: {}", error);
+        }
+    });
+
+    Ok(response)
+}
+
+async fn serve(
+    websocket: HyperWebsocket,
+    protocol: WebSocketProtocols,
+    headers: HeaderMap,
+    query: Option<String>,
+    client_ip: Option<IpAddr>,
+    app_ctx: Arc<AppContext>,
+) -> anyhow::Result<()> {
+    let (mut sink, stream) = websocket.await?.split();
+    let executor = WebSocketExecutor::new(app_ctx, query, client_ip);
+
+    let input = stream
+        .take_while(|message| future::ready(message.is_ok()))
+        .filter_map(|message| {
+            future::ready(match message {
+                Ok(message @ (Message::Text(_) | Message::Binary(_))) => Some(message.into_data()),
+                _ => None,
+            })
+        });
+
+    let output = messages(executor, headers, input, protocol);
+    futures_util::pin_mut!(output);
+
+    while let Some(message) = output.next().await {
+        let message = match message {
+            WsMessage::Text(text) => Message::Text(text),
+            WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame {
+                code: CloseCode::from(code),
+                reason: reason.into(),
+            })),
+        };
+        sink.send(message).await?;
+    }
+
+    Ok(())
+}
+
+/// Messages sent back to the client in response to the ones it sends, in the
+/// negotiated sub-protocol.
+fn messages<S>(
+    executor: WebSocketExecutor,
+    headers: HeaderMap,
+    input: S,
+    protocol: WebSocketProtocols,
+) -> impl Stream<Item = WsMessage>
+where
+    S: Stream<Item = Vec<u8>> + Send + Unpin + 'static,
+{
+    let connection_headers = executor.headers.clone();
+
+    WebSocket::new(executor, input, protocol).on_connection_init(move |payload| async move {
+        let _ = connection_headers.set(merge_headers(headers, &payload));
+        Ok(Data::default())
+    })
+}
+
+/// Browsers can't set custom headers on the upgrade request, so clients pass
+/// them in the `connection_init` payload instead, either at the top level or
+/// nested under `headers`. Those take precedence over the upgrade headers.
+fn merge_headers(mut headers: HeaderMap, payload: &serde_json::Value) -> HeaderMap {
+    let params = payload.get("headers").unwrap_or(payload);
+
+    if let Some(params) = params.as_object() {
+        for (key, value) in params {
+            let name = HeaderName::from_bytes(key.as_bytes()).ok();
+            let value = value
+                .as_str()
+                .and_then(|value| HeaderValue::from_str(value).ok());
+
+            if let (Some(name), Some(value)) = (name, value) {
+                headers.insert(name, value);
+            }
+        }
+    }
+
+    headers
+}
+
+/// Executes the operations of a single WebSocket connection. Every operation
+/// gets its own [RequestContext](crate::core::http::RequestContext), created
+/// from the headers received when the connection was initialised and the query
+/// string of the upgrade request. The rate limit of the server applies to
+/// every operation rather than to the connection.
+#[derive(Clone)]
+struct WebSocketExecutor {
+    app_ctx: Arc<AppContext>,
+    headers: Arc<OnceLock<HeaderMap>>,
+    query: Option<String>,
+    client_ip: Option<IpAddr>,
+}
+
+impl WebSocketExecutor {
+    fn new(app_ctx: Arc<AppContext>, query: Option<String>, client_ip: Option<IpAddr>) -> Self {
+        Self {
+            app_ctx,
+            headers: Arc::new(OnceLock::new()),
+            query,
+            client_ip,
+        }
+    }
+
+    fn headers(&self) -> HeaderMap {
+        self.headers.get().cloned().unwrap_or_default()
+    }
+
+    fn request_context(&self) -> Arc<RequestContext> {
+        Arc::new(
+            create_request_context(&self.headers(), &self.app_ctx)
+                .query(self.query.clone())
+                .client_ip(self.client_ip),
+        )
+    }
+
+    fn jit_executor(
+        &self,
+        request: &mut GraphQLRequest,
+        req_ctx: Arc<RequestContext>,
+    ) -> JITExecutor {
+        let is_query = request.is_query();
+        let operation_id = request.operation_id(&self.headers());
+
+        JITExecutor::new(self.app_ctx.clone(), req_ctx, is_query, operation_id)
+    }
+
+    /// Replaces the query of the request with the trusted document it refers
+    /// to, when only trusted documents can be executed.
+    fn resolve_trusted(&self, request: &mut GraphQLRequest) -> Result<(), NotTrusted> {
+        match self.app_ctx.trusted_documents.as_ref() {
+            Some(documents) => documents.resolve(&mut request.0),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Executor for WebSocketExecutor {
+    fn execute(
+        &self,
+        request: async_graphql::Request,
+    ) -> impl Future<Output = async_graphql::Response> + Send {
+        let mut request = GraphQLRequest(request);
+        let trusted = self.resolve_trusted(&mut request);
+        let req_ctx = self.request_context();
+        let executor = self.jit_executor(&mut request, req_ctx.clone());
+
+        async move {
+            if let Err(error) = trusted {
+                return async_graphql::Response::from_errors(vec![error.into()]);
+            }
+            if let Err(error) = rate_limit::check_request(&req_ctx).await {
+                return async_graphql::Response::from_errors(vec![error.into()]);
+            }
+            req_ctx.auth_ctx.verify_for_templates(&req_ctx).await;
+
+            executor.execute(request.0).await
+        }
+    }
+
+    fn execute_stream(
+        &self,
+        request: async_graphql::Request,
+        data: Option<Arc<Data>>,
+    ) -> BoxStream<'static, async_graphql::Response> {
+        let mut request = GraphQLRequest(request);
+        let trusted = self
+            .resolve_trusted(&mut request)
+            .map_err(ServerError::from);
+        let req_ctx = self.request_context();
+        let executor = self.jit_executor(&mut request, req_ctx.clone());
+
+        futures_util::stream::once(async move {
+            let allowed = match trusted {
+                Ok(()) => rate_limit::check_request(&req_ctx)
+                    .await
+                    .map_err(ServerError::from),
+                Err(error) => Err(error),
+            };
+            match allowed {
+                Ok(()) => {
+                    req_ctx.auth_ctx.verify_for_templates(&req_ctx).await;
+                    executor.execute_stream(request.0, data)
+                }
+                Err(error) => {
+                    let response = async_graphql::Response::from_errors(vec![error]);
+                    futures_util::stream::once(async move { response }).boxed()
+                }
+            }
+        })
+        .flatten()
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use hyper::body::Bytes;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::Response as UpstreamResponse;
+    use crate::core::rest::EndpointSet;
+    use crate::core::runtime::test::init;
+    use crate::core::valid::Validator;
+    use crate::core::HttpIO;
+
+    /// Streams a single event from the upstream.
+    struct SseHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for SseHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<UpstreamResponse<Bytes>> {
+            Ok(UpstreamResponse {
+                status: reqwest::StatusCode::OK,
+                headers: Default::default(),
+                body: Bytes::from("data: {\"count\": 1}\n\n"),
+            })
+        }
+    }
+
+    fn executor(server: &str) -> WebSocketExecutor {
+        let sdl = format!(
+            r#"
+            schema {server} @upstream(baseURL: "http://localhost:3000") {{
+              query: Query
+              subscription: Subscription
+            }}
+
+            type Query {{
+              hello: String @expr(body: "world")
+            }}
+
+            type Subscription {{
+              counter: Counter @http(path: "/counter")
+            }}
+
+            type Counter {{
+              count: Int
+            }}
+            "#
+        );
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let mut runtime = init(None);
+        runtime.http = Arc::new(SseHttp);
+        let app_ctx = Arc::new(AppContext::new(blueprint, runtime, EndpointSet::default()));
+
+        WebSocketExecutor::new(app_ctx, None, None)
+    }
+
+    /// Sends the messages over a connection that stays open, and returns the
+    /// first `count` messages received.
+    async fn exchange(executor: WebSocketExecutor, input: Vec<Value>, count: usize) -> Vec<Value> {
+        let input = stream::iter(
+            input
+                .into_iter()
+                .map(|message| message.to_string().into_bytes()),
+        )
+        .chain(stream::pending());
+
+        messages(
+            executor,
+            HeaderMap::new(),
+            input,
+            WebSocketProtocols::GraphQLWS,
+        )
+        .take(count)
+        .map(|message| match message {
+            WsMessage::Text(text) => serde_json::from_str(&text).unwrap(),
+            WsMessage::Close(code, reason) => panic!("closed with {code}: {reason}"),
+        })
+        .collect()
+        .await
+    }
+
+    fn subscribe(id: &str, query: &str) -> Value {
+        json!({ "id": id, "type": "subscribe", "payload": { "query": query } })
+    }
+
+    #[tokio::test]
+    async fn test_connection_init() {
+        let input = vec![json!({ "type": "connection_init", "payload": {} })];
+        let output = exchange(executor("@server"), input, 1).await;
+
+        assert_eq!(output, vec![json!({ "type": "connection_ack" })]);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let input = vec![
+            json!({ "type": "connection_init" }),
+            subscribe("1", "subscription { counter { count } }"),
+        ];
+        let output = exchange(executor("@server"), input, 3).await;
+
+        assert_eq!(
+            output,
+            vec![
+                json!({ "type": "connection_ack" }),
+                json!({
+                    "id": "1",
+                    "type": "next",
+                    "payload": { "data": { "counter": { "count": 1 } } }
+                }),
+                json!({ "id": "1", "type": "complete" }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_operation() {
+        let server = "@server(rateLimit: {capacity: 1, refillInterval: 60000})";
+        let input = vec![
+            json!({ "type": "connection_init" }),
+            subscribe("1", "{ hello }"),
+            subscribe("2", "{ hello }"),
+        ];
+        let output = exchange(executor(server), input, 5).await;
+
+        // both operations share the bucket of the connection
+        let payloads: Vec<&Value> = output
+            .iter()
+            .filter(|message| message["type"] == "next")
+            .map(|message| &message["payload"])
+            .collect();
+        assert_eq!(payloads.len(), 2);
+        assert!(payloads
+            .iter()
+            .any(|payload| payload["data"] == json!({ "hello": "world" })));
+        assert!(payloads
+            .iter()
+            .any(|payload| payload["errors"][0]["extensions"]["code"] == "RATE_LIMITED"));
+    }
+
+    #[test]
+    fn test_merge_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer upgrade".parse().unwrap());
+        headers.insert("x-tenant", "acme".parse().unwrap());
+
+        let payload = json!({ "Authorization": "Bearer init", "retries": 3 });
+        let actual = merge_headers(headers.clone(), &payload);
+
+        assert_eq!(actual.get("authorization").unwrap(), "Bearer init");
+        assert_eq!(actual.get("x-tenant").unwrap(), "acme");
+        assert!(actual.get("retries").is_none());
+
+        let payload = json!({ "headers": { "x-tenant": "globex" } });
+        let actual = merge_headers(headers, &payload);
+
+        assert_eq!(actual.get("authorization").unwrap(), "Bearer upgrade");
+        assert_eq!(actual.get("x-tenant").unwrap(), "globex");
+    }
+}
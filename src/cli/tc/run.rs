@@ -36,8 +36,8 @@ pub async fn run() -> Result<()> {
 
 async fn run_command(cli: Cli, config_reader: ConfigReader, runtime: TargetRuntime) -> Result<()> {
     match cli.command {
-        Command::Start { file_paths } => {
-            start::start_command(file_paths, &config_reader).await?;
+        Command::Start { file_paths, watch } => {
+            start::start_command(file_paths, watch, &config_reader).await?;
         }
         Command::Check { file_paths, n_plus_one_queries, schema, format } => {
             check::check_command(
@@ -10,11 +10,14 @@ use crate::core::data_loader::{DataLoader, DedupeResult};
 use crate::core::graphql::GraphqlDataLoader;
 use crate::core::grpc;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::resilience::LoaderResilience;
+use crate::core::http::{CircuitBreakers, DataLoaderRequest, HttpDataLoader};
 use crate::core::ir::model::{DataLoaderId, IoId, IO, IR};
 use crate::core::ir::Error;
+use crate::core::persisted_query::PersistedQueries;
 use crate::core::rest::{Checked, EndpointSet};
 use crate::core::runtime::TargetRuntime;
+use crate::core::trusted_documents::TrustedDocuments;
 
 pub struct AppContext {
     pub schema: dynamic::Schema,
@@ -26,7 +29,14 @@ pub struct AppContext {
     pub endpoints: EndpointSet<Checked>,
     pub auth_ctx: Arc<GlobalAuthContext>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    pub revalidate_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    pub circuit_breakers: Arc<CircuitBreakers>,
     pub dedupe_operation_handler: DedupeResult<OperationId, Arc<async_graphql::Response>, Error>,
+    pub trusted_documents: Option<TrustedDocuments>,
+    pub persisted_queries: Option<PersistedQueries>,
+    // whether types or fields are rate limited, in which case the requests
+    // executed without the JIT engine are planned to take their tokens
+    pub has_field_rate_limits: bool,
 }
 
 impl AppContext {
@@ -38,10 +48,21 @@ impl AppContext {
         let mut http_data_loaders = vec![];
         let mut gql_data_loaders = vec![];
         let mut grpc_data_loaders = vec![];
+        let mut reads_claims = false;
+        let mut has_field_rate_limits = false;
+        let circuit_breakers = Arc::new(CircuitBreakers::default());
 
         for def in blueprint.definitions.iter_mut() {
             if let Definition::Object(def) = def {
                 for field in &mut def.fields {
+                    has_field_rate_limits |= field.rate_limit.is_some();
+                    field.map_expr(|expr| {
+                        expr.modify(&mut |expr| {
+                            reads_claims |= expr.reads_claims();
+                            None
+                        })
+                    });
+
                     let upstream_batch = &blueprint.upstream.batch;
                     field.map_expr(|expr| {
                         expr.modify(&mut |expr| match expr {
@@ -52,15 +73,21 @@ impl AppContext {
                                     http_filter,
                                     is_list,
                                     dedupe,
+                                    resilience,
+                                    upstream,
                                     ..
                                 } => {
                                     let is_list = *is_list;
                                     let dedupe = *dedupe;
                                     let data_loader = HttpDataLoader::new(
-                                        runtime.clone(),
+                                        runtime.with_upstream(upstream.as_deref()),
                                         group_by.clone(),
                                         is_list,
                                     )
+                                    .resilience(LoaderResilience {
+                                        resilience: resilience.clone(),
+                                        circuit_breakers: circuit_breakers.clone(),
+                                    })
                                     .to_data_loader(upstream_batch.clone().unwrap_or_default());
 
                                     let result = Some(IR::IO(IO::Http {
@@ -70,6 +97,8 @@ impl AppContext {
                                         http_filter: http_filter.clone(),
                                         is_list,
                                         dedupe,
+                                        resilience: resilience.clone(),
+                                        upstream: upstream.clone(),
                                     }));
 
                                     http_data_loaders.push(data_loader);
@@ -77,13 +106,25 @@ impl AppContext {
                                     result
                                 }
 
-                                IO::GraphQL { req_template, field_name, batch, dedupe, .. } => {
+                                IO::GraphQL {
+                                    req_template,
+                                    field_name,
+                                    batch,
+                                    dedupe,
+                                    resilience,
+                                    upstream,
+                                    ..
+                                } => {
                                     let dedupe = *dedupe;
-                                    let graphql_data_loader =
-                                        GraphqlDataLoader::new(runtime.clone(), *batch)
-                                            .into_data_loader(
-                                                upstream_batch.clone().unwrap_or_default(),
-                                            );
+                                    let graphql_data_loader = GraphqlDataLoader::new(
+                                        runtime.with_upstream(upstream.as_deref()),
+                                        *batch,
+                                    )
+                                    .resilience(LoaderResilience {
+                                        resilience: resilience.clone(),
+                                        circuit_breakers: circuit_breakers.clone(),
+                                    })
+                                    .into_data_loader(upstream_batch.clone().unwrap_or_default());
 
                                     let result = Some(IR::IO(IO::GraphQL {
                                         req_template: req_template.clone(),
@@ -91,6 +132,8 @@ impl AppContext {
                                         batch: *batch,
                                         dl_id: Some(DataLoaderId::new(gql_data_loaders.len())),
                                         dedupe,
+                                        resilience: resilience.clone(),
+                                        upstream: upstream.clone(),
                                     }));
 
                                     gql_data_loaders.push(graphql_data_loader);
@@ -98,12 +141,23 @@ impl AppContext {
                                     result
                                 }
 
-                                IO::Grpc { req_template, group_by, dedupe, .. } => {
+                                IO::Grpc {
+                                    req_template,
+                                    group_by,
+                                    dedupe,
+                                    resilience,
+                                    upstream,
+                                    ..
+                                } => {
                                     let dedupe = *dedupe;
                                     let data_loader = GrpcDataLoader {
-                                        runtime: runtime.clone(),
+                                        runtime: runtime.with_upstream(upstream.as_deref()),
                                         operation: req_template.operation.clone(),
                                         group_by: group_by.clone(),
+                                        resilience: LoaderResilience {
+                                            resilience: resilience.clone(),
+                                            circuit_breakers: circuit_breakers.clone(),
+                                        },
                                     };
                                     let data_loader = data_loader.into_data_loader(
                                         upstream_batch.clone().unwrap_or_default(),
@@ -114,6 +168,8 @@ impl AppContext {
                                         group_by: group_by.clone(),
                                         dl_id: Some(DataLoaderId::new(grpc_data_loaders.len())),
                                         dedupe,
+                                        resilience: resilience.clone(),
+                                        upstream: upstream.clone(),
                                     }));
 
                                     grpc_data_loaders.push(data_loader);
@@ -134,7 +190,17 @@ impl AppContext {
         let schema = blueprint
             .to_schema_with(SchemaModifiers::default().extensions(runtime.extensions.clone()));
         let auth = blueprint.server.auth.clone();
-        let auth_ctx = GlobalAuthContext::new(auth);
+        let auth_ctx = GlobalAuthContext::new(auth, reads_claims);
+        let trusted_documents = blueprint
+            .server
+            .trusted_documents
+            .as_ref()
+            .map(|documents| TrustedDocuments::new(&blueprint, documents));
+        let persisted_queries = blueprint
+            .server
+            .persisted_queries
+            .as_ref()
+            .map(PersistedQueries::new);
 
         AppContext {
             schema,
@@ -146,7 +212,12 @@ impl AppContext {
             endpoints,
             auth_ctx: Arc::new(auth_ctx),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
+            revalidate_handler: Arc::new(DedupeResult::new(false)),
+            circuit_breakers,
             dedupe_operation_handler: DedupeResult::new(false),
+            trusted_documents,
+            persisted_queries,
+            has_field_rate_limits,
         }
     }
 
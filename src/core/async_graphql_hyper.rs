@@ -3,7 +3,9 @@ use std::hash::{Hash, Hasher};
 
 use anyhow::Result;
 use async_graphql::parser::types::{ExecutableDocument, OperationType};
-use async_graphql::{BatchResponse, Executor, Value};
+use async_graphql::{BatchResponse, Executor, ServerError, Value};
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
 use http::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
 use http::{Response, StatusCode};
 use hyper::Body;
@@ -11,6 +13,8 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use tailcall_hasher::TailcallHasher;
 
+use crate::core::http::encode_event;
+
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub struct OperationId(u64);
 
@@ -21,8 +25,15 @@ pub trait GraphQLRequestLike: Hash + Send {
     where
         E: Executor;
 
+    fn execute_stream<E>(self, executor: &E) -> GraphQLStreamResponse
+    where
+        E: Executor;
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument>;
 
+    /// The individual requests, many of them for a batch.
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request>;
+
     fn is_query(&mut self) -> bool {
         self.parse_query()
             .map(|a| {
@@ -35,6 +46,35 @@ pub trait GraphQLRequestLike: Hash + Send {
             .unwrap_or(false)
     }
 
+    fn is_subscription(&mut self) -> bool {
+        self.parse_query()
+            .map(|a| {
+                a.operations
+                    .iter()
+                    .any(|(_, operation)| operation.node.ty == OperationType::Subscription)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Checks that the operations selected by the requests are all queries.
+    /// Documents that fail to parse are left for the execution to report.
+    fn selects_queries(&mut self) -> bool {
+        self.requests_mut().into_iter().all(|request| {
+            let operation_name = request.operation_name.clone();
+            match request.parsed_query() {
+                Ok(doc) => doc
+                    .operations
+                    .iter()
+                    .filter(|(name, _)| {
+                        operation_name.is_none()
+                            || name.map(|name| name.as_str()) == operation_name.as_deref()
+                    })
+                    .all(|(_, operation)| operation.node.ty == OperationType::Query),
+                Err(_) => true,
+            }
+        })
+    }
+
     fn operation_id(&self, headers: &HeaderMap) -> OperationId {
         let mut hasher = TailcallHasher::default();
         let state = &mut hasher;
@@ -81,9 +121,24 @@ impl GraphQLRequestLike for GraphQLBatchRequest {
         GraphQLResponse(executor.execute_batch(self.0).await)
     }
 
+    fn execute_stream<E>(self, _: &E) -> GraphQLStreamResponse
+    where
+        E: Executor,
+    {
+        let response = async_graphql::Response::from_errors(vec![ServerError::new(
+            "Subscriptions are not supported in batch requests",
+            None,
+        )]);
+        GraphQLStreamResponse(stream::once(async move { response }).boxed())
+    }
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         None
     }
+
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request> {
+        self.0.iter_mut().collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,9 +170,21 @@ impl GraphQLRequestLike for GraphQLRequest {
         GraphQLResponse(executor.execute(self.0).await.into())
     }
 
+    /// Shortcut method to subscribe to the request on the executor.
+    fn execute_stream<E>(self, executor: &E) -> GraphQLStreamResponse
+    where
+        E: Executor,
+    {
+        GraphQLStreamResponse(executor.execute_stream(self.0, None))
+    }
+
     fn parse_query(&mut self) -> Option<&ExecutableDocument> {
         self.0.parsed_query().ok()
     }
+
+    fn requests_mut(&mut self) -> Vec<&mut async_graphql::Request> {
+        vec![&mut self.0]
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -133,8 +200,10 @@ impl From<async_graphql::Response> for GraphQLResponse {
     }
 }
 
-impl From<GraphQLQuery> for GraphQLRequest {
-    fn from(query: GraphQLQuery) -> Self {
+impl TryFrom<GraphQLQuery> for GraphQLRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(query: GraphQLQuery) -> Result<Self, Self::Error> {
         let mut request = async_graphql::Request::new(query.query);
 
         if let Some(operation_name) = query.operation_name {
@@ -142,20 +211,29 @@ impl From<GraphQLQuery> for GraphQLRequest {
         }
 
         if let Some(variables) = query.variables {
-            let value = serde_json::from_str(&variables).unwrap_or_default();
+            let value = serde_json::from_str(&variables)?;
             let variables = async_graphql::Variables::from_json(value);
             request = request.variables(variables);
         }
 
-        GraphQLRequest(request)
+        if let Some(extensions) = query.extensions {
+            request.extensions = serde_json::from_str(&extensions)?;
+        }
+
+        Ok(GraphQLRequest(request))
     }
 }
 
-#[derive(Debug)]
+/// A request sent in the query string of a GET request, with the variables
+/// and extensions encoded as JSON.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GraphQLQuery {
+    #[serde(default)]
     query: String,
     operation_name: Option<String>,
     variables: Option<String>,
+    extensions: Option<String>,
 }
 
 impl GraphQLQuery {
@@ -164,14 +242,43 @@ impl GraphQLQuery {
     where
         E: Executor,
     {
-        let request: GraphQLRequest = self.into();
-        request.execute(executor).await
+        match GraphQLRequest::try_from(self) {
+            Ok(request) => request.execute(executor).await,
+            Err(err) => GraphQLResponse::from(async_graphql::Response::from_errors(vec![
+                ServerError::new(format!("Unexpected GraphQL Request: {}", err), None),
+            ])),
+        }
     }
 }
 
 static APPLICATION_JSON: Lazy<HeaderValue> =
     Lazy::new(|| HeaderValue::from_static("application/json"));
 
+static TEXT_EVENT_STREAM: Lazy<HeaderValue> =
+    Lazy::new(|| HeaderValue::from_static("text/event-stream"));
+
+/// Responses of a subscription, delivered to the client as
+/// `text/event-stream` following the distinct connections mode of the
+/// GraphQL over SSE protocol.
+pub struct GraphQLStreamResponse(pub BoxStream<'static, async_graphql::Response>);
+
+impl GraphQLStreamResponse {
+    pub fn into_response(self) -> Result<Response<hyper::Body>> {
+        let events = self
+            .0
+            .map(|response| {
+                serde_json::to_string(&response).map(|data| encode_event("next", &data))
+            })
+            .chain(stream::once(async { Ok(encode_event("complete", "")) }));
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, TEXT_EVENT_STREAM.as_ref())
+            .header(CACHE_CONTROL, "no-cache")
+            .body(Body::wrap_stream(events))?)
+    }
+}
+
 impl GraphQLResponse {
     fn build_response(&self, status: StatusCode, body: Body) -> Result<Response<Body>> {
         let mut response = Response::builder()
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use headers::{Cookie, HeaderMapExt};
+use sha2::{Digest, Sha256};
+
+use super::error::Error;
+use super::jwt::jwt_verify::JwtClaim;
+use super::verification::Verification;
+use super::verify::Verify;
+use crate::core::blueprint;
+use crate::core::http::RequestContext;
+
+/// Verifies the static API key sent with the request against the hashes of
+/// the known keys. The metadata of the matched key becomes the claims of the
+/// request.
+pub struct ApiKeyVerifier {
+    id: Option<String>,
+    source: blueprint::ApiKeySource,
+    keys: HashMap<String, JwtClaim>,
+}
+
+impl ApiKeyVerifier {
+    pub fn new(options: blueprint::ApiKeys) -> Self {
+        let keys = options
+            .keys
+            .into_iter()
+            .filter_map(|(hash, claims)| Some((hash, JwtClaim::try_from(claims).ok()?)))
+            .collect();
+
+        Self { id: options.id, source: options.source, keys }
+    }
+
+    fn resolve_key(&self, request: &RequestContext) -> Option<String> {
+        match &self.source {
+            blueprint::ApiKeySource::Header(name) => request
+                .request_headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned),
+            blueprint::ApiKeySource::Query(name) => {
+                let query = request.query.as_deref()?;
+
+                serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+                    .ok()?
+                    .into_iter()
+                    .find_map(|(key, value)| (key == *name).then_some(value))
+            }
+            blueprint::ApiKeySource::Cookie(name) => request
+                .request_headers
+                .typed_get::<Cookie>()?
+                .get(name)
+                .map(str::to_owned),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Verify for ApiKeyVerifier {
+    async fn verify(&self, request: &RequestContext) -> Verification {
+        let Some(key) = self.resolve_key(request) else {
+            return Verification::fail(Error::Missing);
+        };
+
+        let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+
+        match self.keys.get(&hash) {
+            Some(claims) => {
+                request
+                    .auth_ctx
+                    .set_claims(self.id.as_deref(), claims.clone());
+                Verification::succeed()
+            }
+            None => Verification::fail(Error::Invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderValue, COOKIE};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn verifier(source: blueprint::ApiKeySource) -> ApiKeyVerifier {
+        let Value::Object(claims) = json!({ "sub": "partner", "scp": ["read"] }) else {
+            unreachable!()
+        };
+
+        ApiKeyVerifier::new(blueprint::ApiKeys {
+            id: None,
+            source,
+            keys: [(format!("{:x}", Sha256::digest(b"secret-key")), claims)]
+                .into_iter()
+                .collect(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_header() {
+        let verifier = verifier(blueprint::ApiKeySource::Header("x-api-key".to_owned()));
+
+        let mut request = RequestContext::default();
+        request
+            .request_headers
+            .insert("x-api-key", HeaderValue::from_static("secret-key"));
+
+        assert_eq!(verifier.verify(&request).await, Verification::succeed());
+        assert_eq!(request.auth_ctx.claims().unwrap().scopes(), vec!["read"]);
+
+        let mut request = RequestContext::default();
+        request
+            .request_headers
+            .insert("x-api-key", HeaderValue::from_static("wrong-key"));
+
+        assert_eq!(
+            verifier.verify(&request).await,
+            Verification::fail(Error::Invalid)
+        );
+
+        assert_eq!(
+            verifier.verify(&RequestContext::default()).await,
+            Verification::fail(Error::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query() {
+        let verifier = verifier(blueprint::ApiKeySource::Query("api_key".to_owned()));
+        let request = RequestContext::default().query(Some("a=1&api_key=secret-key".to_owned()));
+
+        assert_eq!(verifier.verify(&request).await, Verification::succeed());
+        assert_eq!(
+            request.auth_ctx.claim(&["sub"]),
+            Some(Value::from("partner"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cookie() {
+        let verifier = verifier(blueprint::ApiKeySource::Cookie("api_key".to_owned()));
+
+        let mut request = RequestContext::default();
+        request.request_headers.insert(
+            COOKIE,
+            HeaderValue::from_static("session=1; api_key=secret-key"),
+        );
+
+        assert_eq!(verifier.verify(&request).await, Verification::succeed());
+    }
+}
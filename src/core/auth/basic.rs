@@ -58,7 +58,7 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
 
     impl blueprint::Basic {
         pub fn test_value() -> Self {
-            Self { htpasswd: HTPASSWD_TEST.to_owned() }
+            Self { id: None, htpasswd: HTPASSWD_TEST.to_owned() }
         }
     }
 
@@ -129,6 +129,6 @@ testuser3:{SHA}Y2fEjdGT1W6nsLqtJbGUVeUp9e4=
 
     // Helper function for setting up the provider
     fn setup_provider() -> BasicVerifier {
-        BasicVerifier::new(blueprint::Basic { htpasswd: HTPASSWD_TEST.to_owned() })
+        BasicVerifier::new(blueprint::Basic { id: None, htpasswd: HTPASSWD_TEST.to_owned() })
     }
 }
@@ -1,18 +1,34 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 
+use serde_json::Value;
+
+use super::error::Error;
+use super::jwt::jwt_verify::JwtClaim;
 use super::verification::Verification;
-use super::verify::{AuthVerifier, Verify};
-use crate::core::blueprint::Auth;
+use super::verify::{AuthVerifier, Verifier, Verify};
+use crate::core::blueprint::{Auth, AuthRequirement};
 use crate::core::http::RequestContext;
 
 #[derive(Default)]
 pub struct GlobalAuthContext {
     verifier: Option<AuthVerifier>,
+    // providers that can be referenced from `@protected` by their id
+    providers: HashMap<String, Verifier>,
+    // whether templates read the claims, which are then verified up front
+    reads_claims: bool,
 }
 
 #[derive(Default)]
 pub struct AuthContext {
     auth_result: RwLock<Option<Verification>>,
+    provider_results: RwLock<HashMap<String, Verification>>,
+    // claims of every provider that verified the request, by provider id, in
+    // the order they were verified
+    claims: RwLock<Vec<(Option<String>, JwtClaim)>>,
+    // set once a protected field or type is resolved for the request
+    authorized: AtomicBool,
     global_ctx: Arc<GlobalAuthContext>,
 }
 
@@ -28,11 +44,31 @@ impl GlobalAuthContext {
             Verification::succeed()
         }
     }
+
+    async fn validate_provider(&self, id: &str, request: &RequestContext) -> Verification {
+        match self.providers.get(id) {
+            Some(verifier) => verifier.verify(request).await,
+            None => Verification::fail(Error::Invalid),
+        }
+    }
 }
 
 impl GlobalAuthContext {
-    pub fn new(auth: Option<Auth>) -> Self {
-        Self { verifier: auth.map(AuthVerifier::from) }
+    pub fn new(auth: Option<Auth>, reads_claims: bool) -> Self {
+        let providers = auth
+            .iter()
+            .flat_map(|auth| auth.providers())
+            .filter_map(|provider| {
+                let id = provider.id()?.to_owned();
+                Some((id, Verifier::from(provider.clone())))
+            })
+            .collect();
+
+        Self {
+            verifier: auth.map(AuthVerifier::from),
+            providers,
+            reads_claims,
+        }
     }
 }
 
@@ -48,6 +84,137 @@ impl AuthContext {
 
         result
     }
+
+    async fn validate_provider(&self, id: &str, request: &RequestContext) -> Verification {
+        if let Some(result) = self.provider_results.read().unwrap().get(id) {
+            return result.clone();
+        }
+
+        let result = self.global_ctx.validate_provider(id, request).await;
+
+        self.provider_results
+            .write()
+            .unwrap()
+            .insert(id.to_owned(), result.clone());
+
+        result
+    }
+
+    /// Verifies the request before it's resolved when templates read its
+    /// claims, so that they render the same whether or not a protected field
+    /// was resolved first. Requests failing the verification are resolved
+    /// without claims.
+    pub async fn verify_for_templates(&self, request: &RequestContext) {
+        if self.global_ctx.reads_claims {
+            let _ = self.validate(request).await;
+        }
+    }
+
+    /// Verifies the request and checks that the verified identity satisfies
+    /// all the requirements.
+    pub async fn authorize(
+        &self,
+        request: &RequestContext,
+        requirements: &[AuthRequirement],
+    ) -> Verification {
+        self.authorized.store(true, Ordering::Relaxed);
+
+        for requirement in requirements {
+            let verification = if requirement.providers.is_empty() {
+                self.validate(request)
+                    .await
+                    .and(self.check_verified(requirement))
+            } else {
+                let mut verification = Verification::fail(Error::Missing);
+                for id in requirement.providers.iter() {
+                    // only the claims of the provider that verified the request count
+                    let provider = self
+                        .validate_provider(id, request)
+                        .await
+                        .and(requirement.check(self.provider_claims(id).as_ref()));
+                    verification = verification.or(provider);
+                }
+                verification
+            };
+
+            if verification != Verification::succeed() {
+                return verification;
+            }
+        }
+
+        Verification::succeed()
+    }
+
+    /// Checks the requirement against the claims of every verified provider,
+    /// succeeding if any of them satisfies it.
+    fn check_verified(&self, requirement: &AuthRequirement) -> Verification {
+        let claims = self.claims.read().unwrap();
+
+        if claims.is_empty() {
+            return requirement.check(None);
+        }
+
+        claims.iter().fold(
+            Verification::fail(Error::Missing),
+            |verification, (_, claims)| verification.or(requirement.check(Some(claims))),
+        )
+    }
+
+    fn provider_claims(&self, id: &str) -> Option<JwtClaim> {
+        self.claims
+            .read()
+            .unwrap()
+            .iter()
+            .find(|(provider, _)| provider.as_deref() == Some(id))
+            .map(|(_, claims)| claims.clone())
+    }
+
+    /// Whether the request resolved any protected field or type.
+    pub fn is_authorized(&self) -> bool {
+        self.authorized.load(Ordering::Relaxed)
+    }
+
+    /// Claims of the first provider that verified the request, if any
+    pub fn claims(&self) -> Option<JwtClaim> {
+        self.claims
+            .read()
+            .unwrap()
+            .first()
+            .map(|(_, claims)| claims.clone())
+    }
+
+    /// Returns the claim of the verified identity at the given path, or all of
+    /// its claims when the path is empty.
+    pub fn claim<T: AsRef<str>>(&self, path: &[T]) -> Option<Value> {
+        let claims = self.claims.read().unwrap();
+        let (_, claims) = claims.first()?;
+
+        if path.is_empty() {
+            Some(Value::Object(claims.claims.clone()))
+        } else {
+            claims.path(path).cloned()
+        }
+    }
+
+    /// A copy of the context with the claims verified for the request.
+    pub fn detach(&self) -> Self {
+        Self {
+            global_ctx: self.global_ctx.clone(),
+            claims: RwLock::new(self.claims.read().unwrap().clone()),
+            ..Default::default()
+        }
+    }
+
+    /// Records the claims of the provider with the given id that verified the
+    /// request.
+    pub(crate) fn set_claims(&self, provider: Option<&str>, claims: JwtClaim) {
+        let mut all = self.claims.write().unwrap();
+
+        match all.iter_mut().find(|(id, _)| id.as_deref() == provider) {
+            Some((_, existing)) => *existing = claims,
+            None => all.push((provider.map(str::to_owned), claims)),
+        }
+    }
 }
 
 impl From<&Arc<GlobalAuthContext>> for AuthContext {
@@ -55,13 +222,20 @@ impl From<&Arc<GlobalAuthContext>> for AuthContext {
         Self {
             global_ctx: global_ctx.clone(),
             auth_result: Default::default(),
+            provider_results: Default::default(),
+            claims: Default::default(),
+            authorized: Default::default(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use http::header::HeaderValue;
+    use sha2::{Digest, Sha256};
+
     use super::*;
+    use crate::core::auth::api_key::ApiKeyVerifier;
     use crate::core::auth::basic::tests::{create_basic_auth_request, HTPASSWD_TEST};
     use crate::core::auth::basic::BasicVerifier;
     use crate::core::auth::error::Error;
@@ -106,10 +280,90 @@ mod tests {
         assert_eq!(validation, Verification::succeed());
     }
 
+    #[tokio::test]
+    async fn authorize_with_provider_claims() {
+        let api_keys = |id: &str, header: &str, key: &str, scope: &str| {
+            let serde_json::Value::Object(claims) =
+                serde_json::json!({ "sub": id, "scp": [scope] })
+            else {
+                unreachable!()
+            };
+
+            Verifier::ApiKeys(ApiKeyVerifier::new(blueprint::ApiKeys {
+                id: Some(id.to_owned()),
+                source: blueprint::ApiKeySource::Header(header.to_owned()),
+                keys: [(format!("{:x}", Sha256::digest(key.as_bytes())), claims)]
+                    .into_iter()
+                    .collect(),
+            }))
+        };
+
+        let global_ctx = Arc::new(GlobalAuthContext {
+            verifier: None,
+            providers: [
+                (
+                    "reader".to_owned(),
+                    api_keys("reader", "x-reader-key", "key-1", "read"),
+                ),
+                (
+                    "admin".to_owned(),
+                    api_keys("admin", "x-admin-key", "key-2", "admin"),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            reads_claims: false,
+        });
+
+        let requirement = |providers: &[&str], scope: &str| AuthRequirement {
+            providers: providers.iter().map(|id| id.to_string()).collect(),
+            scopes: vec![scope.to_owned()],
+            ..Default::default()
+        };
+
+        let mut request = RequestContext::default();
+        request.auth_ctx = AuthContext::from(&global_ctx);
+        request
+            .request_headers
+            .insert("x-reader-key", HeaderValue::from_static("key-1"));
+        request
+            .request_headers
+            .insert("x-admin-key", HeaderValue::from_static("key-2"));
+
+        let auth_ctx = &request.auth_ctx;
+
+        assert_eq!(
+            auth_ctx
+                .authorize(&request, &[requirement(&["admin"], "admin")])
+                .await,
+            Verification::succeed()
+        );
+
+        // the admin scope verified above doesn't apply to the reader provider
+        assert_eq!(
+            auth_ctx
+                .authorize(&request, &[requirement(&["reader"], "admin")])
+                .await,
+            Verification::fail(Error::Forbidden)
+        );
+        assert_eq!(
+            auth_ctx
+                .authorize(&request, &[requirement(&["reader"], "read")])
+                .await,
+            Verification::succeed()
+        );
+        assert_eq!(
+            auth_ctx
+                .authorize(&request, &[requirement(&["reader", "admin"], "admin")])
+                .await,
+            Verification::succeed()
+        );
+    }
+
     // Helper function for setting up the auth context
     async fn setup_auth_context() -> GlobalAuthContext {
         let basic_provider =
-            BasicVerifier::new(blueprint::Basic { htpasswd: HTPASSWD_TEST.to_owned() });
+            BasicVerifier::new(blueprint::Basic { id: None, htpasswd: HTPASSWD_TEST.to_owned() });
         let jwt_options = blueprint::Jwt::test_value();
         let jwt_provider = JwtVerifier::new(jwt_options);
 
@@ -118,6 +372,8 @@ mod tests {
                 AuthVerifier::Single(Verifier::Basic(basic_provider)).into(),
                 AuthVerifier::Single(Verifier::Jwt(jwt_provider)).into(),
             )),
+            providers: Default::default(),
+            reads_claims: false,
         }
     }
 }
@@ -8,4 +8,7 @@ pub enum Error {
 
     #[error("Invalid Authorization Header")]
     Invalid,
+
+    #[error("Insufficient Permissions")]
+    Forbidden,
 }
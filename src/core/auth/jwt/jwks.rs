@@ -4,6 +4,7 @@ use anyhow::Result;
 use derive_setters::Setters;
 use jsonwebtoken::jwk::{Jwk, JwkSet};
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde_json::{Map, Value};
 
 use super::jwt_verify::JwtClaim;
 use crate::core::auth::error::Error;
@@ -33,9 +34,10 @@ impl Jwks {
         // will validate on our side later
         validation.validate_aud = false;
 
-        let decoded = decode::<JwtClaim>(token, &key, &validation).map_err(|_| Error::Invalid)?;
+        let decoded =
+            decode::<Map<String, Value>>(token, &key, &validation).map_err(|_| Error::Invalid)?;
 
-        Ok(decoded.claims)
+        JwtClaim::try_from(decoded.claims).map_err(|_| Error::Invalid)
     }
 
     pub fn decode(&self, token: &str) -> Result<JwtClaim, Error> {
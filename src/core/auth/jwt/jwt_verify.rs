@@ -1,41 +1,105 @@
 use headers::authorization::Bearer;
 use headers::{Authorization, HeaderMapExt};
 use serde::Deserialize;
+use serde_json::{Map, Value};
 
 use super::jwks::Jwks;
+use super::remote_jwks::RemoteJwks;
 use crate::core::auth::error::Error;
 use crate::core::auth::verification::Verification;
 use crate::core::auth::verify::Verify;
 use crate::core::blueprint;
 use crate::core::http::RequestContext;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(untagged)]
 pub enum OneOrMany<T> {
     One(T),
     Vec(Vec<T>),
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize)]
 pub struct JwtClaim {
     pub aud: Option<OneOrMany<String>>,
     pub iss: Option<String>,
+    /// All the claims of the token, including the ones above.
+    #[serde(skip)]
+    pub claims: Map<String, Value>,
+}
+
+impl TryFrom<Map<String, Value>> for JwtClaim {
+    type Error = serde_json::Error;
+
+    fn try_from(claims: Map<String, Value>) -> Result<Self, Self::Error> {
+        let claim = serde_json::from_value::<JwtClaim>(Value::Object(claims.clone()))?;
+
+        Ok(Self { claims, ..claim })
+    }
+}
+
+impl JwtClaim {
+    /// Returns the claim at the given dot separated path, e.g. `org.id`.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        self.path(&path.split('.').collect::<Vec<_>>())
+    }
+
+    /// Returns the claim at the given path, e.g. `["org", "id"]`.
+    pub fn path<T: AsRef<str>>(&self, path: &[T]) -> Option<&Value> {
+        let (first, rest) = path.split_first()?;
+        let first = self.claims.get(first.as_ref())?;
+
+        rest.iter()
+            .try_fold(first, |value, part| value.get(part.as_ref()))
+    }
+
+    /// Scopes granted to the token, read from either the space-separated
+    /// `scope` claim or the `scp` claim.
+    pub fn scopes(&self) -> Vec<&str> {
+        let mut scopes = match self.claims.get("scope") {
+            Some(Value::String(scope)) => scope.split_whitespace().collect(),
+            _ => Vec::new(),
+        };
+        scopes.extend(strings(self.claims.get("scp")));
+
+        scopes
+    }
+
+    /// Roles of the token, read from the `roles` claim.
+    pub fn roles(&self) -> Vec<&str> {
+        strings(self.claims.get("roles"))
+    }
+}
+
+fn strings(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::String(value)) => vec![value.as_str()],
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+enum Decoder {
+    Local(Jwks),
+    Remote(RemoteJwks),
 }
 
 pub struct JwtVerifier {
     options: blueprint::Jwt,
-    decoder: Jwks,
+    decoder: Decoder,
 }
 
 impl JwtVerifier {
     pub fn new(options: blueprint::Jwt) -> Self {
-        Self {
-            decoder: Jwks {
-                set: options.jwks.clone(),
-                optional_kid: options.optional_kid,
-            },
-            options,
-        }
+        let jwks = Jwks {
+            set: options.jwks.clone(),
+            optional_kid: options.optional_kid,
+        };
+        let decoder = match options.refresh.clone() {
+            Some(refresh) => Decoder::Remote(RemoteJwks::new(refresh, jwks)),
+            None => Decoder::Local(jwks),
+        };
+
+        Self { decoder, options }
     }
 
     fn resolve_token(&self, request: &RequestContext) -> anyhow::Result<Option<String>> {
@@ -46,10 +110,23 @@ impl JwtVerifier {
         Ok(value.map(|token| token.token().to_owned()))
     }
 
-    async fn validate_token(&self, token: &str) -> Verification {
+    async fn validate_token(&self, token: &str, request: &RequestContext) -> Verification {
+        let decoded = match &self.decoder {
+            Decoder::Local(jwks) => jwks.decode(token),
+            Decoder::Remote(jwks) => jwks.decode(token, &request.runtime).await,
+        };
+
         Verification::from_result(
-            self.decoder.decode(token),
-            |claims| self.validate_claims(&claims),
+            decoded,
+            |claims| {
+                let verification = self.validate_claims(&claims);
+                if verification == Verification::succeed() {
+                    request
+                        .auth_ctx
+                        .set_claims(self.options.id.as_deref(), claims);
+                }
+                verification
+            },
             |err| Verification::fail(Error::Parse(err.to_string())),
         )
     }
@@ -74,7 +151,7 @@ impl Verify for JwtVerifier {
             return Verification::fail(Error::Missing);
         };
 
-        self.validate_token(&token).await
+        self.validate_token(&token, request).await
     }
 }
 
@@ -155,10 +232,12 @@ pub mod tests {
     impl blueprint::Jwt {
         pub fn test_value() -> Self {
             Self {
+                id: None,
                 issuer: Default::default(),
                 audiences: Default::default(),
                 optional_kid: false,
                 jwks: JWK_SET.clone(),
+                refresh: None,
             }
         }
     }
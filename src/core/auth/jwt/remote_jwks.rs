@@ -0,0 +1,221 @@
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use jsonwebtoken::decode_header;
+use jsonwebtoken::jwk::JwkSet;
+use tokio::sync::Mutex;
+
+use super::jwks::Jwks;
+use super::jwt_verify::JwtClaim;
+use crate::core::auth::error::Error;
+use crate::core::blueprint;
+use crate::core::runtime::TargetRuntime;
+
+#[derive(Clone)]
+struct State {
+    jwks: Arc<Jwks>,
+    fetched_at: Instant,
+}
+
+/// Key set fetched from a URL that follows the key rotations of the identity
+/// provider. The set is fetched again once it's older than the configured
+/// ttl, or when a token is signed with a `kid` it doesn't contain. Fetches
+/// happen at most once per `min_refetch_interval`, so that tokens with made-up
+/// `kid`s can't flood the provider.
+pub struct RemoteJwks {
+    options: blueprint::JwksRefresh,
+    state: RwLock<State>,
+    /// Time of the last fetch attempt, also serializes the fetches
+    last_fetch: Mutex<Option<Instant>>,
+}
+
+impl RemoteJwks {
+    /// Creates the remote key set from the one fetched when the config was
+    /// loaded.
+    pub fn new(options: blueprint::JwksRefresh, jwks: Jwks) -> Self {
+        Self {
+            options,
+            state: RwLock::new(State { jwks: Arc::new(jwks), fetched_at: Instant::now() }),
+            last_fetch: Mutex::new(None),
+        }
+    }
+
+    fn state(&self) -> State {
+        self.state.read().unwrap().clone()
+    }
+
+    pub async fn decode(&self, token: &str, runtime: &TargetRuntime) -> Result<JwtClaim, Error> {
+        let state = self.state();
+
+        if state.fetched_at.elapsed() >= self.options.ttl || has_unknown_kid(&state.jwks.set, token)
+        {
+            self.refetch(runtime, state.fetched_at).await;
+        }
+
+        self.state().jwks.decode(token)
+    }
+
+    async fn refetch(&self, runtime: &TargetRuntime, fetched_at: Instant) {
+        let mut last_fetch = self.last_fetch.lock().await;
+
+        // the set was refreshed while waiting for the lock
+        if self.state().fetched_at != fetched_at {
+            return;
+        }
+
+        if last_fetch.is_some_and(|at| at.elapsed() < self.options.min_refetch_interval) {
+            return;
+        }
+
+        *last_fetch = Some(Instant::now());
+
+        match self.fetch(runtime).await {
+            Ok(set) => {
+                let mut state = self.state.write().unwrap();
+                let optional_kid = state.jwks.optional_kid;
+
+                *state = State {
+                    jwks: Arc::new(Jwks { set, optional_kid }),
+                    fetched_at: Instant::now(),
+                };
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to refresh the JWKS from {}: {}",
+                    self.options.url,
+                    err
+                );
+            }
+        }
+    }
+
+    async fn fetch(&self, runtime: &TargetRuntime) -> anyhow::Result<JwkSet> {
+        let request = reqwest::Request::new(reqwest::Method::GET, self.options.url.clone());
+        let response = runtime.http.execute(request).await?;
+
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+}
+
+fn has_unknown_kid(set: &JwkSet, token: &str) -> bool {
+    decode_header(token)
+        .ok()
+        .and_then(|header| header.kid)
+        .is_some_and(|kid| set.find(&kid).is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::auth::jwt::jwt_verify::tests::{JWK_SET, JWT_VALID_TOKEN_WITH_KID};
+
+    fn start_mock_server() -> httpmock::MockServer {
+        httpmock::MockServer::start()
+    }
+
+    fn remote_jwks(
+        server: &httpmock::MockServer,
+        ttl: Duration,
+        min_refetch_interval: Duration,
+    ) -> RemoteJwks {
+        let options = blueprint::JwksRefresh {
+            url: server.url("/jwks.json").parse().unwrap(),
+            ttl,
+            min_refetch_interval,
+        };
+
+        RemoteJwks::new(options, Jwks::from(JwkSet { keys: vec![] }))
+    }
+
+    #[tokio::test]
+    async fn test_refetch_on_unknown_kid() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/jwks.json");
+            then.status(200).json_body_obj(&*JWK_SET);
+        });
+
+        let jwks = remote_jwks(&server, Duration::from_secs(600), Duration::ZERO);
+        let runtime = crate::core::runtime::test::init(None);
+
+        assert!(jwks
+            .decode(JWT_VALID_TOKEN_WITH_KID, &runtime)
+            .await
+            .is_ok());
+        assert!(jwks
+            .decode(JWT_VALID_TOKEN_WITH_KID, &runtime)
+            .await
+            .is_ok());
+
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_refetch_is_rate_limited() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/jwks.json");
+            then.status(200)
+                .json_body(serde_json::json!({ "keys": [] }));
+        });
+
+        let jwks = remote_jwks(&server, Duration::from_secs(600), Duration::from_secs(600));
+        let runtime = crate::core::runtime::test::init(None);
+
+        for _ in 0..3 {
+            assert!(jwks
+                .decode(JWT_VALID_TOKEN_WITH_KID, &runtime)
+                .await
+                .is_err());
+        }
+
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_after_ttl() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/jwks.json");
+            then.status(200).json_body_obj(&*JWK_SET);
+        });
+
+        let jwks = remote_jwks(&server, Duration::ZERO, Duration::ZERO);
+        let runtime = crate::core::runtime::test::init(None);
+
+        for _ in 0..2 {
+            assert!(jwks
+                .decode(JWT_VALID_TOKEN_WITH_KID, &runtime)
+                .await
+                .is_ok());
+        }
+
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_set_on_failure() {
+        let server = start_mock_server();
+        let mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/jwks.json");
+            then.status(500);
+        });
+
+        let options = blueprint::JwksRefresh {
+            url: server.url("/jwks.json").parse().unwrap(),
+            ttl: Duration::ZERO,
+            min_refetch_interval: Duration::ZERO,
+        };
+        let jwks = RemoteJwks::new(options, Jwks::from(JWK_SET.clone()));
+        let runtime = crate::core::runtime::test::init(None);
+
+        assert!(jwks
+            .decode(JWT_VALID_TOKEN_WITH_KID, &runtime)
+            .await
+            .is_ok());
+
+        mock.assert_hits(1);
+    }
+}
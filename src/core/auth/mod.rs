@@ -1,6 +1,9 @@
+pub mod api_key;
 pub mod basic;
 pub mod context;
 pub mod error;
+pub mod introspection;
 pub mod jwt;
+mod requirement;
 mod verification;
 pub mod verify;
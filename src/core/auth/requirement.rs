@@ -0,0 +1,155 @@
+use serde_json::Value;
+
+use super::error::Error;
+use super::jwt::jwt_verify::JwtClaim;
+use super::verification::Verification;
+use crate::core::blueprint::AuthRequirement;
+
+impl AuthRequirement {
+    /// Checks the claims of the verified identity against the requirement.
+    /// Identities without claims only satisfy requirements that don't ask for
+    /// any scope, role or claim.
+    pub fn check(&self, claims: Option<&JwtClaim>) -> Verification {
+        if self.scopes.is_empty() && self.roles.is_empty() && self.claims.is_empty() {
+            return Verification::succeed();
+        }
+
+        let Some(claims) = claims else {
+            return Verification::fail(Error::Forbidden);
+        };
+
+        let scopes = claims.scopes();
+        let has_scopes = self
+            .scopes
+            .iter()
+            .all(|scope| scopes.contains(&scope.as_str()));
+
+        let roles = claims.roles();
+        let has_role =
+            self.roles.is_empty() || self.roles.iter().any(|role| roles.contains(&role.as_str()));
+
+        let has_claims = self.claims.iter().all(|(path, expected)| {
+            claims
+                .get(path)
+                .is_some_and(|value| matches(value, expected))
+        });
+
+        if has_scopes && has_role && has_claims {
+            Verification::succeed()
+        } else {
+            Verification::fail(Error::Forbidden)
+        }
+    }
+}
+
+fn matches(value: &Value, expected: &str) -> bool {
+    match value {
+        Value::String(value) => value == expected,
+        Value::Number(_) | Value::Bool(_) => value.to_string() == expected,
+        Value::Array(values) => values.iter().any(|value| matches(value, expected)),
+        Value::Null | Value::Object(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn claims(value: Value) -> JwtClaim {
+        let Value::Object(map) = value else {
+            panic!("claims must be an object")
+        };
+
+        JwtClaim::try_from(map).unwrap()
+    }
+
+    fn requirement() -> AuthRequirement {
+        AuthRequirement::default()
+    }
+
+    #[test]
+    fn test_no_requirements() {
+        assert_eq!(requirement().check(None), Verification::succeed());
+    }
+
+    #[test]
+    fn test_missing_claims() {
+        let requirement = AuthRequirement { scopes: vec!["read".to_owned()], ..requirement() };
+
+        assert_eq!(
+            requirement.check(None),
+            Verification::fail(Error::Forbidden)
+        );
+    }
+
+    #[test]
+    fn test_scopes() {
+        let requirement = AuthRequirement {
+            scopes: vec!["read".to_owned(), "write".to_owned()],
+            ..requirement()
+        };
+
+        let claims_ = claims(json!({ "scope": "read write admin" }));
+        assert_eq!(requirement.check(Some(&claims_)), Verification::succeed());
+
+        let claims_ = claims(json!({ "scp": ["read", "write"] }));
+        assert_eq!(requirement.check(Some(&claims_)), Verification::succeed());
+
+        let claims_ = claims(json!({ "scope": "read" }));
+        assert_eq!(
+            requirement.check(Some(&claims_)),
+            Verification::fail(Error::Forbidden)
+        );
+    }
+
+    #[test]
+    fn test_roles() {
+        let requirement = AuthRequirement {
+            roles: vec!["admin".to_owned(), "editor".to_owned()],
+            ..requirement()
+        };
+
+        let claims_ = claims(json!({ "roles": ["viewer", "editor"] }));
+        assert_eq!(requirement.check(Some(&claims_)), Verification::succeed());
+
+        let claims_ = claims(json!({ "roles": "admin" }));
+        assert_eq!(requirement.check(Some(&claims_)), Verification::succeed());
+
+        let claims_ = claims(json!({ "roles": ["viewer"] }));
+        assert_eq!(
+            requirement.check(Some(&claims_)),
+            Verification::fail(Error::Forbidden)
+        );
+    }
+
+    #[test]
+    fn test_claims() {
+        let requirement = AuthRequirement {
+            claims: vec![
+                ("org.id".to_owned(), "42".to_owned()),
+                ("groups".to_owned(), "staff".to_owned()),
+                ("email_verified".to_owned(), "true".to_owned()),
+            ],
+            ..requirement()
+        };
+
+        let claims_ = claims(json!({
+            "org": { "id": 42 },
+            "groups": ["staff", "beta"],
+            "email_verified": true
+        }));
+        assert_eq!(requirement.check(Some(&claims_)), Verification::succeed());
+
+        let claims_ = claims(json!({
+            "org": { "id": 7 },
+            "groups": ["staff"],
+            "email_verified": true
+        }));
+        assert_eq!(
+            requirement.check(Some(&claims_)),
+            Verification::fail(Error::Forbidden)
+        );
+    }
+}
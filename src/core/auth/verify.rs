@@ -1,6 +1,8 @@
 use futures_util::join;
 
+use super::api_key::ApiKeyVerifier;
 use super::basic::BasicVerifier;
+use super::introspection::IntrospectionVerifier;
 use super::jwt::jwt_verify::JwtVerifier;
 use super::verification::Verification;
 use crate::core::blueprint;
@@ -14,6 +16,8 @@ pub(crate) trait Verify {
 pub enum Verifier {
     Basic(BasicVerifier),
     Jwt(JwtVerifier),
+    Introspection(IntrospectionVerifier),
+    ApiKeys(ApiKeyVerifier),
 }
 
 pub enum AuthVerifier {
@@ -27,6 +31,12 @@ impl From<blueprint::Provider> for Verifier {
         match provider {
             blueprint::Provider::Basic(options) => Verifier::Basic(BasicVerifier::new(options)),
             blueprint::Provider::Jwt(options) => Verifier::Jwt(JwtVerifier::new(options)),
+            blueprint::Provider::Introspection(options) => {
+                Verifier::Introspection(IntrospectionVerifier::new(options))
+            }
+            blueprint::Provider::ApiKeys(options) => {
+                Verifier::ApiKeys(ApiKeyVerifier::new(options))
+            }
         }
     }
 }
@@ -51,6 +61,8 @@ impl Verify for Verifier {
         match self {
             Verifier::Basic(basic) => basic.verify(req_ctx).await,
             Verifier::Jwt(jwt) => jwt.verify(req_ctx).await,
+            Verifier::Introspection(introspection) => introspection.verify(req_ctx).await,
+            Verifier::ApiKeys(api_keys) => api_keys.verify(req_ctx).await,
         }
     }
 }
@@ -1,28 +1,149 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
+use std::time::Duration;
 
 use jsonwebtoken::jwk::JwkSet;
+use serde_json::{Map, Value};
+use url::Url;
 
-use crate::core::config::ConfigModule;
-use crate::core::valid::Valid;
+use crate::core::config::{self, ConfigModule, Content};
+use crate::core::valid::{Valid, ValidationError, Validator};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Basic {
+    pub id: Option<String>,
     pub htpasswd: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Jwt {
+    pub id: Option<String>,
     pub issuer: Option<String>,
     pub audiences: HashSet<String>,
     pub optional_kid: bool,
     pub jwks: JwkSet,
+    pub refresh: Option<JwksRefresh>,
+}
+
+/// Settings to keep a key set fetched from a URL up to date.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JwksRefresh {
+    pub url: Url,
+    pub ttl: Duration,
+    pub min_refetch_interval: Duration,
+}
+
+impl JwksRefresh {
+    const DEFAULT_TTL: Duration = Duration::from_secs(600);
+    const DEFAULT_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+
+    fn make(refresh: &config::JwksRefresh) -> Valid<Self, String> {
+        Valid::from(
+            Url::parse(&refresh.url).map_err(|e| {
+                ValidationError::new(format!("Invalid JWKS url `{}`: {e}", refresh.url))
+            }),
+        )
+        .map(|url| JwksRefresh {
+            url,
+            ttl: refresh
+                .ttl
+                .map(Duration::from_secs)
+                .unwrap_or(Self::DEFAULT_TTL),
+            min_refetch_interval: refresh
+                .min_refetch_interval
+                .map(Duration::from_secs)
+                .unwrap_or(Self::DEFAULT_MIN_REFETCH_INTERVAL),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Introspection {
+    pub id: Option<String>,
+    pub url: Url,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApiKeySource {
+    Header(String),
+    Query(String),
+    Cookie(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiKeys {
+    pub id: Option<String>,
+    pub source: ApiKeySource,
+    /// Claims of every key, by the hex encoded SHA-256 hash of the key
+    pub keys: BTreeMap<String, Map<String, Value>>,
+}
+
+impl ApiKeys {
+    fn make(api_keys: &Content<config::ApiKeys>) -> Valid<Self, String> {
+        let name = api_keys.source.name().to_owned();
+        let source = match api_keys.source {
+            config::ApiKeySource::Header { .. } => ApiKeySource::Header(name),
+            config::ApiKeySource::Query { .. } => ApiKeySource::Query(name),
+            config::ApiKeySource::Cookie { .. } => ApiKeySource::Cookie(name),
+        };
+
+        Valid::from_iter(api_keys.keys.iter(), |key| {
+            let hash = key.hash.to_lowercase();
+
+            Valid::<(), String>::fail(format!(
+                "API key hash `{}` is not a hex encoded SHA-256 hash",
+                key.hash
+            ))
+            .when(|| hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(|_| (hash, api_key_claims(key)))
+        })
+        .map(|keys| ApiKeys {
+            id: api_keys.id.clone(),
+            source,
+            keys: keys.into_iter().collect(),
+        })
+    }
+}
+
+/// Maps the metadata of a key to claims, the same way they'd be set in a
+/// token: the name is the subject and the scopes are under `scp`.
+fn api_key_claims(key: &config::ApiKey) -> Map<String, Value> {
+    let mut claims = key.metadata.clone();
+
+    if let Some(name) = &key.name {
+        claims.insert("name".to_owned(), Value::from(name.as_str()));
+        claims
+            .entry("sub")
+            .or_insert_with(|| Value::from(name.as_str()));
+    }
+
+    if !key.scopes.is_empty() {
+        claims.insert("scp".to_owned(), Value::from(key.scopes.clone()));
+    }
+
+    claims
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Provider {
     Basic(Basic),
     Jwt(Jwt),
+    Introspection(Introspection),
+    ApiKeys(ApiKeys),
+}
+
+impl Provider {
+    /// Id of the `@link` the provider is defined by
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Provider::Basic(basic) => basic.id.as_deref(),
+            Provider::Jwt(jwt) => jwt.id.as_deref(),
+            Provider::Introspection(introspection) => introspection.id.as_deref(),
+            Provider::ApiKeys(api_keys) => api_keys.id.as_deref(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -36,23 +157,63 @@ impl Auth {
     pub fn make(config_module: &ConfigModule) -> Valid<Option<Auth>, String> {
         let htpasswd = config_module.extensions().htpasswd.iter().map(|htpasswd| {
             Auth::Provider(Provider::Basic(Basic {
+                id: htpasswd.id.clone(),
                 htpasswd: htpasswd.content.clone(),
             }))
         });
 
-        let jwks = config_module.extensions().jwks.iter().map(|jwks| {
-            Auth::Provider(Provider::Jwt(Jwt {
-                jwks: jwks.content.clone(),
-                // TODO: read those options from link instead of using defaults
-                issuer: Default::default(),
-                audiences: Default::default(),
-                optional_kid: Default::default(),
-            }))
+        let jwks = Valid::from_iter(config_module.extensions().jwks.iter(), |jwks| {
+            let refresh = match &jwks.refresh {
+                Some(refresh) => JwksRefresh::make(refresh).some(),
+                None => Valid::none(),
+            };
+
+            refresh.map(|refresh| {
+                Auth::Provider(Provider::Jwt(Jwt {
+                    id: jwks.id.clone(),
+                    jwks: jwks.set.clone(),
+                    refresh,
+                    // TODO: read those options from link instead of using defaults
+                    issuer: Default::default(),
+                    audiences: Default::default(),
+                    optional_kid: Default::default(),
+                }))
+            })
         });
 
-        let auth = htpasswd.chain(jwks).reduce(|left, right| left.or(right));
+        let introspection = Valid::from_iter(
+            config_module.extensions().introspection.iter(),
+            |introspection| {
+                Valid::from(Url::parse(&introspection.url).map_err(|e| {
+                    ValidationError::new(format!(
+                        "Invalid introspection url `{}`: {e}",
+                        introspection.url
+                    ))
+                }))
+                .map(|url| {
+                    Auth::Provider(Provider::Introspection(Introspection {
+                        id: introspection.id.clone(),
+                        url,
+                        client_id: introspection.client_id.clone(),
+                        client_secret: introspection.client_secret.clone(),
+                    }))
+                })
+            },
+        );
+
+        let api_keys = Valid::from_iter(config_module.extensions().api_keys.iter(), |api_keys| {
+            ApiKeys::make(api_keys).map(|api_keys| Auth::Provider(Provider::ApiKeys(api_keys)))
+        });
 
-        Valid::succeed(auth)
+        jwks.zip(introspection)
+            .zip(api_keys)
+            .map(|((jwks, introspection), api_keys)| {
+                htpasswd
+                    .chain(jwks)
+                    .chain(introspection)
+                    .chain(api_keys)
+                    .reduce(|left, right| left.or(right))
+            })
     }
 
     pub fn and(self, other: Self) -> Self {
@@ -62,6 +223,43 @@ impl Auth {
     pub fn or(self, other: Self) -> Self {
         Auth::Or(Box::new(self), Box::new(other))
     }
+
+    /// Returns all the providers the auth is composed of
+    pub fn providers(&self) -> Vec<&Provider> {
+        match self {
+            Auth::Provider(provider) => vec![provider],
+            Auth::And(left, right) | Auth::Or(left, right) => {
+                let mut providers = left.providers();
+                providers.extend(right.providers());
+                providers
+            }
+        }
+    }
+}
+
+/// Requirements set by `@protected` that the identity verified by the auth
+/// providers must satisfy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuthRequirement {
+    pub providers: Vec<String>,
+    pub scopes: Vec<String>,
+    pub roles: Vec<String>,
+    pub claims: Vec<(String, String)>,
+}
+
+impl From<&config::Protected> for AuthRequirement {
+    fn from(protected: &config::Protected) -> Self {
+        Self {
+            providers: protected.providers.clone(),
+            scopes: protected.scopes.clone(),
+            roles: protected.roles.clone(),
+            claims: protected
+                .claims
+                .iter()
+                .map(|claim| (claim.key.clone(), claim.value.clone()))
+                .collect(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -69,11 +267,11 @@ mod tests {
     use super::{Auth, Basic, Jwt, Provider};
 
     fn test_basic_provider_1() -> Provider {
-        Provider::Basic(Basic { htpasswd: "1".into() })
+        Provider::Basic(Basic { id: None, htpasswd: "1".into() })
     }
 
     fn test_basic_provider_2() -> Provider {
-        Provider::Basic(Basic { htpasswd: "2".into() })
+        Provider::Basic(Basic { id: None, htpasswd: "2".into() })
     }
 
     fn test_jwt_provider() -> Provider {
@@ -0,0 +1,9 @@
+/// Cost of a field, counted against `maxCost` of `@server`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cost {
+    /// Cost of resolving the field once
+    pub weight: u64,
+    /// Arguments of the field whose values multiply the cost of the fields
+    /// selected under it
+    pub multipliers: Vec<String>,
+}
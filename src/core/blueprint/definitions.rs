@@ -7,7 +7,8 @@ use union_resolver::update_union_resolver;
 use crate::core::blueprint::*;
 use crate::core::config::{Config, Enum, Field, GraphQLOperationType, Protected, Union};
 use crate::core::directive::DirectiveCodec;
-use crate::core::ir::model::{Cache, IR};
+use crate::core::ir::model::{Cache, CachePolicy, IR};
+use crate::core::mustache::Mustache;
 use crate::core::try_fold::TryFold;
 use crate::core::valid::{Valid, Validator};
 use crate::core::{config, scalar, Type};
@@ -281,6 +282,8 @@ fn update_args<'a>(
                 directives: Vec::new(),
                 resolver: None,
                 default_value: field.default_value.clone(),
+                rate_limit: None,
+                cost: Default::default(),
             })
         },
     )
@@ -343,12 +346,23 @@ pub fn fix_dangling_resolvers<'a>(
 /// Wraps the IO Expression with Expression::Cached
 /// if `Field::cache` is present for that field
 pub fn update_cache_resolvers<'a>(
+    type_name: &'a str,
 ) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
 {
     TryFold::<(&ConfigModule, &Field, &config::Type, &str), FieldDefinition, String>::new(
-        move |(_config, field, typ, _name), mut b_field| {
-            if let Some(config::Cache { max_age }) = field.cache.as_ref().or(typ.cache.as_ref()) {
-                b_field.map_expr(|expression| Cache::wrap(*max_age, expression))
+        move |(_config, field, typ, name), mut b_field| {
+            if let Some(cache) = field.cache.as_ref().or(typ.cache.as_ref()) {
+                let tags = std::iter::once(format!("{type_name}.{name}"))
+                    .chain(cache.tags.iter().cloned())
+                    .map(|tag| Mustache::parse(&tag))
+                    .collect::<Vec<_>>();
+                let policy = CachePolicy {
+                    max_age: cache.max_age,
+                    stale_while_revalidate: cache.stale_while_revalidate,
+                    stale_if_error: cache.stale_if_error,
+                    tags,
+                };
+                b_field.map_expr(|expression| Cache::wrap(policy, expression))
             }
 
             Valid::succeed(b_field)
@@ -494,6 +508,7 @@ pub fn to_field_definition(
     update_args()
         .and(update_apollo_federation(operation_type).trace("_entities"))
         .and(update_http().trace(config::Http::trace_name().as_str()))
+        .and(update_subscription(object_name))
         .and(update_grpc(operation_type).trace(config::Grpc::trace_name().as_str()))
         .and(update_const_field().trace(config::Expr::trace_name().as_str()))
         .and(update_js_field().trace(config::JS::trace_name().as_str()))
@@ -501,8 +516,11 @@ pub fn to_field_definition(
         .and(update_modify().trace(config::Modify::trace_name().as_str()))
         .and(update_call(operation_type, object_name).trace(config::Call::trace_name().as_str()))
         .and(fix_dangling_resolvers())
-        .and(update_cache_resolvers())
+        .and(update_cache_resolvers(object_name))
+        .and(update_invalidate(object_name).trace(config::Invalidate::trace_name().as_str()))
         .and(update_protected(object_name).trace(Protected::trace_name().as_str()))
+        .and(update_rate_limit(object_name).trace(config::RateLimit::trace_name().as_str()))
+        .and(update_cost().trace(config::Cost::trace_name().as_str()))
         .and(update_enum_alias())
         .and(update_union_resolver())
         .try_fold(
@@ -34,6 +34,10 @@ pub fn config_blueprint<'a>() -> TryFold<'a, ConfigModule, Blueprint, String> {
         Valid::from(Upstream::try_from(config_module)).map(|upstream| blueprint.upstream(upstream))
     });
 
+    let upstreams = TryFoldConfig::<Blueprint>::new(|config_module, blueprint| {
+        upstreams(config_module).map(|upstreams| blueprint.upstreams(upstreams))
+    });
+
     let links = TryFoldConfig::<Blueprint>::new(|config_module, blueprint| {
         Valid::from(Links::try_from(config_module.links.clone())).map_to(blueprint)
     });
@@ -47,6 +51,7 @@ pub fn config_blueprint<'a>() -> TryFold<'a, ConfigModule, Blueprint, String> {
         .and(schema)
         .and(definitions)
         .and(upstream)
+        .and(upstreams)
         .and(links)
         .and(opentelemetry)
         .update(apply_batching)
@@ -9,7 +9,7 @@ use tracing::Instrument;
 
 use crate::core::blueprint::{Blueprint, Definition};
 use crate::core::http::RequestContext;
-use crate::core::ir::{EvalContext, ResolverContext, TypedValue};
+use crate::core::ir::{Error, EvalContext, ResolverContext, TypedValue};
 use crate::core::scalar;
 
 /// We set the default value for an `InputValue` by reading it from the
@@ -90,13 +90,27 @@ fn to_type(def: &Definition) -> dynamic::Type {
                                 );
 
                                 let expr = expr.to_owned();
+                                let is_nullable = field.of_type.is_nullable();
                                 FieldFuture::new(
                                     async move {
+                                        let graphql_ctx = ctx.ctx;
                                         let ctx: ResolverContext = ctx.into();
                                         let ctx = &mut EvalContext::new(req_ctx, &ctx);
 
-                                        let value =
-                                            expr.eval(ctx).await.map_err(|err| err.extend())?;
+                                        let value = match expr.eval(ctx).await {
+                                            Ok(value) => value,
+                                            // a field that timed out is nulled, so that the rest
+                                            // of the response is still served
+                                            Err(err @ Error::Timeout(_)) if is_nullable => {
+                                                let error = err
+                                                    .extend()
+                                                    .into_server_error(graphql_ctx.item.pos);
+                                                graphql_ctx
+                                                    .add_error(graphql_ctx.set_error_path(error));
+                                                ConstValue::Null
+                                            }
+                                            Err(err) => return Err(err.extend()),
+                                        };
 
                                         if let ConstValue::Null = value {
                                             Ok(FieldValue::NONE)
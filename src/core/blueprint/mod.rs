@@ -2,6 +2,7 @@ mod auth;
 mod blueprint;
 mod compress;
 mod cors;
+mod cost;
 mod definitions;
 mod dynamic_value;
 mod from_config;
@@ -10,6 +11,8 @@ mod into_schema;
 mod links;
 mod mustache;
 mod operators;
+mod rate_limit;
+mod resilience;
 mod schema;
 mod server;
 pub mod telemetry;
@@ -20,12 +23,15 @@ mod upstream;
 pub use auth::*;
 pub use blueprint::*;
 pub use cors::*;
+pub use cost::Cost;
 pub use definitions::*;
 pub use dynamic_value::*;
 pub use from_config::*;
 pub use index::*;
 pub use links::*;
 pub use operators::*;
+pub use rate_limit::RateLimit;
+pub use resilience::{CircuitBreaker, Resilience, Retry};
 pub use schema::*;
 pub use server::*;
 pub use timeout::GlobalTimeout;
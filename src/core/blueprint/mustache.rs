@@ -88,6 +88,16 @@ impl<'a> MustachePartsValidator<'a> {
                     return Valid::fail(format!("var '{tail}' is not set in the server config"));
                 }
             }
+            "auth" => {
+                if tail != "claims" {
+                    return Valid::fail(format!("unknown auth value '{tail}', expected 'claims'"));
+                }
+            }
+            "client" => {
+                if tail != "ip" {
+                    return Valid::fail(format!("unknown client value '{tail}', expected 'ip'"));
+                }
+            }
             "headers" | "env" => {
                 // "headers" and "env" refers to values known at runtime, which
                 // we can't validate here
@@ -216,6 +226,8 @@ mod test {
             directives: vec![],
             description: None,
             default_value: None,
+            rate_limit: None,
+            cost: Default::default(),
         };
 
         (config, fld)
@@ -244,4 +256,22 @@ mod test {
 
         assert!(validation_result.to_result().is_err())
     }
+
+    #[test]
+    fn test_auth_claims() {
+        let (config, field_def) = initialize_test_config_and_field();
+
+        let parts_validator =
+            MustachePartsValidator::new(config.types.get("T1").unwrap(), &config, &field_def);
+
+        let validation_result = parts_validator.validate(
+            &["auth".to_string(), "claims".to_string(), "sub".to_string()],
+            false,
+        );
+        assert!(validation_result.is_succeed());
+
+        let validation_result =
+            parts_validator.validate(&["auth".to_string(), "sub".to_string()], true);
+        assert!(validation_result.to_result().is_err());
+    }
 }
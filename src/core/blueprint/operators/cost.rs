@@ -0,0 +1,49 @@
+use crate::core::blueprint::{Cost, FieldDefinition};
+use crate::core::config::{self, ConfigModule, Field};
+use crate::core::try_fold::TryFold;
+use crate::core::valid::{Valid, Validator};
+
+pub fn update_cost<'a>(
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        |(_, field, type_, _), mut b_field| {
+            // fields with a resolver cost one call by default, the others are resolved
+            // along with their parent
+            let default_weight = u64::from(field.has_resolver());
+
+            let multipliers = match (field.cost.as_ref(), type_.cost.as_ref()) {
+                (Some(cost), _) => Valid::from_iter(cost.multipliers.iter(), |name| {
+                    if field.args.contains_key(name) {
+                        Valid::succeed(name.clone())
+                    } else {
+                        Valid::fail(format!(
+                            "Multiplier `{name}` is not an argument of the field"
+                        ))
+                    }
+                }),
+                // the multipliers of the type only apply to the fields that have the argument
+                (None, Some(cost)) => Valid::succeed(
+                    cost.multipliers
+                        .iter()
+                        .filter(|name| field.args.contains_key(*name))
+                        .cloned()
+                        .collect(),
+                ),
+                (None, None) => Valid::succeed(vec![]),
+            };
+
+            let weight = field
+                .cost
+                .as_ref()
+                .or(type_.cost.as_ref())
+                .and_then(|cost| cost.weight)
+                .unwrap_or(default_weight);
+
+            multipliers.map(|multipliers| {
+                b_field.cost = Cost { weight, multipliers };
+                b_field
+            })
+        },
+    )
+}
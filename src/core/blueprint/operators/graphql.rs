@@ -1,6 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::core::blueprint::FieldDefinition;
+use crate::core::blueprint::{resolver_upstream, FieldDefinition, Resilience};
 use crate::core::config::{
     Config, ConfigModule, Field, GraphQL, GraphQLOperationType, Resolver, Type,
 };
@@ -47,32 +47,45 @@ pub fn compile_graphql(
     graphql: &GraphQL,
 ) -> Valid<IR, String> {
     let args = graphql.args.as_ref();
-    Valid::from_option(
-        graphql
-            .base_url
-            .as_ref()
-            .or(config.upstream.base_url.as_ref()),
-        "No base URL defined".to_string(),
-    )
-    .zip(helpers::headers::to_mustache_headers(&graphql.headers))
-    .and_then(|(base_url, headers)| {
-        Valid::from(
-            RequestTemplate::new(
-                base_url.to_owned(),
-                operation_type,
-                &graphql.name,
-                args,
-                headers,
-                create_related_fields(config, type_name, &mut HashSet::new()),
-            )
-            .map_err(|e| ValidationError::new(e.to_string())),
+    resolver_upstream(config, graphql.upstream.as_deref()).and_then(|upstream| {
+        Valid::from_option(
+            graphql.base_url.as_ref().or(upstream.base_url.as_ref()),
+            "No base URL defined".to_string(),
         )
-    })
-    .map(|req_template| {
-        let field_name = graphql.name.clone();
-        let batch = graphql.batch;
-        let dedupe = graphql.dedupe.unwrap_or_default();
-        IR::IO(IO::GraphQL { req_template, field_name, batch, dl_id: None, dedupe })
+        .zip(helpers::headers::to_mustache_headers(&graphql.headers))
+        .and_then(|(base_url, headers)| {
+            Valid::from(
+                RequestTemplate::new(
+                    base_url.to_owned(),
+                    operation_type,
+                    &graphql.name,
+                    args,
+                    headers,
+                    create_related_fields(config, type_name, &mut HashSet::new()),
+                )
+                .map_err(|e| ValidationError::new(e.to_string())),
+            )
+        })
+        .zip(Resilience::make(
+            &upstream,
+            graphql.retry.as_ref(),
+            graphql.circuit_breaker.as_ref(),
+            graphql.timeout,
+        ))
+        .map(|(req_template, resilience)| {
+            let field_name = graphql.name.clone();
+            let batch = graphql.batch;
+            let dedupe = graphql.dedupe.unwrap_or_default();
+            IR::IO(IO::GraphQL {
+                req_template,
+                field_name,
+                batch,
+                dl_id: None,
+                dedupe,
+                resilience,
+                upstream: graphql.upstream.clone(),
+            })
+        })
     })
 }
 
@@ -3,7 +3,7 @@ use std::fmt::Display;
 use prost_reflect::prost_types::FileDescriptorSet;
 use prost_reflect::FieldDescriptor;
 
-use crate::core::blueprint::FieldDefinition;
+use crate::core::blueprint::{resolver_upstream, FieldDefinition, Resilience};
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::{Config, ConfigModule, Field, GraphQLOperationType, Grpc, Resolver};
 use crate::core::grpc::protobuf::{ProtobufOperation, ProtobufSet};
@@ -15,9 +15,13 @@ use crate::core::try_fold::TryFold;
 use crate::core::valid::{Valid, ValidationError, Validator};
 use crate::core::{config, helpers};
 
-fn to_url(grpc: &Grpc, method: &GrpcMethod, config: &Config) -> Valid<Mustache, String> {
+fn to_url(
+    grpc: &Grpc,
+    method: &GrpcMethod,
+    upstream: &config::Upstream,
+) -> Valid<Mustache, String> {
     Valid::from_option(
-        grpc.base_url.as_ref().or(config.upstream.base_url.as_ref()),
+        grpc.base_url.as_ref().or(upstream.base_url.as_ref()),
         "No base URL defined".to_string(),
     )
     .and_then(|base_url| {
@@ -162,52 +166,71 @@ pub fn compile_grpc(inputs: CompileGrpc) -> Valid<IR, String> {
     let validate_with_schema = inputs.validate_with_schema;
     let dedupe = grpc.dedupe.unwrap_or_default();
 
-    Valid::from(GrpcMethod::try_from(grpc.method.as_str()))
-        .and_then(|method| {
-            let file_descriptor_set = config_module.extensions().get_file_descriptor_set();
+    resolver_upstream(config_module, grpc.upstream.as_deref()).and_then(|upstream| {
+        Valid::from(GrpcMethod::try_from(grpc.method.as_str()))
+            .and_then(|method| {
+                let file_descriptor_set = config_module.extensions().get_file_descriptor_set();
 
-            if file_descriptor_set.file.is_empty() {
-                return Valid::fail("Protobuf files were not specified in the config".to_string());
-            }
+                if file_descriptor_set.file.is_empty() {
+                    return Valid::fail(
+                        "Protobuf files were not specified in the config".to_string(),
+                    );
+                }
 
-            to_operation(&method, file_descriptor_set)
-                .fuse(to_url(grpc, &method, config_module))
-                .fuse(helpers::headers::to_mustache_headers(&grpc.headers))
-                .fuse(helpers::body::to_body(grpc.body.as_ref()))
-                .into()
-        })
-        .and_then(|(operation, url, headers, body)| {
-            let validation = if validate_with_schema {
-                let field_schema = json_schema_from_field(config_module, field);
-                if grpc.batch_key.is_empty() {
-                    validate_schema(field_schema, &operation, field.type_of.name()).unit()
+                to_operation(&method, file_descriptor_set)
+                    .fuse(to_url(grpc, &method, &upstream))
+                    .fuse(helpers::headers::to_mustache_headers(&grpc.headers))
+                    .fuse(helpers::body::to_body(grpc.body.as_ref()))
+                    .into()
+            })
+            .and_then(|(operation, url, headers, body)| {
+                let validation = if validate_with_schema {
+                    let field_schema = json_schema_from_field(config_module, field);
+                    if grpc.batch_key.is_empty() {
+                        validate_schema(field_schema, &operation, field.type_of.name()).unit()
+                    } else {
+                        validate_group_by(&field_schema, &operation, grpc.batch_key.clone()).unit()
+                    }
+                } else {
+                    Valid::succeed(())
+                };
+                validation.map(|_| (url, headers, operation, body))
+            })
+            .zip(Resilience::make(
+                &upstream,
+                grpc.retry.as_ref(),
+                grpc.circuit_breaker.as_ref(),
+                grpc.timeout,
+            ))
+            .map(|((url, headers, operation, body), resilience)| {
+                let req_template = RequestTemplate {
+                    url,
+                    headers,
+                    operation,
+                    body,
+                    operation_type: operation_type.clone(),
+                };
+                if !grpc.batch_key.is_empty() {
+                    IR::IO(IO::Grpc {
+                        req_template,
+                        group_by: Some(GroupBy::new(grpc.batch_key.clone(), None)),
+                        dl_id: None,
+                        dedupe,
+                        resilience,
+                        upstream: grpc.upstream.clone(),
+                    })
                 } else {
-                    validate_group_by(&field_schema, &operation, grpc.batch_key.clone()).unit()
+                    IR::IO(IO::Grpc {
+                        req_template,
+                        group_by: None,
+                        dl_id: None,
+                        dedupe,
+                        resilience,
+                        upstream: grpc.upstream.clone(),
+                    })
                 }
-            } else {
-                Valid::succeed(())
-            };
-            validation.map(|_| (url, headers, operation, body))
-        })
-        .map(|(url, headers, operation, body)| {
-            let req_template = RequestTemplate {
-                url,
-                headers,
-                operation,
-                body,
-                operation_type: operation_type.clone(),
-            };
-            if !grpc.batch_key.is_empty() {
-                IR::IO(IO::Grpc {
-                    req_template,
-                    group_by: Some(GroupBy::new(grpc.batch_key.clone(), None)),
-                    dl_id: None,
-                    dedupe,
-                })
-            } else {
-                IR::IO(IO::Grpc { req_template, group_by: None, dl_id: None, dedupe })
-            }
-        })
+            })
+    })
 }
 
 pub fn update_grpc<'a>(
@@ -15,87 +15,97 @@ pub fn compile_http(
 ) -> Valid<IR, String> {
     let dedupe = http.dedupe.unwrap_or_default();
 
-    Valid::<(), String>::fail("GroupBy is only supported for GET requests".to_string())
-        .when(|| !http.batch_key.is_empty() && http.method != Method::GET)
-        .and(
-            Valid::<(), String>::fail(
-                "Batching capability was used without enabling it in upstream".to_string(),
+    resolver_upstream(config_module, http.upstream.as_deref()).and_then(|upstream| {
+        Valid::<(), String>::fail("GroupBy is only supported for GET requests".to_string())
+            .when(|| !http.batch_key.is_empty() && http.method != Method::GET)
+            .and(
+                Valid::<(), String>::fail(
+                    "Batching capability was used without enabling it in upstream".to_string(),
+                )
+                .when(|| {
+                    (config_module.upstream.get_delay() < 1
+                        || config_module.upstream.get_max_size() < 1)
+                        && !http.batch_key.is_empty()
+                }),
             )
-            .when(|| {
-                (config_module.upstream.get_delay() < 1
-                    || config_module.upstream.get_max_size() < 1)
-                    && !http.batch_key.is_empty()
-            }),
-        )
-        .and(Valid::from_option(
-            http.base_url
-                .as_ref()
-                .or(config_module.upstream.base_url.as_ref()),
-            "No base URL defined".to_string(),
-        ))
-        .zip(helpers::headers::to_mustache_headers(&http.headers))
-        .and_then(|(base_url, headers)| {
-            let mut base_url = base_url.trim_end_matches('/').to_owned();
-            base_url.push_str(http.path.clone().as_str());
+            .and(Valid::from_option(
+                http.base_url.as_ref().or(upstream.base_url.as_ref()),
+                "No base URL defined".to_string(),
+            ))
+            .zip(helpers::headers::to_mustache_headers(&http.headers))
+            .and_then(|(base_url, headers)| {
+                let mut base_url = base_url.trim_end_matches('/').to_owned();
+                base_url.push_str(http.path.clone().as_str());
 
-            let query = http
-                .query
-                .clone()
-                .iter()
-                .map(|key_value| {
-                    (
-                        key_value.key.clone(),
-                        key_value.value.clone(),
-                        key_value.skip_empty.unwrap_or_default(),
-                    )
-                })
-                .collect();
+                let query = http
+                    .query
+                    .clone()
+                    .iter()
+                    .map(|key_value| {
+                        (
+                            key_value.key.clone(),
+                            key_value.value.clone(),
+                            key_value.skip_empty.unwrap_or_default(),
+                        )
+                    })
+                    .collect();
 
-            RequestTemplate::try_from(
-                Endpoint::new(base_url.to_string())
-                    .method(http.method.clone())
-                    .query(query)
-                    .body(http.body.clone())
-                    .encoding(http.encoding.clone()),
-            )
-            .map(|req_tmpl| req_tmpl.headers(headers))
-            .map_err(|e| ValidationError::new(e.to_string()))
-            .into()
-        })
-        .map(|req_template| {
-            // marge http and upstream on_request
-            let http_filter = http
-                .on_request
-                .clone()
-                .or(config_module.upstream.on_request.clone())
-                .map(|on_request| HttpFilter { on_request });
+                RequestTemplate::try_from(
+                    Endpoint::new(base_url.to_string())
+                        .method(http.method.clone())
+                        .query(query)
+                        .body(http.body.clone())
+                        .encoding(http.encoding.clone()),
+                )
+                .map(|req_tmpl| req_tmpl.headers(headers))
+                .map_err(|e| ValidationError::new(e.to_string()))
+                .into()
+            })
+            .zip(Resilience::make(
+                &upstream,
+                http.retry.as_ref(),
+                http.circuit_breaker.as_ref(),
+                http.timeout,
+            ))
+            .map(|(req_template, resilience)| {
+                // marge http and upstream on_request
+                let http_filter = http
+                    .on_request
+                    .clone()
+                    .or(upstream.on_request.clone())
+                    .map(|on_request| HttpFilter { on_request });
 
-            if !http.batch_key.is_empty() && http.method == Method::GET {
-                // Find a query parameter that contains a reference to the {{.value}} key
-                let key = http.query.iter().find_map(|q| {
-                    Mustache::parse(&q.value)
-                        .expression_contains("value")
-                        .then(|| q.key.clone())
-                });
-                IR::IO(IO::Http {
-                    req_template,
-                    group_by: Some(GroupBy::new(http.batch_key.clone(), key)),
-                    dl_id: None,
-                    http_filter,
-                    is_list,
-                    dedupe,
-                })
-            } else {
-                IR::IO(IO::Http {
-                    req_template,
-                    group_by: None,
-                    dl_id: None,
-                    http_filter,
-                    is_list,
-                    dedupe,
-                })
-            }
-        })
+                if !http.batch_key.is_empty() && http.method == Method::GET {
+                    // Find a query parameter that contains a reference to the {{.value}} key
+                    let key = http.query.iter().find_map(|q| {
+                        Mustache::parse(&q.value)
+                            .expression_contains("value")
+                            .then(|| q.key.clone())
+                    });
+                    IR::IO(IO::Http {
+                        req_template,
+                        group_by: Some(GroupBy::new(http.batch_key.clone(), key)),
+                        dl_id: None,
+                        http_filter,
+                        is_list,
+                        dedupe,
+                        resilience,
+                        upstream: http.upstream.clone(),
+                    })
+                } else {
+                    IR::IO(IO::Http {
+                        req_template,
+                        group_by: None,
+                        dl_id: None,
+                        http_filter,
+                        is_list,
+                        dedupe,
+                        resilience,
+                        upstream: http.upstream.clone(),
+                    })
+                }
+            })
+    })
 }
 
 pub fn update_http<'a>(
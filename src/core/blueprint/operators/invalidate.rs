@@ -0,0 +1,38 @@
+use crate::core::blueprint::FieldDefinition;
+use crate::core::config::{self, ConfigModule, Field};
+use crate::core::ir::model::IR;
+use crate::core::mustache::Mustache;
+use crate::core::try_fold::TryFold;
+use crate::core::valid::Valid;
+
+pub fn update_invalidate<'a>(
+    type_name: &'a str,
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        move |(config, field, _, _), mut b_field| {
+            let Some(invalidate) = field.invalidate.as_ref() else {
+                return Valid::succeed(b_field);
+            };
+            if config.schema.mutation.as_deref() != Some(type_name) {
+                return Valid::fail("@invalidate can only be used on mutation fields".to_owned());
+            }
+
+            let tags = invalidate
+                .tags
+                .iter()
+                .map(|tag| Mustache::parse(tag))
+                .collect();
+            b_field.resolver = Some(IR::Invalidate(
+                tags,
+                Box::new(
+                    b_field
+                        .resolver
+                        .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
+                ),
+            ));
+
+            Valid::succeed(b_field)
+        },
+    )
+}
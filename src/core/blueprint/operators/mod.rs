@@ -1,21 +1,29 @@
 mod apollo_federation;
 mod call;
+mod cost;
 mod enum_alias;
 mod expr;
 mod graphql;
 mod grpc;
 mod http;
+mod invalidate;
 mod js;
 mod modify;
 mod protected;
+mod rate_limit;
+mod subscription;
 
 pub use apollo_federation::*;
 pub use call::*;
+pub use cost::*;
 pub use enum_alias::*;
 pub use expr::*;
 pub use graphql::*;
 pub use grpc::*;
 pub use http::*;
+pub use invalidate::*;
 pub use js::*;
 pub use modify::*;
 pub use protected::*;
+pub use rate_limit::*;
+pub use subscription::*;
@@ -1,8 +1,10 @@
-use crate::core::blueprint::FieldDefinition;
+use std::collections::HashSet;
+
+use crate::core::blueprint::{AuthRequirement, FieldDefinition};
 use crate::core::config::{self, ConfigModule, Field};
 use crate::core::ir::model::IR;
 use crate::core::try_fold::TryFold;
-use crate::core::valid::Valid;
+use crate::core::valid::{Valid, Validator};
 
 pub fn update_protected<'a>(
     type_name: &'a str,
@@ -10,13 +12,21 @@ pub fn update_protected<'a>(
 {
     TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
         |(config, field, type_, _), mut b_field| {
-            if field.protected.is_some() // check the field itself has marked as protected
-                || type_.protected.is_some() // check the type that contains current field
-                || config // check that output type of the field is protected
+            let protected = [
+                // check the field itself has marked as protected
+                field.protected.as_ref(),
+                // check the type that contains current field
+                type_.protected.as_ref(),
+                // check that output type of the field is protected
+                config
                     .find_type(field.type_of.name())
-                    .and_then(|type_| type_.protected.as_ref())
-                    .is_some()
-            {
+                    .and_then(|type_| type_.protected.as_ref()),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+            if !protected.is_empty() {
                 if config.input_types().contains(type_name) {
                     return Valid::fail("Input types can not be protected".to_owned());
                 }
@@ -27,11 +37,52 @@ pub fn update_protected<'a>(
                     );
                 }
 
-                b_field.resolver = Some(IR::Protect(Box::new(
+                let extensions = config.extensions();
+                let provider_ids = extensions
+                    .htpasswd
+                    .iter()
+                    .filter_map(|htpasswd| htpasswd.id.as_deref())
+                    .chain(extensions.jwks.iter().filter_map(|jwks| jwks.id.as_deref()))
+                    .chain(
+                        extensions
+                            .introspection
+                            .iter()
+                            .filter_map(|introspection| introspection.id.as_deref()),
+                    )
+                    .chain(
+                        extensions
+                            .api_keys
+                            .iter()
+                            .filter_map(|api_keys| api_keys.id.as_deref()),
+                    )
+                    .collect::<HashSet<_>>();
+
+                let mut requirements: Vec<AuthRequirement> = Vec::new();
+                for requirement in protected.into_iter().map(AuthRequirement::from) {
+                    if !requirements.contains(&requirement) {
+                        requirements.push(requirement);
+                    }
+                }
+
+                return Valid::from_iter(requirements.iter(), |requirement| {
+                    Valid::from_iter(requirement.providers.iter(), |id| {
+                        Valid::<(), String>::fail(format!(
+                            "Auth provider with id `{id}` is not defined"
+                        ))
+                        .when(|| !provider_ids.contains(id.as_str()))
+                    })
+                })
+                .map(|_| {
+                    b_field.resolver = Some(IR::Protect(
+                        requirements,
+                        Box::new(
+                            b_field
+                                .resolver
+                                .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
+                        ),
+                    ));
                     b_field
-                        .resolver
-                        .unwrap_or(IR::ContextPath(vec![b_field.name.clone()])),
-                )));
+                });
             }
 
             Valid::succeed(b_field)
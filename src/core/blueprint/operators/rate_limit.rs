@@ -0,0 +1,39 @@
+use crate::core::blueprint::{FieldDefinition, RateLimit};
+use crate::core::config::{self, ConfigModule, Field};
+use crate::core::try_fold::TryFold;
+use crate::core::valid::{Valid, Validator};
+
+pub fn update_rate_limit<'a>(
+    type_name: &'a str,
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        |(config, field, type_, field_name), mut b_field| {
+            // the limit of the field takes precedence over the one of its type, which is
+            // shared by all the fields of the type
+            let rate_limit = field
+                .rate_limit
+                .as_ref()
+                .map(|rate_limit| (format!("{type_name}.{field_name}"), rate_limit))
+                .or_else(|| {
+                    type_
+                        .rate_limit
+                        .as_ref()
+                        .map(|rate_limit| (type_name.to_owned(), rate_limit))
+                });
+
+            let Some((scope, rate_limit)) = rate_limit else {
+                return Valid::succeed(b_field);
+            };
+
+            if config.input_types().contains(type_name) {
+                return Valid::fail("Input types can not be rate limited".to_owned());
+            }
+
+            RateLimit::make(scope, rate_limit).map(|rate_limit| {
+                b_field.rate_limit = Some(rate_limit);
+                b_field
+            })
+        },
+    )
+}
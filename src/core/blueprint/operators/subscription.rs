@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use crate::core::blueprint::FieldDefinition;
+use crate::core::config::{self, ConfigModule, Field, Resolver};
+use crate::core::ir::model::{Stream, StreamSource, IO, IR};
+use crate::core::try_fold::TryFold;
+use crate::core::valid::{Valid, Validator};
+
+pub fn update_subscription<'a>(
+    type_name: &'a str,
+) -> TryFold<'a, (&'a ConfigModule, &'a Field, &'a config::Type, &'a str), FieldDefinition, String>
+{
+    TryFold::<(&ConfigModule, &Field, &config::Type, &'a str), FieldDefinition, String>::new(
+        |(config_module, field, _, _), mut b_field| {
+            let poll_interval = match &field.resolver {
+                Some(Resolver::Http(http)) => http.poll_interval,
+                _ => None,
+            };
+
+            if config_module.schema.subscription.as_deref() != Some(type_name) {
+                return Valid::<(), String>::fail(
+                    "pollInterval can only be used on fields of the subscription type".to_owned(),
+                )
+                .when(|| poll_interval.is_some())
+                .map_to(b_field);
+            }
+
+            let source = match poll_interval {
+                Some(interval) => StreamSource::Poll(Duration::from_millis(interval)),
+                None => StreamSource::ServerSentEvents,
+            };
+
+            match b_field.resolver.take() {
+                Some(IR::IO(IO::Http { req_template, upstream, .. })) => {
+                    b_field.resolver = Some(IR::Stream(Stream { req_template, source, upstream }));
+                    Valid::succeed(b_field)
+                }
+                _ => Valid::fail("Subscription fields must be resolved using @http".to_owned()),
+            }
+        },
+    )
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use crate::core::config;
+use crate::core::mustache::Mustache;
+use crate::core::valid::{Valid, Validator};
+
+/// Token bucket limit set with `@rateLimit` on the server, a type or a field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    /// What the limit is set on, `@server`, a type name or `Type.field`.
+    /// Buckets are kept separately for each of them.
+    pub scope: String,
+    /// Identifies the client the bucket belongs to
+    pub key: Mustache,
+    pub capacity: u64,
+    /// Tokens added back to the bucket every `refill_interval`
+    pub refill_rate: u64,
+    pub refill_interval: Duration,
+}
+
+impl RateLimit {
+    pub const SERVER_SCOPE: &'static str = "@server";
+    const DEFAULT_KEY: &'static str = "{{.client.ip}}";
+    const DEFAULT_REFILL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn make(scope: impl Into<String>, rate_limit: &config::RateLimit) -> Valid<Self, String> {
+        let key = rate_limit.key.as_deref().unwrap_or(Self::DEFAULT_KEY);
+        let mustache = Mustache::parse(key);
+
+        Valid::from_iter(mustache.expression_segments(), |parts| {
+            Valid::<(), String>::fail(format!(
+                "Invalid rate limit key `{key}`: `{}` is not available, use headers, vars, env, auth.claims or client.ip",
+                parts.join(".")
+            ))
+            .when(|| !is_request_value(parts))
+        })
+        .map(|_| RateLimit {
+            scope: scope.into(),
+            key: mustache,
+            capacity: rate_limit.capacity.get(),
+            refill_rate: rate_limit
+                .refill_rate
+                .unwrap_or(rate_limit.capacity)
+                .get(),
+            refill_interval: rate_limit
+                .refill_interval
+                .map(|interval| Duration::from_millis(interval.get()))
+                .unwrap_or(Self::DEFAULT_REFILL_INTERVAL),
+        })
+    }
+
+    /// Whether the key refers to the claims of the verified identity
+    pub fn uses_claims(&self) -> bool {
+        self.key
+            .expression_segments()
+            .iter()
+            .any(|parts| parts.first().is_some_and(|head| head == "auth"))
+    }
+
+    /// Key of the bucket for the given client
+    pub fn bucket(&self, client: &str) -> String {
+        format!("{}:{}", self.scope, client)
+    }
+}
+
+/// Checks that the expression refers to a value that is known before the
+/// request is executed.
+fn is_request_value(parts: &[String]) -> bool {
+    match parts {
+        [head, _] if matches!(head.as_str(), "headers" | "vars" | "env") => true,
+        [head, claims, ..] if head == "auth" && claims == "claims" => true,
+        [head, ip] if head == "client" && ip == "ip" => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use super::*;
+
+    fn config(key: Option<&str>) -> config::RateLimit {
+        config::RateLimit {
+            capacity: NonZeroU64::new(10).unwrap(),
+            key: key.map(str::to_owned),
+            refill_interval: None,
+            refill_rate: None,
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let rate_limit = RateLimit::make("Query.users", &config(None))
+            .to_result()
+            .unwrap();
+
+        assert_eq!(rate_limit.key, Mustache::parse("{{.client.ip}}"));
+        assert!(!rate_limit.uses_claims());
+        assert_eq!(rate_limit.refill_rate, 10);
+        assert_eq!(rate_limit.refill_interval, Duration::from_secs(1));
+        assert_eq!(rate_limit.bucket("127.0.0.1"), "Query.users:127.0.0.1");
+    }
+
+    #[test]
+    fn test_key() {
+        let result = RateLimit::make(
+            "Query",
+            &config(Some("{{.headers.x-client-id}}-{{.auth.claims.org.id}}")),
+        )
+        .to_result();
+
+        assert!(result.unwrap().uses_claims());
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        let result = RateLimit::make("Query", &config(Some("{{.args.id}}"))).to_result();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::core::config;
+use crate::core::mustache::Mustache;
+use crate::core::valid::{Valid, Validator};
+
+/// How the requests of a resolver to its upstream are retried, bounded in
+/// time and isolated from the failures of the upstream host.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resilience {
+    pub retry: Option<Retry>,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Time allowed to resolve the field, retries included
+    pub timeout: Option<Duration>,
+    /// Header telling the upstream of the time left to respond
+    pub deadline_header: Option<String>,
+    /// Key by which the load balancer of the upstream picks the endpoint
+    pub hash_key: Option<Mustache>,
+}
+
+impl Resilience {
+    /// The policies set on the resolver replace the ones of `@upstream`.
+    pub fn make(
+        upstream: &config::Upstream,
+        retry: Option<&config::Retry>,
+        circuit_breaker: Option<&config::CircuitBreaker>,
+        timeout: Option<u64>,
+    ) -> Valid<Self, String> {
+        let retry = match retry.or(upstream.retry.as_ref()) {
+            Some(retry) => Retry::make(retry).trace("retry").map(Some),
+            None => Valid::none(),
+        };
+        let circuit_breaker = match circuit_breaker.or(upstream.circuit_breaker.as_ref()) {
+            Some(circuit_breaker) => CircuitBreaker::make(circuit_breaker)
+                .trace("circuitBreaker")
+                .map(Some),
+            None => Valid::none(),
+        };
+
+        let timeout = match timeout {
+            Some(0) => Valid::fail("timeout must be greater than 0".to_string()).trace("timeout"),
+            timeout => Valid::succeed(timeout.map(Duration::from_millis)),
+        };
+
+        retry
+            .zip(circuit_breaker)
+            .zip(timeout)
+            .map(|((retry, circuit_breaker), timeout)| Resilience {
+                retry,
+                circuit_breaker,
+                timeout,
+                deadline_header: upstream.deadline_header.clone(),
+                hash_key: upstream
+                    .load_balancer
+                    .as_ref()
+                    .and_then(|load_balancer| load_balancer.hash_key.as_deref())
+                    .map(Mustache::parse),
+            })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Retry {
+    /// Attempts made in total, the first one included
+    pub max_attempts: usize,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub status_codes: Vec<u16>,
+}
+
+impl Retry {
+    const DEFAULT_MAX_ATTEMPTS: usize = 3;
+    const DEFAULT_BACKOFF: Duration = Duration::from_millis(100);
+    const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+    const DEFAULT_STATUS_CODES: [u16; 4] = [429, 502, 503, 504];
+
+    pub fn make(retry: &config::Retry) -> Valid<Self, String> {
+        let max_attempts = retry.max_attempts.unwrap_or(Self::DEFAULT_MAX_ATTEMPTS);
+        let status_codes = if retry.status_codes.is_empty() {
+            Self::DEFAULT_STATUS_CODES.to_vec()
+        } else {
+            retry.status_codes.clone()
+        };
+
+        Valid::<(), String>::fail("maxAttempts must be greater than 0".to_string())
+            .when(|| max_attempts == 0)
+            .and(Valid::from_iter(status_codes.iter(), |code| {
+                Valid::<(), String>::fail(format!("{code} is not a valid status code"))
+                    .when(|| !(100..600).contains(code))
+            }))
+            .map(|_| Retry {
+                max_attempts,
+                backoff: retry
+                    .backoff
+                    .map(Duration::from_millis)
+                    .unwrap_or(Self::DEFAULT_BACKOFF),
+                max_backoff: retry
+                    .max_backoff
+                    .map(Duration::from_millis)
+                    .unwrap_or(Self::DEFAULT_MAX_BACKOFF),
+                status_codes,
+            })
+    }
+
+    /// Delay before the given retry, starting at 1, with a random jitter.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let exponent = u32::try_from(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.max_backoff);
+        let millis = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+        let jitter = rand::thread_rng().gen_range(0..=millis / 2);
+
+        Duration::from_millis(millis - jitter)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitBreaker {
+    /// Percentage of failures from which the circuit opens
+    pub failure_rate: u64,
+    pub minimum_requests: u64,
+    pub window: Duration,
+    pub open_duration: Duration,
+    pub half_open_requests: u64,
+}
+
+impl CircuitBreaker {
+    const DEFAULT_FAILURE_RATE: u64 = 50;
+    const DEFAULT_MINIMUM_REQUESTS: u64 = 20;
+    const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+    const DEFAULT_OPEN_DURATION: Duration = Duration::from_secs(30);
+    const DEFAULT_HALF_OPEN_REQUESTS: u64 = 3;
+
+    pub fn make(circuit_breaker: &config::CircuitBreaker) -> Valid<Self, String> {
+        let failure_rate = circuit_breaker
+            .failure_rate
+            .unwrap_or(Self::DEFAULT_FAILURE_RATE);
+        let half_open_requests = circuit_breaker
+            .half_open_requests
+            .unwrap_or(Self::DEFAULT_HALF_OPEN_REQUESTS);
+
+        Valid::<(), String>::fail("failureRate must be between 1 and 100".to_string())
+            .when(|| !(1..=100).contains(&failure_rate))
+            .and(
+                Valid::<(), String>::fail("halfOpenRequests must be greater than 0".to_string())
+                    .when(|| half_open_requests == 0),
+            )
+            .map(|_| CircuitBreaker {
+                failure_rate,
+                minimum_requests: circuit_breaker
+                    .minimum_requests
+                    .unwrap_or(Self::DEFAULT_MINIMUM_REQUESTS),
+                window: circuit_breaker
+                    .window
+                    .map(Duration::from_millis)
+                    .unwrap_or(Self::DEFAULT_WINDOW),
+                open_duration: circuit_breaker
+                    .open_duration
+                    .map(Duration::from_millis)
+                    .unwrap_or(Self::DEFAULT_OPEN_DURATION),
+                half_open_requests,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let retry = Retry::make(&config::Retry {
+            backoff: Some(100),
+            max_backoff: Some(300),
+            ..Default::default()
+        })
+        .to_result()
+        .unwrap();
+
+        for (attempt, delay) in [(1, 100), (2, 200), (3, 300), (10, 300)] {
+            let backoff = retry.backoff(attempt).as_millis();
+            assert!((delay / 2..=delay).contains(&backoff), "{backoff}");
+        }
+    }
+
+    #[test]
+    fn test_field_overrides_upstream() {
+        let upstream = config::Upstream {
+            retry: Some(config::Retry { max_attempts: Some(5), ..Default::default() }),
+            circuit_breaker: Some(Default::default()),
+            ..Default::default()
+        };
+        let retry = config::Retry { max_attempts: Some(2), ..Default::default() };
+
+        let resilience = Resilience::make(&upstream, Some(&retry), None, Some(500))
+            .to_result()
+            .unwrap();
+
+        assert_eq!(resilience.retry.unwrap().max_attempts, 2);
+        assert_eq!(resilience.circuit_breaker.unwrap().failure_rate, 50);
+        assert_eq!(resilience.timeout, Some(Duration::from_millis(500)));
+    }
+}
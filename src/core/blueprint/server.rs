@@ -6,12 +6,19 @@ use std::time::Duration;
 use derive_setters::Setters;
 use http::header::{HeaderMap, HeaderName, HeaderValue};
 use rustls_pki_types::CertificateDer;
+use url::Url;
 
-use super::Auth;
+use super::{Auth, RateLimit};
 use crate::core::blueprint::Cors;
+use crate::core::cache::{Eviction, DEFAULT_MAX_ENTRIES};
 use crate::core::config::{self, ConfigModule, HttpVersion, PrivateKey, Routes};
 use crate::core::valid::{Valid, ValidationError, Validator};
 
+const DEFAULT_REDIS_POOL_SIZE: usize = 4;
+const DEFAULT_REDIS_KEY_PREFIX: &str = "tailcall:";
+const DEFAULT_PERSISTED_QUERY_SIZE: usize = 16 * 1024;
+const DEFAULT_PERSISTED_QUERY_ENTRIES: usize = 10000;
+
 #[derive(Clone, Debug, Setters)]
 pub struct Server {
     pub enable_jit: bool,
@@ -36,6 +43,71 @@ pub struct Server {
     pub experimental_headers: HashSet<HeaderName>,
     pub auth: Option<Auth>,
     pub routes: Routes,
+    pub rate_limit: Option<RateLimit>,
+    pub query_limits: QueryLimits,
+    /// Documents of the only operations that can be executed, when trusted
+    /// documents are enabled
+    pub trusted_documents: Option<Vec<String>>,
+    pub response_cache: Option<ResponseCache>,
+    pub entity_cache: EntityCache,
+    /// Limits of the documents registered with automatic persisted queries,
+    /// when they are enabled
+    pub persisted_queries: Option<PersistedQueries>,
+}
+
+/// Limits of the in-memory entity cache.
+#[derive(Clone, Debug)]
+pub struct EntityCache {
+    pub max_entries: usize,
+    pub max_size: Option<usize>,
+    pub eviction: Eviction,
+    pub redis: Option<RedisCache>,
+}
+
+/// Limits of the documents registered with automatic persisted queries.
+#[derive(Clone, Debug)]
+pub struct PersistedQueries {
+    pub max_document_size: usize,
+    pub max_entries: usize,
+}
+
+/// Redis server storing the cached values instead of the in-memory cache.
+#[derive(Clone, Debug)]
+pub struct RedisCache {
+    pub url: Url,
+    pub encoding: config::CacheEncoding,
+    pub key_prefix: String,
+    pub pool_size: usize,
+}
+
+/// What the cached responses of whole queries vary on.
+#[derive(Clone, Debug, Default)]
+pub struct ResponseCache {
+    pub headers: Vec<HeaderName>,
+    pub claims: Vec<Vec<String>>,
+}
+
+impl ResponseCache {
+    /// Whether the cached responses vary on the claim at the given path.
+    pub fn varies_on_claim<T: AsRef<str>>(&self, path: &[T]) -> bool {
+        self.claims.iter().any(|claim| {
+            claim.len() == path.len()
+                && claim
+                    .iter()
+                    .zip(path)
+                    .all(|(name, part)| name == part.as_ref())
+        })
+    }
+}
+
+/// Limits on the shape of the queries, checked while they are planned.
+#[derive(Clone, Debug, Default)]
+pub struct QueryLimits {
+    pub max_aliases: Option<usize>,
+    pub max_cost: Option<u64>,
+    pub max_depth: Option<usize>,
+    pub max_fields: Option<usize>,
+    pub max_root_fields: Option<usize>,
 }
 
 /// Mimic of mini_v8::Script that's wasm compatible
@@ -125,8 +197,30 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                     .and_then(|headers| headers.get_cors()),
             ))
             .fuse(Auth::make(&config_module))
+            .fuse(validate_rate_limit(config_server.rate_limit.as_ref()))
+            .fuse(validate_trusted_documents(&config_module))
+            .fuse(validate_response_cache(
+                config_server.response_cache.as_ref(),
+            ))
+            .fuse(validate_entity_cache(config_server.entity_cache.as_ref()))
+            .fuse(validate_persisted_queries(
+                config_server.persisted_queries.as_ref(),
+            ))
             .map(
-                |(hostname, http, response_headers, script, experimental_headers, cors, auth)| {
+                |(
+                    hostname,
+                    http,
+                    response_headers,
+                    script,
+                    experimental_headers,
+                    cors,
+                    auth,
+                    rate_limit,
+                    trusted_documents,
+                    response_cache,
+                    entity_cache,
+                    persisted_queries,
+                )| {
                     Server {
                         enable_jit: (config_server).enable_jit(),
                         enable_apollo_tracing: (config_server).enable_apollo_tracing(),
@@ -150,6 +244,18 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
                         cors,
                         auth,
                         routes: config_server.get_routes(),
+                        rate_limit,
+                        query_limits: QueryLimits {
+                            max_aliases: config_server.max_aliases,
+                            max_cost: config_server.max_cost,
+                            max_depth: config_server.max_depth,
+                            max_fields: config_server.max_fields,
+                            max_root_fields: config_server.max_root_fields,
+                        },
+                        trusted_documents,
+                        response_cache,
+                        entity_cache,
+                        persisted_queries,
                     }
                 },
             )
@@ -157,6 +263,142 @@ impl TryFrom<crate::core::config::ConfigModule> for Server {
     }
 }
 
+fn validate_trusted_documents(config_module: &ConfigModule) -> Valid<Option<Vec<String>>, String> {
+    let operations = &config_module.extensions().operations;
+
+    if !config_module.server.enable_trusted_documents() {
+        Valid::succeed(None)
+    } else if operations.is_empty() {
+        Valid::fail("Trusted documents require a link to an operation file".to_string())
+            .trace("trustedDocuments")
+            .trace("@server")
+            .trace("schema")
+    } else {
+        Valid::succeed(Some(operations.clone()))
+    }
+}
+
+fn validate_entity_cache(entity_cache: Option<&config::EntityCache>) -> Valid<EntityCache, String> {
+    let entity_cache = entity_cache.cloned().unwrap_or_default();
+    let max_entries = entity_cache.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES);
+    let eviction = match entity_cache.eviction.unwrap_or_default() {
+        config::Eviction::LRU => Eviction::Lru,
+        config::Eviction::TinyLFU => Eviction::TinyLfu,
+    };
+
+    let max_entries = if max_entries == 0 {
+        Valid::fail("maxEntries must be greater than 0".to_string())
+    } else {
+        Valid::succeed(max_entries)
+    };
+    let max_size = match entity_cache.max_size {
+        Some(0) => Valid::fail("maxSize must be greater than 0".to_string()),
+        max_size => Valid::succeed(max_size),
+    };
+
+    max_entries
+        .zip(max_size)
+        .zip(validate_redis_cache(entity_cache.redis.as_ref()))
+        .map(|((max_entries, max_size), redis)| EntityCache {
+            max_entries,
+            max_size,
+            eviction,
+            redis,
+        })
+        .trace("entityCache")
+        .trace("@server")
+        .trace("schema")
+}
+
+fn validate_redis_cache(redis: Option<&config::RedisCache>) -> Valid<Option<RedisCache>, String> {
+    let Some(redis) = redis else {
+        return Valid::none();
+    };
+
+    let url = match Url::parse(&redis.url) {
+        Ok(url) if ["redis", "rediss", "redis+unix", "unix"].contains(&url.scheme()) => {
+            Valid::succeed(url)
+        }
+        Ok(url) => Valid::fail(format!("unsupported scheme {}", url.scheme())),
+        Err(e) => Valid::fail(e.to_string()),
+    }
+    .trace("url");
+
+    let pool_size = match redis.pool_size.unwrap_or(DEFAULT_REDIS_POOL_SIZE) {
+        0 => Valid::fail("poolSize must be greater than 0".to_string()),
+        pool_size => Valid::succeed(pool_size),
+    };
+
+    url.zip(pool_size)
+        .map(|(url, pool_size)| RedisCache {
+            url,
+            encoding: redis.encoding.unwrap_or_default(),
+            key_prefix: redis
+                .key_prefix
+                .clone()
+                .unwrap_or_else(|| DEFAULT_REDIS_KEY_PREFIX.to_string()),
+            pool_size,
+        })
+        .some()
+        .trace("redis")
+}
+
+fn validate_persisted_queries(
+    persisted_queries: Option<&config::PersistedQueries>,
+) -> Valid<Option<PersistedQueries>, String> {
+    let Some(persisted_queries) = persisted_queries else {
+        return Valid::none();
+    };
+
+    let max_document_size = match persisted_queries
+        .max_document_size
+        .unwrap_or(DEFAULT_PERSISTED_QUERY_SIZE)
+    {
+        0 => Valid::fail("maxDocumentSize must be greater than 0".to_string()),
+        max_document_size => Valid::succeed(max_document_size),
+    };
+    let max_entries = match persisted_queries
+        .max_entries
+        .unwrap_or(DEFAULT_PERSISTED_QUERY_ENTRIES)
+    {
+        0 => Valid::fail("maxEntries must be greater than 0".to_string()),
+        max_entries => Valid::succeed(max_entries),
+    };
+
+    max_document_size
+        .zip(max_entries)
+        .map(|(max_document_size, max_entries)| PersistedQueries { max_document_size, max_entries })
+        .some()
+        .trace("persistedQueries")
+        .trace("@server")
+        .trace("schema")
+}
+
+fn validate_response_cache(
+    response_cache: Option<&config::ResponseCache>,
+) -> Valid<Option<ResponseCache>, String> {
+    let Some(response_cache) = response_cache else {
+        return Valid::none();
+    };
+
+    Valid::from_iter(response_cache.headers.iter(), |name| {
+        Valid::from(HeaderName::from_str(name).map_err(|e| ValidationError::new(e.to_string())))
+            .trace(name)
+    })
+    .map(|headers| ResponseCache {
+        headers,
+        claims: response_cache
+            .claims
+            .iter()
+            .map(|claim| claim.split('.').map(str::to_owned).collect())
+            .collect(),
+    })
+    .some()
+    .trace("responseCache")
+    .trace("@server")
+    .trace("schema")
+}
+
 fn to_script(config_module: &crate::core::config::ConfigModule) -> Valid<Option<Script>, String> {
     config_module.extensions().script.as_ref().map_or_else(
         || Valid::succeed(None),
@@ -182,6 +424,16 @@ fn validate_cors(cors: Option<config::cors::Cors>) -> Valid<Option<Cors>, String
         .trace("schema")
 }
 
+fn validate_rate_limit(rate_limit: Option<&config::RateLimit>) -> Valid<Option<RateLimit>, String> {
+    match rate_limit {
+        Some(rate_limit) => RateLimit::make(RateLimit::SERVER_SCOPE, rate_limit).some(),
+        None => Valid::none(),
+    }
+    .trace("rateLimit")
+    .trace("@server")
+    .trace("schema")
+}
+
 fn validate_hostname(hostname: String) -> Valid<IpAddr, String> {
     if hostname == "localhost" {
         Valid::succeed(IpAddr::from([127, 0, 0, 1]))
@@ -1,15 +1,45 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use derive_setters::Setters;
 
-use crate::core::config::{self, Batch, ConfigModule};
+use crate::core::config::{self, BalancingStrategy, Batch, ConfigModule, Extensions};
+use crate::core::merge_right::MergeRight;
 use crate::core::valid::{Valid, ValidationError, Validator};
 
+const DEFAULT_HTTP_CACHE_DISK_SIZE: u64 = 1024 * 1024 * 1024;
+const DEFAULT_MAX_FAILURES: u64 = 5;
+const DEFAULT_EJECTION_TIME: u64 = 30_000;
+
 #[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
 pub struct Proxy {
     pub url: String,
 }
 
+#[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
+pub struct HttpCacheDisk {
+    pub path: String,
+    pub max_size: u64,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
+pub struct LoadBalancer {
+    pub endpoints: Vec<String>,
+    pub strategy: BalancingStrategy,
+    pub max_failures: u64,
+    /// Milliseconds
+    pub max_latency: Option<u64>,
+    /// Milliseconds
+    pub ejection_time: u64,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, schemars::JsonSchema)]
+pub struct Tls {
+    /// PEM of the client certificate followed by its private key
+    pub identity: Option<Vec<u8>>,
+    /// PEM of the trusted CA certificates
+    pub ca: Vec<Vec<u8>>,
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Setters, schemars::JsonSchema)]
 pub struct Upstream {
     pub pool_idle_timeout: u64,
@@ -25,10 +55,14 @@ pub struct Upstream {
     pub allowed_headers: BTreeSet<String>,
     pub base_url: Option<String>,
     pub http_cache: u64,
+    pub http_cache_disk: Option<HttpCacheDisk>,
+    pub deadline_header: Option<String>,
+    pub load_balancer: Option<LoadBalancer>,
     pub batch: Option<Batch>,
     pub http2_only: bool,
     pub on_request: Option<String>,
     pub verify_ssl: bool,
+    pub tls: Option<Tls>,
 }
 
 impl Upstream {
@@ -54,41 +88,128 @@ impl TryFrom<&ConfigModule> for Upstream {
     type Error = ValidationError<String>;
 
     fn try_from(config_module: &ConfigModule) -> Result<Self, Self::Error> {
-        let config_upstream = config_module.upstream.clone();
+        Valid::<(), String>::fail("the default @upstream can't have a name".to_string())
+            .when(|| config_module.upstream.name.is_some())
+            .trace("name")
+            .trace("@upstream")
+            .trace("schema")
+            .and(make(
+                &config_module.upstream,
+                allowed_headers(config_module),
+                config_module.extensions(),
+            ))
+            .to_result()
+    }
+}
 
-        let mut allowed_headers = config_upstream.get_allowed_headers();
+/// Builds the upstreams with a name, completed with the settings of the
+/// default upstream.
+pub fn upstreams(config_module: &ConfigModule) -> Valid<BTreeMap<String, Upstream>, String> {
+    let allowed_headers = allowed_headers(config_module);
+    let mut names = BTreeSet::new();
 
-        if config_module.extensions().has_auth() {
-            // force add auth specific headers to use it to make actual validation
-            allowed_headers.insert(http::header::AUTHORIZATION.to_string());
-        }
+    Valid::from_iter(config_module.upstreams.iter(), |upstream| {
+        let Some(name) = upstream.name.clone() else {
+            return Valid::fail("upstreams must have a name".to_string())
+                .trace("@upstream")
+                .trace("schema");
+        };
 
-        get_batch(&config_upstream)
-            .fuse(get_base_url(&config_upstream))
-            .fuse(get_proxy(&config_upstream))
-            .map(|(batch, base_url, proxy)| Upstream {
-                pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
-                pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
-                keep_alive_interval: (config_upstream).get_keep_alive_interval(),
-                keep_alive_timeout: (config_upstream).get_keep_alive_timeout(),
-                keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
-                proxy,
-                connect_timeout: (config_upstream).get_connect_timeout(),
-                timeout: (config_upstream).get_timeout(),
-                tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
-                user_agent: (config_upstream).get_user_agent(),
-                allowed_headers,
-                base_url,
-                http_cache: (config_upstream).get_http_cache_size(),
-                batch,
-                http2_only: (config_upstream).get_http_2_only(),
-                on_request: (config_upstream).get_on_request(),
-                verify_ssl: (config_upstream).get_verify_ssl(),
-            })
-            .to_result()
+        // both are shared by all the upstreams
+        Valid::<(), String>::fail(format!("the upstream {name} is defined more than once"))
+            .when(|| !names.insert(name.clone()))
+            .and(
+                Valid::<(), String>::fail("batch can only be set on the default @upstream".into())
+                    .when(|| upstream.batch.is_some())
+                    .trace("batch"),
+            )
+            .and(
+                Valid::<(), String>::fail(
+                    "allowedHeaders can only be set on the default @upstream".into(),
+                )
+                .when(|| upstream.allowed_headers.is_some())
+                .trace("allowedHeaders"),
+            )
+            .trace("@upstream")
+            .trace("schema")
+            .and(make(
+                &config_module.upstream.clone().merge_right(upstream.clone()),
+                allowed_headers.clone(),
+                config_module.extensions(),
+            ))
+            .map(|upstream| (name, upstream))
+    })
+    .map(BTreeMap::from_iter)
+}
+
+/// The upstream the requests of a resolver go through, the default one unless
+/// it's selected by name.
+pub fn resolver_upstream(
+    config_module: &ConfigModule,
+    name: Option<&str>,
+) -> Valid<config::Upstream, String> {
+    match name {
+        Some(name) => Valid::from_option(
+            config_module.find_upstream(name),
+            format!("no upstream named {name}"),
+        )
+        .trace("upstream"),
+        None => Valid::succeed(config_module.upstream.clone()),
     }
 }
 
+fn allowed_headers(config_module: &ConfigModule) -> BTreeSet<String> {
+    let mut allowed_headers = config_module.upstream.get_allowed_headers();
+
+    if config_module.extensions().has_auth() {
+        // force add auth specific headers to use it to make actual validation
+        allowed_headers.insert(http::header::AUTHORIZATION.to_string());
+    }
+
+    allowed_headers
+}
+
+fn make(
+    config_upstream: &config::Upstream,
+    allowed_headers: BTreeSet<String>,
+    extensions: &Extensions,
+) -> Valid<Upstream, String> {
+    get_batch(config_upstream)
+        .fuse(get_base_url(config_upstream))
+        .fuse(get_proxy(config_upstream))
+        .fuse(get_http_cache_disk(config_upstream))
+        .fuse(get_deadline_header(config_upstream))
+        .fuse(get_load_balancer(config_upstream))
+        .fuse(get_tls(config_upstream, extensions))
+        .map(
+            |(batch, base_url, proxy, http_cache_disk, deadline_header, load_balancer, tls)| {
+                Upstream {
+                    pool_idle_timeout: (config_upstream).get_pool_idle_timeout(),
+                    pool_max_idle_per_host: (config_upstream).get_pool_max_idle_per_host(),
+                    keep_alive_interval: (config_upstream).get_keep_alive_interval(),
+                    keep_alive_timeout: (config_upstream).get_keep_alive_timeout(),
+                    keep_alive_while_idle: (config_upstream).get_keep_alive_while_idle(),
+                    proxy,
+                    connect_timeout: (config_upstream).get_connect_timeout(),
+                    timeout: (config_upstream).get_timeout(),
+                    tcp_keep_alive: (config_upstream).get_tcp_keep_alive(),
+                    user_agent: (config_upstream).get_user_agent(),
+                    allowed_headers,
+                    base_url,
+                    http_cache: (config_upstream).get_http_cache_size(),
+                    http_cache_disk,
+                    deadline_header,
+                    load_balancer,
+                    batch,
+                    http2_only: (config_upstream).get_http_2_only(),
+                    on_request: (config_upstream).get_on_request(),
+                    verify_ssl: (config_upstream).get_verify_ssl(),
+                    tls,
+                }
+            },
+        )
+}
+
 fn get_batch(upstream: &config::Upstream) -> Valid<Option<Batch>, String> {
     upstream.batch.as_ref().map_or_else(
         || Valid::succeed(None),
@@ -118,3 +239,141 @@ fn get_proxy(upstream: &config::Upstream) -> Valid<Option<Proxy>, String> {
         Valid::succeed(None)
     }
 }
+
+fn get_http_cache_disk(upstream: &config::Upstream) -> Valid<Option<HttpCacheDisk>, String> {
+    let Some(disk) = upstream.http_cache_disk.as_ref() else {
+        return Valid::none();
+    };
+
+    match disk.max_size.unwrap_or(DEFAULT_HTTP_CACHE_DISK_SIZE) {
+        0 => Valid::fail("maxSize must be greater than 0".to_string()),
+        max_size => Valid::succeed(Some(HttpCacheDisk { path: disk.path.clone(), max_size })),
+    }
+    .trace("httpCacheDisk")
+    .trace("@upstream")
+    .trace("schema")
+}
+
+fn get_deadline_header(upstream: &config::Upstream) -> Valid<Option<String>, String> {
+    let Some(header) = upstream.deadline_header.as_ref() else {
+        return Valid::none();
+    };
+
+    match http::HeaderName::try_from(header.as_str()) {
+        Ok(name) => Valid::succeed(Some(name.to_string())),
+        Err(_) => Valid::fail(format!("{header} is not a valid header name")),
+    }
+    .trace("deadlineHeader")
+    .trace("@upstream")
+    .trace("schema")
+}
+
+fn get_load_balancer(upstream: &config::Upstream) -> Valid<Option<LoadBalancer>, String> {
+    let Some(load_balancer) = upstream.load_balancer.as_ref() else {
+        return Valid::none();
+    };
+    let strategy = load_balancer.strategy.unwrap_or_default();
+    let max_failures = load_balancer.max_failures.unwrap_or(DEFAULT_MAX_FAILURES);
+
+    let endpoints =
+        Valid::from_iter(
+            load_balancer.endpoints.iter(),
+            |endpoint| match reqwest::Url::parse(endpoint) {
+                Ok(url) if url.has_host() && matches!(url.scheme(), "http" | "https") => {
+                    Valid::succeed(endpoint.clone())
+                }
+                _ => Valid::fail(format!("{endpoint} is not a valid endpoint")),
+            },
+        )
+        .and_then(|endpoints| {
+            Valid::<(), String>::fail("at least one endpoint is required".to_string())
+                .when(|| endpoints.is_empty())
+                .map_to(endpoints)
+        })
+        .trace("endpoints");
+
+    Valid::<(), String>::fail("loadBalancer requires a baseURL".to_string())
+        .when(|| upstream.base_url.is_none())
+        .and(
+            Valid::<(), String>::fail("maxFailures must be greater than 0".to_string())
+                .when(|| max_failures == 0)
+                .trace("maxFailures"),
+        )
+        .and(
+            Valid::<(), String>::fail(
+                "hashKey is only used by the ConsistentHash strategy".to_string(),
+            )
+            .when(|| {
+                load_balancer.hash_key.is_some() && strategy != BalancingStrategy::ConsistentHash
+            })
+            .trace("hashKey"),
+        )
+        .and(endpoints)
+        .map(|endpoints| {
+            Some(LoadBalancer {
+                endpoints,
+                strategy,
+                max_failures,
+                max_latency: load_balancer.max_latency,
+                ejection_time: load_balancer.ejection_time.unwrap_or(DEFAULT_EJECTION_TIME),
+            })
+        })
+        .trace("loadBalancer")
+        .trace("@upstream")
+        .trace("schema")
+}
+
+fn get_tls(upstream: &config::Upstream, extensions: &Extensions) -> Valid<Option<Tls>, String> {
+    let Some(tls) = upstream.tls.as_ref() else {
+        return Valid::none();
+    };
+
+    // the PEM of a link, checked to hold at least one item of the expected kind
+    let pem = |id: &String, is_kind: fn(&rustls_pemfile::Item) -> bool, kind: &str| {
+        let content = extensions
+            .upstream_tls
+            .iter()
+            .find(|content| content.id.as_ref() == Some(id));
+        match content {
+            None => Valid::fail(format!("no Cert or Key link with id {id}")),
+            Some(content) => match rustls_pemfile::read_all(&mut content.as_bytes()) {
+                Ok(items) if items.iter().any(is_kind) => {
+                    Valid::succeed(content.as_bytes().to_vec())
+                }
+                _ => Valid::fail(format!("the link {id} doesn't contain a {kind}")),
+            },
+        }
+    };
+    let is_cert: fn(&rustls_pemfile::Item) -> bool =
+        |item| matches!(item, rustls_pemfile::Item::X509Certificate(_));
+    let is_key: fn(&rustls_pemfile::Item) -> bool = |item| {
+        matches!(
+            item,
+            rustls_pemfile::Item::RSAKey(_)
+                | rustls_pemfile::Item::PKCS8Key(_)
+                | rustls_pemfile::Item::ECKey(_)
+        )
+    };
+
+    let identity = match (&tls.cert, &tls.key) {
+        (Some(cert), Some(key)) => pem(cert, is_cert, "certificate")
+            .trace("cert")
+            .zip(pem(key, is_key, "private key").trace("key"))
+            .map(|(mut cert, key)| {
+                cert.push(b'\n');
+                cert.extend(key);
+                Some(cert)
+            }),
+        (Some(_), None) => Valid::fail("cert requires a key".to_string()).trace("key"),
+        (None, Some(_)) => Valid::fail("key requires a cert".to_string()).trace("cert"),
+        (None, None) => Valid::none(),
+    };
+    let ca = Valid::from_iter(tls.ca.iter(), |id| pem(id, is_cert, "certificate")).trace("ca");
+
+    identity
+        .zip(ca)
+        .map(|(identity, ca)| Some(Tls { identity, ca }))
+        .trace("tls")
+        .trace("@upstream")
+        .trace("schema")
+}
@@ -1,22 +1,141 @@
-use std::hash::Hash;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroU64;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 
-use ttl_cache::TtlCache;
+use async_graphql_value::ConstValue;
+use lru::LruCache;
+use tailcall_hasher::TailcallHasher;
 
 use super::error::Result;
+use super::sketch::FrequencySketch;
+
+pub const DEFAULT_MAX_ENTRIES: usize = 100000;
+
+/// Most shards the entries are split between, each behind its own lock, so
+/// that concurrent requests rarely wait on each other.
+const MAX_SHARDS: usize = 16;
+
+/// Least entries of a shard, smaller caches aren't split.
+const MIN_SHARD_ENTRIES: usize = 4096;
+
+/// Policy choosing the entries evicted once the cache is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// Evicts the least recently used entries.
+    #[default]
+    Lru,
+    /// Evicts the least recently used entries, unless they're accessed more
+    /// often than the entries replacing them, which are then not cached.
+    TinyLfu,
+}
+
+/// Counters of a cache, exported as metrics.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: u64,
+    /// Estimated size of the entries in bytes, when the cache is bounded by
+    /// size
+    pub size: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+    size: usize,
+    /// Tags the key is associated with, to remove it from their index along
+    /// with the entry
+    tags: Vec<String>,
+}
+
+struct Shard<K: Hash + Eq, V> {
+    entries: LruCache<K, Entry<V>>,
+    size: usize,
+    sketch: Option<FrequencySketch>,
+}
+
+impl<K: Hash + Eq, V> Shard<K, V> {
+    fn remove(&mut self, key: &K) -> Option<Entry<V>> {
+        let entry = self.entries.pop(key)?;
+        self.size -= entry.size;
+        Some(entry)
+    }
+
+    /// Inserts the entry, evicting entries until it fits within the limits.
+    /// The value of a cached key is updated in place, keeping its tags, while
+    /// new keys go through the admission of TinyLFU. Returns the evicted
+    /// entries.
+    fn insert(
+        &mut self,
+        key: K,
+        mut entry: Entry<V>,
+        max_entries: usize,
+        max_size: Option<usize>,
+    ) -> Vec<(K, Entry<V>)> {
+        let mut evicted = vec![];
+
+        if let Some(cached) = self.entries.get_mut(&key) {
+            entry.tags = std::mem::take(&mut cached.tags);
+            self.size = self.size - cached.size + entry.size;
+            *cached = entry;
+
+            // the updated entry is the most recently used, so it's evicted last
+            while max_size.is_some_and(|max_size| self.size > max_size) {
+                let Some((victim, victim_entry)) = self.entries.pop_lru() else {
+                    break;
+                };
+                self.size -= victim_entry.size;
+                evicted.push((victim, victim_entry));
+            }
+            return evicted;
+        }
+
+        let key_hash = hash(&key);
+        while self.entries.len() >= max_entries
+            || max_size.is_some_and(|max_size| self.size + entry.size > max_size)
+        {
+            let Some((victim, victim_entry)) = self.entries.peek_lru() else {
+                break;
+            };
+            if let Some(sketch) = self.sketch.as_ref() {
+                // expired entries make room regardless of how often they were accessed
+                if victim_entry.expires_at > Instant::now()
+                    && sketch.frequency(hash(victim)) > sketch.frequency(key_hash)
+                {
+                    return evicted;
+                }
+            }
+            if let Some((victim, victim_entry)) = self.entries.pop_lru() {
+                self.size -= victim_entry.size;
+                evicted.push((victim, victim_entry));
+            }
+        }
+
+        self.size += entry.size;
+        self.entries.put(key, entry);
+        evicted
+    }
+}
 
 pub struct InMemoryCache<K: Hash + Eq, V> {
-    data: Arc<RwLock<TtlCache<K, V>>>,
+    shards: Vec<Mutex<Shard<K, V>>>,
+    /// Limits of each shard
+    max_entries: usize,
+    max_size: Option<usize>,
+    weigher: fn(&V) -> usize,
+    /// Keys associated with each tag, the keys being removed along with their
+    /// entries
+    tags: RwLock<HashMap<String, HashSet<K>>>,
     hits: AtomicUsize,
     miss: AtomicUsize,
+    evictions: AtomicUsize,
 }
 
-// TODO: take this from the user instead of hardcoding it
-const CACHE_CAPACITY: usize = 100000;
-
 impl<K: Hash + Eq, V: Clone> Default for InMemoryCache<K, V> {
     fn default() -> Self {
         Self::new()
@@ -25,50 +144,211 @@ impl<K: Hash + Eq, V: Clone> Default for InMemoryCache<K, V> {
 
 impl<K: Hash + Eq, V: Clone> InMemoryCache<K, V> {
     pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_ENTRIES, Eviction::default())
+    }
+
+    /// A cache holding at most `max_entries` entries, evicted with the given
+    /// policy.
+    pub fn with_capacity(max_entries: usize, eviction: Eviction) -> Self {
+        let max_entries = max_entries.max(1);
+        let shard_count = (max_entries / MIN_SHARD_ENTRIES).clamp(1, MAX_SHARDS);
+        let shard_entries = max_entries.div_ceil(shard_count);
+        let shards = (0..shard_count)
+            .map(|_| {
+                Mutex::new(Shard {
+                    entries: LruCache::unbounded(),
+                    size: 0,
+                    sketch: match eviction {
+                        Eviction::Lru => None,
+                        Eviction::TinyLfu => Some(FrequencySketch::new(shard_entries)),
+                    },
+                })
+            })
+            .collect();
+
         InMemoryCache {
-            data: Arc::new(RwLock::new(TtlCache::new(CACHE_CAPACITY))),
+            shards,
+            max_entries: shard_entries,
+            max_size: None,
+            weigher: |_| 0,
+            tags: RwLock::new(HashMap::new()),
             hits: AtomicUsize::new(0),
             miss: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+        }
+    }
+
+    /// Bounds the size of the entries as well, in bytes, as estimated by the
+    /// weigher. The size is split evenly between the shards, so values larger
+    /// than the share of a shard aren't cached.
+    pub fn with_max_size(self, max_size: usize, weigher: fn(&V) -> usize) -> Self {
+        let max_size = max_size.div_ceil(self.shards.len());
+        Self { max_size: Some(max_size), weigher, ..self }
+    }
+
+    fn shard(&self, key: &K) -> &Mutex<Shard<K, V>> {
+        &self.shards[hash(key) as usize % self.shards.len()]
+    }
+
+    /// Removes the keys of the removed entries from the index of their tags.
+    /// Called while the shard of the entries is locked, so that the key isn't
+    /// tagged again in the meantime.
+    fn untag<'a>(&self, removed: impl IntoIterator<Item = (&'a K, &'a Entry<V>)>)
+    where
+        K: 'a,
+        V: 'a,
+    {
+        let mut removed = removed
+            .into_iter()
+            .filter(|(_, entry)| !entry.tags.is_empty())
+            .peekable();
+        if removed.peek().is_none() {
+            return;
+        }
+
+        let mut tagged = self.tags.write().unwrap();
+        for (key, entry) in removed {
+            for tag in entry.tags.iter() {
+                if let Some(keys) = tagged.get_mut(tag) {
+                    keys.remove(key);
+                    if keys.is_empty() {
+                        tagged.remove(tag);
+                    }
+                }
+            }
         }
     }
 }
 
 #[async_trait::async_trait]
-impl<K: Hash + Eq + Send + Sync, V: Clone + Send + Sync> crate::core::Cache
+impl<K: Hash + Eq + Clone + Send + Sync, V: Clone + Send + Sync> crate::core::Cache
     for InMemoryCache<K, V>
 {
     type Key = K;
     type Value = V;
     #[allow(clippy::too_many_arguments)]
     async fn set<'a>(&'a self, key: K, value: V, ttl: NonZeroU64) -> Result<()> {
-        let ttl = Duration::from_millis(ttl.get());
-        self.data.write().unwrap().insert(key, value, ttl);
+        let size = self.max_size.map_or(0, |_| (self.weigher)(&value));
+        let expires_at = Instant::now() + Duration::from_millis(ttl.get());
+        let entry = Entry { value, expires_at, size, tags: vec![] };
+
+        let mut shard = self.shard(&key).lock().unwrap();
+        if self.max_size.is_some_and(|max_size| size > max_size) {
+            // too large for a shard, the previous value is dropped rather than kept stale
+            if let Some(previous) = shard.remove(&key) {
+                self.untag([(&key, &previous)]);
+            }
+            return Ok(());
+        }
+        let evicted = shard.insert(key, entry, self.max_entries, self.max_size);
+        self.untag(evicted.iter().map(|(key, entry)| (key, entry)));
+        self.evictions.fetch_add(evicted.len(), Ordering::Relaxed);
         Ok(())
     }
 
     async fn get<'a>(&'a self, key: &'a K) -> Result<Option<Self::Value>> {
-        let val = self.data.read().unwrap().get(key).cloned();
+        let mut shard = self.shard(key).lock().unwrap();
+        if let Some(sketch) = shard.sketch.as_mut() {
+            sketch.increment(hash(key));
+        }
+        let val = match shard.entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            _ => None,
+        };
         if val.is_some() {
             self.hits.fetch_add(1, Ordering::Relaxed);
         } else {
+            // drops the entry in case it expired
+            if let Some(entry) = shard.remove(key) {
+                self.untag([(key, &entry)]);
+            }
             self.miss.fetch_add(1, Ordering::Relaxed);
         }
         Ok(val)
     }
 
+    async fn tag<'a>(&'a self, key: &'a K, tags: &'a [String]) -> Result<()> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+
+        let mut shard = self.shard(key).lock().unwrap();
+        // keys are only indexed while they have an entry, which bounds the index
+        let Some(entry) = shard.entries.peek_mut(key) else {
+            return Ok(());
+        };
+
+        let mut tagged = self.tags.write().unwrap();
+        for tag in tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+            tagged.entry(tag.clone()).or_default().insert(key.clone());
+        }
+        Ok(())
+    }
+
+    async fn purge<'a>(&'a self, tag: &'a str) -> Result<()> {
+        let keys = self.tags.write().unwrap().remove(tag);
+        for key in keys.iter().flatten() {
+            let mut shard = self.shard(key).lock().unwrap();
+            if let Some(entry) = shard.remove(key) {
+                self.untag([(key, &entry)]);
+            }
+        }
+        Ok(())
+    }
+
     fn hit_rate(&self) -> Option<f64> {
-        let cache = self.data.read().unwrap();
         let hits = self.hits.load(Ordering::Relaxed);
         let misses = self.miss.load(Ordering::Relaxed);
 
-        drop(cache);
-
         if hits + misses > 0 {
             return Some(hits as f64 / (hits + misses) as f64);
         }
 
         None
     }
+
+    fn stats(&self) -> Option<Stats> {
+        let mut stats = Stats {
+            hits: self.hits.load(Ordering::Relaxed) as u64,
+            misses: self.miss.load(Ordering::Relaxed) as u64,
+            evictions: self.evictions.load(Ordering::Relaxed) as u64,
+            ..Default::default()
+        };
+        for shard in self.shards.iter() {
+            let shard = shard.lock().unwrap();
+            stats.entries += shard.entries.len() as u64;
+            stats.size += shard.size as u64;
+        }
+
+        Some(stats)
+    }
+}
+
+fn hash<K: Hash>(key: &K) -> u64 {
+    let mut hasher = TailcallHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Estimated bytes held by a value, to bound the size of a cache of values.
+pub fn estimate_size(value: &ConstValue) -> usize {
+    let size = std::mem::size_of::<ConstValue>();
+    match value {
+        ConstValue::String(string) => size + string.len(),
+        ConstValue::Enum(name) => size + name.len(),
+        ConstValue::Binary(bytes) => size + bytes.len(),
+        ConstValue::List(list) => size + list.iter().map(estimate_size).sum::<usize>(),
+        ConstValue::Object(map) => {
+            size + map
+                .iter()
+                .map(|(name, value)| name.len() + estimate_size(value))
+                .sum::<usize>()
+        }
+        _ => size,
+    }
 }
 
 #[cfg(test)]
@@ -76,6 +356,7 @@ mod tests {
     use std::num::NonZeroU64;
     use std::time::Duration;
 
+    use super::{estimate_size, Eviction, InMemoryCache};
     use crate::core::Cache;
 
     #[tokio::test]
@@ -92,4 +373,141 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(ttl.get())).await;
         assert_eq!(cache.get(&10).await.ok(), Some(None));
     }
+
+    #[tokio::test]
+    async fn test_purge_tagged_keys() {
+        let cache: crate::core::cache::InMemoryCache<u64, String> =
+            crate::core::cache::InMemoryCache::default();
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        cache.set(1, "a".into(), ttl).await.unwrap();
+        cache.set(2, "b".into(), ttl).await.unwrap();
+        cache.set(3, "c".into(), ttl).await.unwrap();
+        cache
+            .tag(&1, &["user:1".into(), "users".into()])
+            .await
+            .unwrap();
+        cache.tag(&2, &["users".into()]).await.unwrap();
+
+        cache.purge("user:1").await.unwrap();
+        assert_eq!(cache.get(&1).await.ok(), Some(None));
+        assert_eq!(cache.get(&2).await.ok(), Some(Some("b".into())));
+
+        cache.purge("users").await.unwrap();
+        assert_eq!(cache.get(&2).await.ok(), Some(None));
+        assert_eq!(cache.get(&3).await.ok(), Some(Some("c".into())));
+        assert!(cache.tags.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_untag_removed_keys() {
+        let cache = InMemoryCache::<u64, String>::with_capacity(1, Eviction::Lru);
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        cache.set(1, "a".into(), ttl).await.unwrap();
+        cache.tag(&1, &["user:1".into()]).await.unwrap();
+        cache.set(2, "b".into(), ttl).await.unwrap();
+        cache.tag(&2, &["user:2".into()]).await.unwrap();
+
+        // the key of the evicted entry is forgotten
+        let tags = cache
+            .tags
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(tags, vec!["user:2".to_string()]);
+
+        cache
+            .set(3, "c".into(), NonZeroU64::new(1).unwrap())
+            .await
+            .ok();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        cache.tag(&3, &["user:3".into()]).await.unwrap();
+        assert_eq!(cache.get(&3).await.ok(), Some(None));
+
+        // as well as the key of the expired one
+        assert!(cache.tags.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let cache = InMemoryCache::<u64, String>::with_capacity(2, Eviction::Lru);
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        cache.set(1, "a".into(), ttl).await.unwrap();
+        cache.set(2, "b".into(), ttl).await.unwrap();
+        cache.get(&1).await.unwrap();
+        cache.set(3, "c".into(), ttl).await.unwrap();
+
+        assert_eq!(cache.get(&1).await.ok(), Some(Some("a".into())));
+        assert_eq!(cache.get(&2).await.ok(), Some(None));
+        assert_eq!(cache.get(&3).await.ok(), Some(Some("c".into())));
+        assert_eq!(cache.stats().unwrap().evictions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tiny_lfu_admission() {
+        let cache = InMemoryCache::<u64, String>::with_capacity(1, Eviction::TinyLfu);
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        cache.set(1, "a".into(), ttl).await.unwrap();
+        for _ in 0..3 {
+            cache.get(&1).await.unwrap();
+        }
+
+        // a key accessed less often than the cached one isn't admitted
+        cache.set(2, "b".into(), ttl).await.unwrap();
+        assert_eq!(cache.get(&1).await.ok(), Some(Some("a".into())));
+
+        for _ in 0..5 {
+            cache.get(&2).await.unwrap();
+        }
+        cache.set(2, "b".into(), ttl).await.unwrap();
+        assert_eq!(cache.get(&1).await.ok(), Some(None));
+        assert_eq!(cache.get(&2).await.ok(), Some(Some("b".into())));
+
+        // a cached key is updated regardless of how often it was accessed
+        cache.set(2, "c".into(), ttl).await.unwrap();
+        assert_eq!(cache.get(&2).await.ok(), Some(Some("c".into())));
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_tags() {
+        let cache = InMemoryCache::<u64, String>::with_capacity(10, Eviction::TinyLfu);
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        cache.set(1, "a".into(), ttl).await.unwrap();
+        cache.tag(&1, &["user:1".into()]).await.unwrap();
+        cache.set(1, "b".into(), ttl).await.unwrap();
+        assert_eq!(cache.stats().unwrap().entries, 1);
+
+        cache.purge("user:1").await.unwrap();
+        assert_eq!(cache.get(&1).await.ok(), Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_max_size() {
+        let value = async_graphql_value::ConstValue::String("a".repeat(100));
+        let size = estimate_size(&value);
+        let cache =
+            InMemoryCache::with_capacity(10, Eviction::Lru).with_max_size(size * 2, estimate_size);
+        let ttl = NonZeroU64::new(10000).unwrap();
+
+        for key in 0..3u64 {
+            cache.set(key, value.clone(), ttl).await.unwrap();
+        }
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.size, size as u64 * 2);
+        assert_eq!(cache.get(&0).await.ok(), Some(None));
+
+        // a value too large to be cached drops the previous one
+        let large = async_graphql_value::ConstValue::String("a".repeat(size * 2));
+        cache.set(2, large, ttl).await.unwrap();
+        assert_eq!(cache.get(&2).await.ok(), Some(None));
+        assert_eq!(cache.stats().unwrap().entries, 1);
+    }
 }
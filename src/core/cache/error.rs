@@ -11,6 +11,14 @@ pub enum Error {
     #[debug(fmt = "Kv Error: {}", _0)]
     #[from(ignore)]
     Kv(String),
+
+    #[debug(fmt = "Redis Error: {}", _0)]
+    #[from(ignore)]
+    Redis(String),
+
+    #[debug(fmt = "Encoding Error: {}", _0)]
+    #[from(ignore)]
+    Encoding(String),
 }
 
 impl From<serde_json::Error> for Error {
@@ -26,6 +34,8 @@ impl Display for Error {
         match self {
             Error::SerdeJson(error) => write!(f, "Serde Json Error: {}", error),
             Error::Kv(error) => write!(f, "Kv Error: {}", error),
+            Error::Redis(error) => write!(f, "Redis Error: {}", error),
+            Error::Encoding(error) => write!(f, "Encoding Error: {}", error),
         }
     }
 }
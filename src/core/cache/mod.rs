@@ -1,4 +1,5 @@
 pub mod cache;
 pub mod error;
+mod sketch;
 pub use cache::*;
 pub use error::Error;
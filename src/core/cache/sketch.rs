@@ -0,0 +1,96 @@
+/// Approximate count of how often keys are accessed, a count-min sketch of
+/// 4-bit counters that are halved periodically so that old accesses weigh less
+/// than recent ones.
+pub struct FrequencySketch {
+    counters: Vec<u8>,
+    mask: u64,
+    additions: usize,
+    sample_size: usize,
+}
+
+const DEPTH: u64 = 4;
+const MAX_COUNT: u8 = 15;
+const SEEDS: [u64; DEPTH as usize] = [
+    0x9e37_79b9_7f4a_7c15,
+    0xc2b2_ae3d_27d4_eb4f,
+    0x1656_67b1_9e37_79f9,
+    0x85eb_ca77_c2b2_ae63,
+];
+
+impl FrequencySketch {
+    /// A sketch sized for a cache holding `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        let width = capacity.max(16).next_power_of_two();
+        Self {
+            counters: vec![0; width],
+            mask: width as u64 - 1,
+            additions: 0,
+            sample_size: width * 10,
+        }
+    }
+
+    pub fn increment(&mut self, hash: u64) {
+        for depth in 0..DEPTH {
+            let index = self.index(hash, depth);
+            let counter = &mut self.counters[index];
+            *counter = counter.saturating_add(1).min(MAX_COUNT);
+        }
+
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            self.reset();
+        }
+    }
+
+    pub fn frequency(&self, hash: u64) -> u8 {
+        (0..DEPTH)
+            .map(|depth| self.counters[self.index(hash, depth)])
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn index(&self, hash: u64, depth: u64) -> usize {
+        let hash = (hash ^ SEEDS[depth as usize]).wrapping_mul(SEEDS[depth as usize]);
+        ((hash ^ (hash >> 32)) & self.mask) as usize
+    }
+
+    fn reset(&mut self) {
+        for counter in self.counters.iter_mut() {
+            *counter /= 2;
+        }
+        self.additions /= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrequencySketch;
+
+    #[test]
+    fn test_frequency() {
+        let mut sketch = FrequencySketch::new(64);
+        for _ in 0..5 {
+            sketch.increment(1);
+        }
+        sketch.increment(2);
+
+        assert_eq!(sketch.frequency(1), 5);
+        assert_eq!(sketch.frequency(2), 1);
+        assert_eq!(sketch.frequency(3), 0);
+    }
+
+    #[test]
+    fn test_aging() {
+        let mut sketch = FrequencySketch::new(16);
+        for _ in 0..10 {
+            sketch.increment(1);
+        }
+        // the counters are halved once 10 times the width are counted
+        for _ in 0..150 {
+            sketch.increment(2);
+        }
+
+        assert_eq!(sketch.frequency(1), 5);
+        assert_eq!(sketch.frequency(2), 7);
+    }
+}
@@ -16,7 +16,7 @@ use tailcall_typedefs_common::ServiceDocumentBuilder;
 use super::directives::{Call, Expr, GraphQL, Grpc, Http, Key, JS};
 use super::from_document::from_document;
 use super::telemetry::Telemetry;
-use super::{Link, Resolver, Server, Upstream};
+use super::{KeyValue, Link, Resolver, Server, Upstream};
 use crate::core::config::npo::QueryPath;
 use crate::core::config::source::Source;
 use crate::core::is_default;
@@ -52,6 +52,12 @@ pub struct Config {
     #[serde(default)]
     pub upstream: Upstream,
 
+    ///
+    /// Upstreams with a `name`, selected by the resolvers with their
+    /// `upstream` argument.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub upstreams: Vec<Upstream>,
+
     ///
     /// Specifies the entry points for query and mutation in the generated
     /// GraphQL schema.
@@ -114,6 +120,16 @@ pub struct Type {
     #[serde(default)]
     pub protected: Option<Protected>,
 
+    ///
+    /// Limits how often a client can request the fields of the type
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rate_limit: Option<RateLimit>,
+
+    ///
+    /// Cost of the fields of the type, counted against `maxCost`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cost: Option<Cost>,
+
     ///
     /// Apollo federation entity resolver.
     #[serde(flatten, default, skip_serializing_if = "is_default")]
@@ -173,6 +189,46 @@ pub struct Cache {
     /// Specifies the duration, in milliseconds, of how long the value has to be
     /// stored in the cache.
     pub max_age: NonZeroU64,
+
+    /// Duration, in milliseconds, after `maxAge` during which the expired
+    /// value is served while it's refreshed in the background.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub stale_while_revalidate: Option<NonZeroU64>,
+
+    /// Duration, in milliseconds, after `maxAge` during which the expired
+    /// value is served when refreshing it fails.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub stale_if_error: Option<NonZeroU64>,
+
+    /// Tags of the cached values, purged with `@invalidate`. Tags are
+    /// mustache templates, e.g. `user:{{.args.id}}`. Values are also tagged
+    /// with the name of the field, e.g. `Query.users`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub tags: Vec<String>,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "FieldDefinition")]
+/// The @invalidate directive purges the cached values with the given tags
+/// once the mutation it is applied to is resolved.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct Invalidate {
+    /// Tags of the cached values to purge. Tags are mustache templates
+    /// rendered with the arguments and the result of the mutation, e.g.
+    /// `user:{{.value.id}}`.
+    pub tags: Vec<String>,
 }
 
 #[derive(
@@ -188,7 +244,104 @@ pub struct Cache {
     DirectiveDefinition,
 )]
 #[directive_definition(locations = "Object,FieldDefinition")]
-pub struct Protected {}
+/// The @protected directive restricts access to a field or a type to requests
+/// verified by one of the auth providers. Optionally, it narrows the providers
+/// that are accepted and the permissions the verified identity must have.
+pub struct Protected {
+    /// Ids of the auth providers, as set in their `@link`, that are allowed to
+    /// verify the request. When empty, any provider is accepted.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub providers: Vec<String>,
+
+    /// Scopes that must all be granted to the identity. They are read from the
+    /// space-separated `scope` claim or the `scp` claim of the token.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub scopes: Vec<String>,
+
+    /// Roles of which the identity must have at least one. They are read from
+    /// the `roles` claim of the token.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub roles: Vec<String>,
+
+    /// Claims that the identity must have, each one with the given value.
+    /// Nested claims are referenced with a dot separated path, e.g.
+    /// `org.id`. When the claim is a list, it must contain the value.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub claims: Vec<KeyValue>,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "Object,FieldDefinition")]
+/// The @rateLimit directive limits how often a client can request the field or
+/// the fields of the type it is applied to. Each client gets a token bucket
+/// that every request selecting those fields takes a token from. Once the
+/// bucket is empty, requests fail with a `RATE_LIMITED` error until it's
+/// refilled. Set in `@server`, the limit applies to every request.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// Maximum number of tokens the bucket holds, i.e. the number of requests
+    /// a client can make in a burst.
+    pub capacity: NonZeroU64,
+
+    /// Mustache template identifying the client the bucket belongs to, e.g.
+    /// `{{.headers.x-client-id}}` or `{{.auth.claims.sub}}`. Requests for
+    /// which it renders empty share a single bucket. @default
+    /// `{{.client.ip}}`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub key: Option<String>,
+
+    /// Duration, in milliseconds, over which `refillRate` tokens are added
+    /// back to the bucket. @default `1000`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub refill_interval: Option<NonZeroU64>,
+
+    /// Number of tokens added back to the bucket every `refillInterval`.
+    /// @default the `capacity`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub refill_rate: Option<NonZeroU64>,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Deserialize,
+    Serialize,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+    DirectiveDefinition,
+)]
+#[directive_definition(locations = "Object,FieldDefinition")]
+/// The @cost directive sets how much the field, or each field of the type it
+/// is applied to, adds to the cost of the operations selecting it. Operations
+/// that cost more than `maxCost` of `@server` are rejected before they are
+/// executed.
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct Cost {
+    /// Cost of resolving the field once. @default `1` for fields with a
+    /// resolver and `0` for the others.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub weight: Option<u64>,
+
+    /// Integer arguments of the field, e.g. `first` or `limit`, whose values
+    /// multiply the cost of the fields selected under it.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub multipliers: Vec<String>,
+}
 
 #[derive(
     Serialize,
@@ -275,6 +428,21 @@ pub struct Field {
     #[serde(default)]
     pub protected: Option<Protected>,
 
+    ///
+    /// Limits how often a client can request the field
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub rate_limit: Option<RateLimit>,
+
+    ///
+    /// Cost of the field, counted against `maxCost`
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub cost: Option<Cost>,
+
+    ///
+    /// Cached values purged once the mutation is resolved
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub invalidate: Option<Invalidate>,
+
     ///
     /// Resolver for the field
     #[serde(flatten, default, skip_serializing_if = "is_default")]
@@ -481,6 +649,15 @@ impl Config {
         self.enums.get(name)
     }
 
+    /// Returns the upstream with the given name, completed with the settings
+    /// of the default upstream.
+    pub fn find_upstream(&self, name: &str) -> Option<Upstream> {
+        self.upstreams
+            .iter()
+            .find(|upstream| upstream.name.as_deref() == Some(name))
+            .map(|upstream| self.upstream.clone().merge_right(upstream.clone()))
+    }
+
     pub fn to_yaml(&self) -> Result<String> {
         Ok(serde_yaml::to_string(self)?)
     }
@@ -703,15 +880,18 @@ impl Config {
             .add_directive(Alias::directive_definition(generated_types))
             .add_directive(Cache::directive_definition(generated_types))
             .add_directive(Call::directive_definition(generated_types))
+            .add_directive(Cost::directive_definition(generated_types))
             .add_directive(Expr::directive_definition(generated_types))
             .add_directive(GraphQL::directive_definition(generated_types))
             .add_directive(Grpc::directive_definition(generated_types))
             .add_directive(Http::directive_definition(generated_types))
+            .add_directive(Invalidate::directive_definition(generated_types))
             .add_directive(JS::directive_definition(generated_types))
             .add_directive(Link::directive_definition(generated_types))
             .add_directive(Modify::directive_definition(generated_types))
             .add_directive(Omit::directive_definition(generated_types))
             .add_directive(Protected::directive_definition(generated_types))
+            .add_directive(RateLimit::directive_definition(generated_types))
             .add_directive(Server::directive_definition(generated_types))
             .add_directive(Telemetry::directive_definition(generated_types))
             .add_directive(Upstream::directive_definition(generated_types))
@@ -878,4 +1058,27 @@ mod tests {
             .collect();
         assert_eq!(union_types, expected_union_types);
     }
+
+    #[test]
+    fn test_find_upstream() {
+        let sdl = r#"
+            schema
+                @upstream(baseURL: "http://localhost:8000", timeout: 10)
+                @upstream(name: "payments", baseURL: "http://localhost:8001") {
+                query: Query
+            }
+
+            type Query {
+                hello: String
+            }
+        "#;
+        let config = Config::from_sdl(sdl).to_result().unwrap();
+        assert_eq!(config.upstream.name, None);
+        assert_eq!(config.upstreams.len(), 1);
+
+        let payments = config.find_upstream("payments").unwrap();
+        assert_eq!(payments.base_url.as_deref(), Some("http://localhost:8001"));
+        assert_eq!(payments.timeout, Some(10));
+        assert_eq!(config.find_upstream("orders"), None);
+    }
 }
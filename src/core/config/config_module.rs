@@ -4,6 +4,7 @@ use std::ops::Deref;
 use jsonwebtoken::jwk::JwkSet;
 use prost_reflect::prost_types::{FileDescriptorProto, FileDescriptorSet};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use serde::Deserialize;
 
 use crate::core::config::Config;
 use crate::core::macros::MergeRight;
@@ -130,6 +131,89 @@ impl PrivateKey {
     }
 }
 
+/// Key set linked with `@link(type: Jwks)`.
+#[derive(Clone, Debug)]
+pub struct Jwks {
+    pub set: JwkSet,
+    /// Set when the key set was fetched from a URL, so that it can be
+    /// refreshed while the server is running.
+    pub refresh: Option<JwksRefresh>,
+}
+
+/// Refresh settings of a remote key set, read from the `meta` of the link.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwksRefresh {
+    #[serde(skip)]
+    pub url: String,
+    /// Seconds after which the key set is fetched again.
+    pub ttl: Option<u64>,
+    /// Minimum number of seconds between two fetches, which also limits the
+    /// fetches caused by tokens signed with an unknown `kid`.
+    pub min_refetch_interval: Option<u64>,
+}
+
+/// OAuth2 token introspection endpoint linked with `@link(type:
+/// Introspection)`. The client credentials are read from the `meta` of the
+/// link.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Introspection {
+    #[serde(skip)]
+    pub url: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// API keys linked with `@link(type: ApiKeys)`.
+#[derive(Clone, Debug)]
+pub struct ApiKeys {
+    pub keys: Vec<ApiKey>,
+    pub source: ApiKeySource,
+}
+
+/// Entry of an API keys file. Only the SHA-256 hash of the key is stored,
+/// along with the metadata of the key.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ApiKey {
+    /// Hex encoded SHA-256 hash of the key
+    pub hash: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Any other metadata of the key
+    #[serde(flatten)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Part of the request the API key is read from, set with the `meta` of the
+/// link, e.g. `{in: "query", name: "api_key"}`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "in", rename_all = "lowercase")]
+pub enum ApiKeySource {
+    Header { name: Option<String> },
+    Query { name: Option<String> },
+    Cookie { name: Option<String> },
+}
+
+impl Default for ApiKeySource {
+    fn default() -> Self {
+        ApiKeySource::Header { name: None }
+    }
+}
+
+impl ApiKeySource {
+    /// Name of the header, query param or cookie holding the key.
+    pub fn name(&self) -> &str {
+        match self {
+            ApiKeySource::Header { name } => name.as_deref().unwrap_or("X-API-Key"),
+            ApiKeySource::Query { name } | ApiKeySource::Cookie { name } => {
+                name.as_deref().unwrap_or("api_key")
+            }
+        }
+    }
+}
+
 /// Extensions are meta-information required before we can generate the
 /// blueprint. Typically, this information cannot be inferred without performing
 /// an IO operation, i.e., reading a file, making an HTTP call, etc.
@@ -147,12 +231,23 @@ pub struct Extensions {
     /// Contains the key used on HTTP2 with TLS
     pub keys: Vec<PrivateKey>,
 
+    /// Contains the PEM documents of the `Cert` and `Key` links used by the
+    /// `tls` of the upstreams
+    pub upstream_tls: Vec<Content<String>>,
+
     /// Contains the endpoints
     pub endpoint_set: EndpointSet<Unchecked>,
 
+    /// Contains the documents of the linked operation files
+    pub operations: Vec<String>,
+
     pub htpasswd: Vec<Content<String>>,
 
-    pub jwks: Vec<Content<JwkSet>>,
+    pub jwks: Vec<Content<Jwks>>,
+
+    pub introspection: Vec<Content<Introspection>>,
+
+    pub api_keys: Vec<Content<ApiKeys>>,
 }
 
 impl Extensions {
@@ -168,7 +263,10 @@ impl Extensions {
     }
 
     pub fn has_auth(&self) -> bool {
-        !self.htpasswd.is_empty() || !self.jwks.is_empty()
+        !self.htpasswd.is_empty()
+            || !self.jwks.is_empty()
+            || !self.introspection.is_empty()
+            || !self.api_keys.is_empty()
     }
 }
 
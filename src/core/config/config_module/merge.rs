@@ -100,6 +100,9 @@ impl Contravariant for Field {
                 cache: self.cache.merge_right(other.cache),
                 default_value: self.default_value.or(other.default_value),
                 protected: self.protected.merge_right(other.protected),
+                rate_limit: self.rate_limit.merge_right(other.rate_limit),
+                cost: self.cost.merge_right(other.cost),
+                invalidate: self.invalidate.merge_right(other.invalidate),
                 resolver: self.resolver.merge_right(other.resolver),
             })
     }
@@ -120,6 +123,9 @@ impl Covariant for Field {
                 cache: self.cache.merge_right(other.cache),
                 default_value: self.default_value.or(other.default_value),
                 protected: self.protected.merge_right(other.protected),
+                rate_limit: self.rate_limit.merge_right(other.rate_limit),
+                cost: self.cost.merge_right(other.cost),
+                invalidate: self.invalidate.merge_right(other.invalidate),
                 resolver: self.resolver.merge_right(other.resolver),
             })
     }
@@ -135,6 +141,8 @@ impl Contravariant for Type {
             implements: self.implements.merge_right(other.implements),
             cache: self.cache.merge_right(other.cache),
             protected: self.protected.merge_right(other.protected),
+            rate_limit: self.rate_limit.merge_right(other.rate_limit),
+            cost: self.cost.merge_right(other.cost),
             resolver: self.resolver.merge_right(other.resolver),
             key: self.key.merge_right(other.key),
         })
@@ -151,6 +159,8 @@ impl Covariant for Type {
             implements: self.implements.merge_right(other.implements),
             cache: self.cache.merge_right(other.cache),
             protected: self.protected.merge_right(other.protected),
+            rate_limit: self.rate_limit.merge_right(other.rate_limit),
+            cost: self.cost.merge_right(other.cost),
             resolver: self.resolver.merge_right(other.resolver),
             key: self.key.merge_right(other.key),
         })
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
-use crate::core::config::KeyValue;
+use crate::core::config::{CircuitBreaker, KeyValue, Retry};
 use crate::core::is_default;
 
 #[derive(
@@ -58,4 +58,24 @@ pub struct GraphQL {
     /// with APIs that expect unique results for identical inputs, such as
     /// nonce-based APIs.
     pub dedupe: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Retries of the GraphQL request, overriding the `retry` of `@upstream`.
+    pub retry: Option<Retry>,
+
+    #[serde(rename = "circuitBreaker", default, skip_serializing_if = "is_default")]
+    /// Thresholds of the circuit breaker of the host for the GraphQL request,
+    /// overriding the `circuitBreaker` of `@upstream`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum time in milliseconds to resolve the field with the GraphQL
+    /// request, retries included. Once it's exceeded, the field resolves to
+    /// `null` with an error, while the rest of the response is served.
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Name of the `@upstream` the GraphQL requests go through, instead of the
+    /// default one. Its settings, like `baseURL`, apply to the field.
+    pub upstream: Option<String>,
 }
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
-use crate::core::config::KeyValue;
+use crate::core::config::{CircuitBreaker, KeyValue, Retry};
 use crate::core::is_default;
 
 #[derive(
@@ -59,4 +59,24 @@ pub struct Grpc {
     /// with APIs that expect unique results for identical inputs, such as
     /// nonce-based APIs.
     pub dedupe: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Retries of the gRPC call, overriding the `retry` of `@upstream`.
+    pub retry: Option<Retry>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Thresholds of the circuit breaker of the host for the gRPC call,
+    /// overriding the `circuitBreaker` of `@upstream`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum time in milliseconds to resolve the field with the gRPC call,
+    /// retries included. Once it's exceeded, the field resolves to `null`
+    /// with an error, while the rest of the response is served.
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Name of the `@upstream` the gRPC calls go through, instead of the
+    /// default one. Its settings, like `baseURL`, apply to the field.
+    pub upstream: Option<String>,
 }
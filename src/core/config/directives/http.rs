@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use tailcall_macros::{DirectiveDefinition, InputDefinition};
 
-use crate::core::config::{Encoding, KeyValue, URLQuery};
+use crate::core::config::{CircuitBreaker, Encoding, KeyValue, Retry, URLQuery};
 use crate::core::http::Method;
 use crate::core::is_default;
 use crate::core::json::JsonSchema;
@@ -82,6 +82,14 @@ pub struct Http {
     /// most cases.
     pub output: Option<JsonSchema>,
 
+    #[serde(rename = "pollInterval", default, skip_serializing_if = "is_default")]
+    /// Only applicable to fields of the subscription type. The interval in
+    /// milliseconds at which the API is called again, every response being
+    /// pushed to the subscriber as a new event. When not specified, the API is
+    /// expected to respond with a `text/event-stream` and every event it emits
+    /// is pushed to the subscriber.
+    pub poll_interval: Option<u64>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// This represents the query parameters of your API call. You can pass it
     /// as a static object or use Mustache template for dynamic parameters.
@@ -98,4 +106,24 @@ pub struct Http {
     /// with APIs that expect unique results for identical inputs, such as
     /// nonce-based APIs.
     pub dedupe: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Retries of the API call, overriding the `retry` of `@upstream`.
+    pub retry: Option<Retry>,
+
+    #[serde(rename = "circuitBreaker", default, skip_serializing_if = "is_default")]
+    /// Thresholds of the circuit breaker of the host for the API call,
+    /// overriding the `circuitBreaker` of `@upstream`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum time in milliseconds to resolve the field with the API call,
+    /// retries included. Once it's exceeded, the field resolves to `null`
+    /// with an error, while the rest of the response is served.
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Name of the `@upstream` the API calls go through, instead of the
+    /// default one. Its settings, like `baseURL`, apply to the field.
+    pub upstream: Option<String>,
 }
@@ -13,8 +13,8 @@ use indexmap::IndexMap;
 use super::telemetry::Telemetry;
 use super::{Alias, Resolver};
 use crate::core::config::{
-    self, Cache, Config, Enum, Link, Modify, Omit, Protected, RootSchema, Server, Union, Upstream,
-    Variant,
+    self, Cache, Config, Cost, Enum, Invalidate, Link, Modify, Omit, Protected, RateLimit,
+    RootSchema, Server, Union, Upstream, Variant,
 };
 use crate::core::directive::DirectiveCodec;
 use crate::core::valid::{Valid, ValidationError, Validator};
@@ -43,7 +43,7 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
     let schema = schema_definition(&doc).map(to_root_schema);
     schema_definition(&doc).and_then(|sd| {
         server(sd)
-            .fuse(upstream(sd))
+            .fuse(upstreams(sd))
             .fuse(types)
             .fuse(unions)
             .fuse(enums)
@@ -51,9 +51,19 @@ pub fn from_document(doc: ServiceDocument) -> Valid<Config, String> {
             .fuse(links(sd))
             .fuse(telemetry(sd))
             .map(
-                |(server, upstream, types, unions, enums, schema, links, telemetry)| Config {
+                |(
+                    server,
+                    (upstream, upstreams),
+                    types,
+                    unions,
+                    enums,
+                    schema,
+                    links,
+                    telemetry,
+                )| Config {
                     server,
                     upstream,
+                    upstreams,
                     types,
                     unions,
                     enums,
@@ -111,11 +121,19 @@ fn server(schema_definition: &SchemaDefinition) -> Valid<Server, String> {
     process_schema_directives(schema_definition, config::Server::directive_name().as_str())
 }
 
-fn upstream(schema_definition: &SchemaDefinition) -> Valid<Upstream, String> {
-    process_schema_directives(
+/// Splits the `@upstream` directives into the default one, the last without a
+/// name, and the named ones.
+fn upstreams(schema_definition: &SchemaDefinition) -> Valid<(Upstream, Vec<Upstream>), String> {
+    process_schema_multiple_directives::<Upstream>(
         schema_definition,
         config::Upstream::directive_name().as_str(),
     )
+    .map(|upstreams| {
+        let (named, unnamed): (Vec<_>, Vec<_>) = upstreams
+            .into_iter()
+            .partition(|upstream| upstream.name.is_some());
+        (unnamed.into_iter().last().unwrap_or_default(), named)
+    })
 }
 
 fn links(schema_definition: &SchemaDefinition) -> Valid<Vec<Link>, String> {
@@ -246,21 +264,27 @@ where
         .fuse(Cache::from_directives(directives.iter()))
         .fuse(to_fields(fields))
         .fuse(Protected::from_directives(directives.iter()))
+        .fuse(RateLimit::from_directives(directives.iter()))
+        .fuse(Cost::from_directives(directives.iter()))
         .fuse(to_add_fields_from_directives(directives))
-        .map(|(resolver, cache, fields, protected, added_fields)| {
-            let doc = description.to_owned().map(|pos| pos.node);
-            let implements = implements.iter().map(|pos| pos.node.to_string()).collect();
-            config::Type {
-                fields,
-                added_fields,
-                doc,
-                implements,
-                cache,
-                protected,
-                resolver,
-                key: None,
-            }
-        })
+        .map(
+            |(resolver, cache, fields, protected, rate_limit, cost, added_fields)| {
+                let doc = description.to_owned().map(|pos| pos.node);
+                let implements = implements.iter().map(|pos| pos.node.to_string()).collect();
+                config::Type {
+                    fields,
+                    added_fields,
+                    doc,
+                    implements,
+                    cache,
+                    protected,
+                    rate_limit,
+                    cost,
+                    resolver,
+                    key: None,
+                }
+            },
+        )
 }
 fn to_input_object(
     input_object_type: InputObjectType,
@@ -335,18 +359,36 @@ where
         .fuse(Omit::from_directives(directives.iter()))
         .fuse(Modify::from_directives(directives.iter()))
         .fuse(Protected::from_directives(directives.iter()))
+        .fuse(RateLimit::from_directives(directives.iter()))
+        .fuse(Cost::from_directives(directives.iter()))
+        .fuse(Invalidate::from_directives(directives.iter()))
         .fuse(default_value)
         .map(
-            |(resolver, cache, omit, modify, protected, default_value)| config::Field {
-                type_of: type_of.into(),
-                args,
-                doc,
-                modify,
-                omit,
+            |(
+                resolver,
                 cache,
+                omit,
+                modify,
                 protected,
+                rate_limit,
+                cost,
+                invalidate,
                 default_value,
-                resolver,
+            )| {
+                config::Field {
+                    type_of: type_of.into(),
+                    args,
+                    doc,
+                    modify,
+                    omit,
+                    cache,
+                    protected,
+                    rate_limit,
+                    cost,
+                    invalidate,
+                    default_value,
+                    resolver,
+                }
             },
         )
         .trace(pos_name_to_string(field.name()).as_str())
@@ -19,6 +19,12 @@ fn config_document(config: &Config) -> ServiceDocument {
         pos(config.server.to_directive()),
         pos(config.upstream.to_directive()),
     ];
+    directives.extend(
+        config
+            .upstreams
+            .iter()
+            .map(|upstream| pos(upstream.to_directive())),
+    );
 
     directives.extend(config.links.iter().map(|link| {
         let mut directive = link.to_directive();
@@ -167,6 +173,13 @@ fn config_document(config: &Config) -> ServiceDocument {
                     .as_ref()
                     .map(|protected| pos(protected.to_directive())),
             )
+            .chain(
+                type_def
+                    .rate_limit
+                    .as_ref()
+                    .map(|rate_limit| pos(rate_limit.to_directive())),
+            )
+            .chain(type_def.cost.as_ref().map(|cost| pos(cost.to_directive())))
             .chain(
                 type_def
                     .resolver
@@ -240,6 +253,9 @@ fn get_directives(field: &crate::core::config::Field) -> Vec<Positioned<ConstDir
         field.omit.as_ref().map(|d| pos(d.to_directive())),
         field.cache.as_ref().map(|d| pos(d.to_directive())),
         field.protected.as_ref().map(|d| pos(d.to_directive())),
+        field.rate_limit.as_ref().map(|d| pos(d.to_directive())),
+        field.cost.as_ref().map(|d| pos(d.to_directive())),
+        field.invalidate.as_ref().map(|d| pos(d.to_directive())),
     ];
 
     directives.into_iter().flatten().collect()
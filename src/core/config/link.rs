@@ -26,6 +26,8 @@ pub enum LinkType {
     Htpasswd,
     Jwks,
     Grpc,
+    Introspection,
+    ApiKeys,
 }
 
 /// The @link directive allows you to import external resources, such as
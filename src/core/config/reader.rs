@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use rustls_pemfile;
@@ -6,7 +7,10 @@ use rustls_pki_types::{
 };
 use url::Url;
 
-use super::{ConfigModule, Content, Link, LinkType, PrivateKey};
+use super::{
+    ApiKeySource, ApiKeys, ConfigModule, Content, Introspection, Jwks, JwksRefresh, Link, LinkType,
+    PrivateKey,
+};
 use crate::core::config::{Config, ConfigReaderContext, Source};
 use crate::core::merge_right::MergeRight;
 use crate::core::proto_reader::ProtoReader;
@@ -59,6 +63,13 @@ impl ConfigReader {
         let mut extensions = config_module.extensions().clone();
         // let mut base_config = config_module.config().clone();
 
+        // the links used by the upstreams aren't the certificate of the server
+        let upstream_tls: HashSet<String> = std::iter::once(&config_module.upstream)
+            .chain(config_module.upstreams.iter())
+            .flat_map(|upstream| upstream.tls_links())
+            .map(String::from)
+            .collect();
+
         for link in links.iter() {
             let path = Self::resolve_path(&link.src, parent_dir);
 
@@ -86,6 +97,16 @@ impl ConfigReader {
                     let content = source.content;
                     extensions.script = Some(content);
                 }
+                LinkType::Cert | LinkType::Key
+                    if link.id.as_ref().is_some_and(|id| upstream_tls.contains(id)) =>
+                {
+                    let source = self.resource_reader.read_file(path).await?;
+                    let content = source.content;
+
+                    extensions
+                        .upstream_tls
+                        .push(Content { id: link.id.clone(), content });
+                }
                 LinkType::Cert => {
                     let source = self.resource_reader.read_file(path).await?;
                     let content = source.content;
@@ -101,6 +122,7 @@ impl ConfigReader {
                     let content = source.content;
 
                     extensions.endpoint_set = EndpointSet::try_new(&content)?;
+                    extensions.operations.push(content);
                 }
                 LinkType::Htpasswd => {
                     let source = self.resource_reader.read_file(path).await?;
@@ -111,16 +133,51 @@ impl ConfigReader {
                         .push(Content { id: link.id.clone(), content });
                 }
                 LinkType::Jwks => {
-                    let source = self.resource_reader.read_file(path).await?;
+                    let source = self.resource_reader.read_file(path.clone()).await?;
                     let content = source.content;
 
                     let de = &mut serde_json::Deserializer::from_str(&content);
+                    let set = serde_path_to_error::deserialize(de)?;
 
-                    extensions.jwks.push(Content {
+                    // key sets served over http are kept up to date with the identity provider
+                    let refresh = match Url::parse(&path) {
+                        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                            let meta = link.meta.clone().unwrap_or(serde_json::json!({}));
+                            let refresh = serde_json::from_value::<JwksRefresh>(meta)?;
+
+                            Some(JwksRefresh { url: path, ..refresh })
+                        }
+                        _ => None,
+                    };
+
+                    extensions
+                        .jwks
+                        .push(Content { id: link.id.clone(), content: Jwks { set, refresh } })
+                }
+                LinkType::Introspection => {
+                    let meta = link.meta.clone().unwrap_or(serde_json::json!({}));
+                    let introspection = serde_json::from_value::<Introspection>(meta)?;
+
+                    extensions.introspection.push(Content {
                         id: link.id.clone(),
-                        content: serde_path_to_error::deserialize(de)?,
+                        content: Introspection { url: link.src.clone(), ..introspection },
                     })
                 }
+                LinkType::ApiKeys => {
+                    let source = self.resource_reader.read_file(path).await?;
+                    let content = source.content;
+
+                    let de = &mut serde_json::Deserializer::from_str(&content);
+                    let keys = serde_path_to_error::deserialize(de)?;
+                    let source = match link.meta.clone() {
+                        Some(meta) => serde_json::from_value::<ApiKeySource>(meta)?,
+                        None => ApiKeySource::default(),
+                    };
+
+                    extensions
+                        .api_keys
+                        .push(Content { id: link.id.clone(), content: ApiKeys { keys, source } })
+                }
                 LinkType::Grpc => {
                     let meta = self
                         .proto_reader
@@ -226,6 +283,16 @@ impl ConfigReader {
         self.ext_links(ConfigModule::from(config), parent_dir).await
     }
 
+    /// Returns the paths of the files read so far, including the ones that were
+    /// linked from the configs. Remote resources are not included.
+    pub fn files(&self) -> Vec<String> {
+        self.resource_reader
+            .paths()
+            .into_iter()
+            .filter(|path| !Url::parse(path).is_ok_and(|url| url.scheme().starts_with("http")))
+            .collect()
+    }
+
     /// Checks if path is a URL or absolute path, returns directly if so.
     /// Otherwise, it joins file path with relative dir path.
     fn resolve_path(src: &str, root_dir: Option<&Path>) -> String {
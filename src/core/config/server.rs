@@ -7,7 +7,7 @@ use tailcall_macros::DirectiveDefinition;
 
 use super::merge_key_value_vecs;
 use crate::core::config::headers::Headers;
-use crate::core::config::KeyValue;
+use crate::core::config::{KeyValue, RateLimit};
 use crate::core::is_default;
 use crate::core::macros::MergeRight;
 
@@ -47,6 +47,12 @@ pub struct Server {
     /// debugging. Use judiciously. @default `false`.
     pub batch_requests: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `entityCache` sets the limits of the in-memory cache of the values
+    /// resolved with `@cache`, and how its entries are evicted once they're
+    /// reached.
+    pub entity_cache: Option<EntityCache>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `headers` contains key-value pairs that are included as default headers
     /// in server responses, allowing for consistent header management across
@@ -68,11 +74,44 @@ pub struct Server {
     /// and operations. @default `true`.
     pub introspection: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxAliases` sets the maximum number of aliases a query can use.
+    /// @default no limit.
+    pub max_aliases: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxCost` sets the maximum cost of an operation, see `@cost`. The cost
+    /// of every operation is then returned in the `cost` extension of its
+    /// response. @default no limit.
+    pub max_cost: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxDepth` sets the maximum depth to which fields can be nested in a
+    /// query, root fields being at depth 1. @default no limit.
+    pub max_depth: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxFields` sets the maximum number of fields a query can select,
+    /// counting the fields of every level. @default no limit.
+    pub max_fields: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `maxRootFields` sets the maximum number of root fields a query can
+    /// select. @default no limit.
+    pub max_root_fields: Option<usize>,
+
     /// `enableFederation` enables functionality to Tailcall server to act
     /// as a federation subgraph.
     #[serde(default, skip_serializing_if = "is_default")]
     pub enable_federation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `persistedQueries` enables Apollo automatic persisted queries. Clients
+    /// can then send the sha256 hash of a document in
+    /// `extensions.persistedQuery` instead of the document itself, once an
+    /// earlier request registered it. @default disabled.
+    pub persisted_queries: Option<PersistedQueries>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `pipelineFlush` allows to control flushing behavior of the server
     /// pipeline.
@@ -88,6 +127,16 @@ pub struct Server {
     /// @default `false`.
     pub query_validation: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `rateLimit` limits how often each client can send requests to the
+    /// server, see `@rateLimit`.
+    pub rate_limit: Option<RateLimit>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `responseCache` caches the responses of whole queries, which are then
+    /// served without being executed until they expire.
+    pub response_cache: Option<ResponseCache>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// `responseValidation` Tailcall automatically validates responses from
     /// upstream services using inferred schema. @default `false`.
@@ -102,6 +151,14 @@ pub struct Server {
     /// `showcase` enables the /showcase/graphql endpoint.
     pub showcase: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `trustedDocuments` only lets the operations of the files linked with
+    /// `@link(type: Operation)` be executed. Clients refer to them by the name
+    /// of the operation, by the sha256 hash of their document in
+    /// `extensions.persistedQuery` or by sending the document itself.
+    /// @default `false`.
+    pub trusted_documents: Option<bool>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     #[merge_right(merge_right_fn = "merge_right_vars")]
     /// This configuration defines local variables for server operations. Useful
@@ -165,6 +222,142 @@ fn merge_right_vars(mut left: Vec<KeyValue>, right: Vec<KeyValue>) -> Vec<KeyVal
     left
 }
 
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Responses are cached by the normalized document, operation name and
+/// variables of the query. They're kept for the least `maxAge` of the `@cache`
+/// fields resolving them, queries resolving no such field aren't cached, nor
+/// are responses with errors or protected data, or resolved from allowed
+/// headers or claims the cached responses don't vary on.
+pub struct ResponseCache {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Claims of the authenticated client that the cached responses vary on,
+    /// nested claims being separated by dots.
+    pub claims: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Request headers that the cached responses vary on.
+    pub headers: Vec<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Limits of the in-memory entity cache. The entries are split between
+/// shards, each one evicting its own entries, so the limits are approximate.
+pub struct EntityCache {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Policy choosing the entries evicted once a limit is reached. @default
+    /// `LRU`.
+    pub eviction: Option<Eviction>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum number of cached values. @default `100000`.
+    pub max_entries: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum estimated size of the cached values, in bytes. Each shard
+    /// holds an even share of it, and values larger than that share aren't
+    /// cached. @default no limit.
+    pub max_size: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Redis server, or any other server speaking its protocol, that stores
+    /// the cached values instead, sharing them between the instances of the
+    /// gateway. The in-memory limits don't apply then.
+    pub redis: Option<RedisCache>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Limits of the documents registered with automatic persisted queries, which
+/// are kept in the memory of each instance of the gateway.
+pub struct PersistedQueries {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Largest document, in bytes, that can be registered. Larger documents
+    /// are still executed, but have to be sent every time. @default `16384`.
+    pub max_document_size: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum number of registered documents, the least recently used ones
+    /// being evicted first. @default `10000`.
+    pub max_entries: Option<usize>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Connection to the Redis server storing the cached values, which expire
+/// along with the `maxAge` of their `@cache`.
+pub struct RedisCache {
+    /// URL of the server, e.g. `redis://localhost:6379/0`, or `rediss://` for
+    /// TLS connections.
+    pub url: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Format of the values stored in the server. @default `JSON`.
+    pub encoding: Option<CacheEncoding>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Prefix of the keys of the cached values, to share the server with
+    /// other applications. @default `tailcall:`.
+    pub key_prefix: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Number of connections opened to the server, over which the commands
+    /// are spread. @default `4`.
+    pub pool_size: Option<usize>,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+)]
+/// Format of the cached values stored outside of the gateway.
+pub enum CacheEncoding {
+    #[default]
+    /// Readable by other applications, e.g. while debugging.
+    JSON,
+    /// Compact binary format, smaller and faster to decode than JSON.
+    MessagePack,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+)]
+/// Policy choosing the entries evicted from the entity cache.
+pub enum Eviction {
+    #[default]
+    /// Evicts the least recently used entries.
+    LRU,
+    /// Evicts the least recently used entries, unless they're accessed more
+    /// often than the entries replacing them, which are then not cached.
+    TinyLFU,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, schemars::JsonSchema, MergeRight)]
 #[serde(rename_all = "camelCase")]
 pub struct ScriptOptions {
@@ -267,6 +460,10 @@ impl Server {
         self.enable_jit.unwrap_or(true)
     }
 
+    pub fn enable_trusted_documents(&self) -> bool {
+        self.trusted_documents.unwrap_or(false)
+    }
+
     pub fn get_routes(&self) -> Routes {
         self.routes.clone().unwrap_or_default()
     }
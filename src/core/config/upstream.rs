@@ -34,6 +34,172 @@ pub struct Proxy {
     pub url: String,
 }
 
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Retries of the requests to an upstream. Only idempotent requests are
+/// retried: those sent with the `GET`, `HEAD`, `OPTIONS`, `PUT`, `DELETE` or
+/// `TRACE` method, and the queries of gRPC and GraphQL upstreams. Batched
+/// requests aren't retried.
+pub struct Retry {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Attempts made in total, the first one included. @default `3`.
+    pub max_attempts: Option<usize>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Delay before the first retry, in milliseconds. It doubles after every
+    /// attempt, and a random jitter of up to half of it is applied. @default
+    /// `100`.
+    pub backoff: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum delay between two attempts, in milliseconds. @default `5000`.
+    pub max_backoff: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Status codes of the responses that are retried, besides the connection
+    /// errors. @default `[429, 502, 503, 504]`.
+    pub status_codes: Vec<u16>,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Circuit breaker kept for every upstream host. Once too many requests to the
+/// host fail, the circuit opens and the requests fail right away. After a
+/// while, a few requests probe the host again, closing the circuit when they
+/// all succeed. Connection errors and `5xx` responses count as failures, and
+/// batched requests aren't counted.
+pub struct CircuitBreaker {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Percentage of the requests failing within the `window` from which the
+    /// circuit opens. @default `50`.
+    pub failure_rate: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Requests sent within the `window` before the failure rate is taken into
+    /// account. @default `20`.
+    pub minimum_requests: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Duration over which the requests are counted, in milliseconds.
+    /// @default `10000`.
+    pub window: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Duration the circuit stays open before the host is probed again, in
+    /// milliseconds. @default `30000`.
+    pub open_duration: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Probes sent once the circuit is half-open, that must all succeed for it
+    /// to close. @default `3`.
+    pub half_open_requests: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, schemars::JsonSchema, MergeRight)]
+#[serde(rename_all = "camelCase")]
+/// On-disk store of the HTTP cache, which can be shared by several instances
+/// of the gateway.
+pub struct HttpCacheDisk {
+    /// Directory of the cached responses, created when missing.
+    pub path: String,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Maximum size of the cached responses on disk, in bytes, beyond which
+    /// the least recently used ones are removed in the background. @default
+    /// `1073741824`, i.e. 1 GiB.
+    pub max_size: Option<u64>,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Spreads the requests sent to the `baseURL` of the upstream across several
+/// endpoints. The endpoints that fail repeatedly, or respond too slowly, are
+/// left out for a while.
+pub struct LoadBalancer {
+    /// Endpoints the requests are sent to, e.g. `http://10.0.0.1:8080`. They
+    /// replace the scheme, host and port of the `baseURL`, and keep the path.
+    pub endpoints: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// How the endpoint of every request is chosen. @default `RoundRobin`.
+    pub strategy: Option<BalancingStrategy>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Mustache template of the key hashed by the `ConsistentHash` strategy,
+    /// e.g. `{{.args.id}}`, rendered for every resolver. Requests without a
+    /// key, like batched ones, are hashed by their URL.
+    pub hash_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Consecutive failures after which an endpoint is ejected. Connection
+    /// errors, `5xx` responses and responses slower than `maxLatency` count
+    /// as failures. @default `5`.
+    pub max_failures: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Duration of a response, in milliseconds, beyond which it counts as a
+    /// failure.
+    pub max_latency: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Duration an endpoint is left out once ejected, in milliseconds. The
+    /// ejected endpoints are used anyway when none is left. @default `30000`.
+    pub ejection_time: Option<u64>,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    schemars::JsonSchema,
+    MergeRight,
+)]
+/// Strategy choosing the endpoint of the load balanced requests.
+pub enum BalancingStrategy {
+    #[default]
+    /// Picks the endpoints in turn.
+    RoundRobin,
+    /// Picks the endpoint with the fewest requests in flight.
+    LeastInFlight,
+    /// Picks the endpoint from the hash of the `hashKey`, so that the
+    /// requests with the same key reach the same endpoint while it's healthy.
+    ConsistentHash,
+}
+
+#[derive(
+    Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default, schemars::JsonSchema, MergeRight,
+)]
+#[serde(rename_all = "camelCase")]
+/// Certificates used on the TLS connections to the upstream, referenced by the
+/// `id` of their `@link`.
+pub struct Tls {
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Id of the `Cert` link of the client certificate presented to the
+    /// upstream for mutual TLS. Requires `key`.
+    pub cert: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Id of the `Key` link of the private key of `cert`.
+    pub key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// Ids of the `Cert` links of CA certificates trusted along with the
+    /// default roots, e.g. the one of a private CA. A named upstream trusts
+    /// the ones of the default upstream too.
+    pub ca: Vec<String>,
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -48,7 +214,7 @@ pub struct Proxy {
     DirectiveDefinition,
     InputDefinition,
 )]
-#[directive_definition(locations = "Schema")]
+#[directive_definition(repeatable, locations = "Schema")]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase", default)]
 /// The `upstream` directive allows you to control various aspects of the
@@ -81,15 +247,34 @@ pub struct Upstream {
     /// the batch).
     pub batch: Option<Batch>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `circuitBreaker` stops sending requests to an upstream host that fails
+    /// too often, failing them right away until the host recovers. It can be
+    /// overridden on `@http`, `@grpc` and `@graphQL`.
+    pub circuit_breaker: Option<CircuitBreaker>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds that the connection will wait for a response before
     /// timing out.
     pub connect_timeout: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `deadlineHeader` is the header telling the HTTP upstreams how many
+    /// milliseconds are left before the response is no longer awaited, e.g.
+    /// `X-Request-Timeout`. It's only sent when the resolver has a `timeout`
+    /// or the server a `globalResponseTimeout`. gRPC upstreams are told with
+    /// the standard `grpc-timeout` header instead.
+    pub deadline_header: Option<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// Providing httpCache size enables Tailcall's HTTP caching, adhering to the [HTTP Caching RFC](https://tools.ietf.org/html/rfc7234), to enhance performance by minimizing redundant data fetches. Defaults to `0` if unspecified.
     pub http_cache: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `httpCacheDisk` keeps the responses cached by `httpCache` on disk as
+    /// well, so that they survive restarts and can outgrow the memory.
+    pub http_cache_disk: Option<HttpCacheDisk>,
+
     #[setters(strip_option)]
     #[serde(rename = "http2Only", default, skip_serializing_if = "is_default")]
     /// The `http2Only` setting allows you to specify whether the client should
@@ -113,6 +298,19 @@ pub struct Upstream {
     /// sent while the connection is idle.
     pub keep_alive_while_idle: Option<bool>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `loadBalancer` sends the requests to the `baseURL` to several endpoints
+    /// instead, e.g. the pods behind a Kubernetes service, which doesn't
+    /// balance the HTTP/2 streams of gRPC by itself.
+    pub load_balancer: Option<LoadBalancer>,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `name` identifies an upstream with its own clients and settings, that
+    /// `@http`, `@grpc` and `@graphQL` select with their `upstream` argument.
+    /// The settings it doesn't set are taken from the `@upstream` without a
+    /// name, which is used by the other resolvers.
+    pub name: Option<String>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The maximum number of idle connections that will be maintained per host.
     pub pool_max_idle_per_host: Option<usize>,
@@ -129,6 +327,12 @@ pub struct Upstream {
     /// enabling custom routing and security policies.
     pub proxy: Option<Proxy>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `retry` sends the upstream requests again when they fail with a
+    /// connection error or a retryable status code. It can be overridden on
+    /// `@http`, `@grpc` and `@graphQL`.
+    pub retry: Option<Retry>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The time in seconds between each TCP keep-alive message sent to maintain
     /// the connection.
@@ -139,6 +343,11 @@ pub struct Upstream {
     /// response.
     pub timeout: Option<u64>,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    /// `tls` sets a client certificate for mutual TLS and the extra CA
+    /// certificates trusted by the connections to the upstream.
+    pub tls: Option<Tls>,
+
     #[serde(default, skip_serializing_if = "is_default")]
     /// The User-Agent header value to be used in HTTP requests. @default
     /// `Tailcall/1.0`
@@ -212,6 +421,20 @@ impl Upstream {
     pub fn get_verify_ssl(&self) -> bool {
         self.verify_ssl.unwrap_or(true)
     }
+
+    /// Ids of the `Cert` and `Key` links used by `tls`.
+    pub fn tls_links(&self) -> Vec<&str> {
+        self.tls
+            .iter()
+            .flat_map(|tls| {
+                tls.cert
+                    .iter()
+                    .chain(tls.key.iter())
+                    .chain(tls.ca.iter())
+                    .map(String::as_str)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -265,4 +488,26 @@ mod tests {
             Some(["a", "b", "c"].iter().map(|s| s.to_string()).collect())
         );
     }
+
+    #[test]
+    fn tls_merge() {
+        let default = Upstream {
+            tls: Some(Tls { ca: vec!["root".into()], ..Default::default() }),
+            ..Default::default()
+        };
+        let named = Upstream {
+            tls: Some(Tls {
+                cert: Some("client".into()),
+                key: Some("client-key".into()),
+                ca: vec!["internal".into()],
+            }),
+            ..Default::default()
+        };
+        let merged = default.merge_right(named);
+
+        assert_eq!(
+            merged.tls_links(),
+            ["client", "client-key", "root", "internal"]
+        );
+    }
 }
@@ -373,6 +373,10 @@ impl Context {
                     headers: vec![],
                     method: field_name.id(),
                     dedupe: None,
+                    retry: None,
+                    circuit_breaker: None,
+                    timeout: None,
+                    upstream: None,
                 }));
 
                 let method_path =
@@ -5,20 +5,34 @@ use std::time::Duration;
 
 use async_graphql::async_trait;
 use async_graphql::futures_util::future::join_all;
+use hyper::body::Bytes;
 
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
+use crate::core::http::resilience::{DeadlineHeader, LoaderResilience};
 use crate::core::http::{DataLoaderRequest, Response};
 use crate::core::runtime::TargetRuntime;
 
 pub struct GraphqlDataLoader {
     pub runtime: TargetRuntime,
     pub batch: bool,
+    pub resilience: LoaderResilience,
 }
 
 impl GraphqlDataLoader {
     pub fn new(runtime: TargetRuntime, batch: bool) -> Self {
-        GraphqlDataLoader { runtime, batch }
+        GraphqlDataLoader { runtime, batch, resilience: LoaderResilience::default() }
+    }
+
+    pub fn resilience(self, resilience: LoaderResilience) -> Self {
+        Self { resilience, ..self }
+    }
+
+    async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        let header = DeadlineHeader::millis(&self.resilience.resilience);
+        self.resilience
+            .execute(self.runtime.http.as_ref(), request, header)
+            .await
     }
 
     pub fn into_data_loader(
@@ -43,12 +57,12 @@ impl Loader<DataLoaderRequest> for GraphqlDataLoader {
     ) -> async_graphql::Result<HashMap<DataLoaderRequest, Self::Value>, Self::Error> {
         if self.batch {
             let batched_req = create_batched_request(keys);
-            let result = self.runtime.http.execute(batched_req).await?.to_json();
+            let result = self.execute(batched_req).await?.to_json();
             let hashmap = extract_responses(result, keys);
             Ok(hashmap)
         } else {
             let results = keys.iter().map(|key| async {
-                let result = self.runtime.http.execute(key.to_request()).await;
+                let result = self.execute(key.to_request()).await;
                 (key.clone(), result)
             });
             let results = join_all(results).await;
@@ -9,11 +9,12 @@ use async_graphql_value::ConstValue;
 
 use super::data_loader_request::DataLoaderRequest;
 use super::protobuf::ProtobufOperation;
-use super::request::execute_grpc_request;
+use super::request::to_grpc_response;
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::request::create_grpc_request;
+use crate::core::http::resilience::{DeadlineHeader, LoaderResilience};
 use crate::core::http::Response;
 use crate::core::json::JsonLike;
 use crate::core::runtime::TargetRuntime;
@@ -23,6 +24,7 @@ pub struct GrpcDataLoader {
     pub(crate) runtime: TargetRuntime,
     pub(crate) operation: ProtobufOperation,
     pub(crate) group_by: Option<GroupBy>,
+    pub(crate) resilience: LoaderResilience,
 }
 
 impl GrpcDataLoader {
@@ -32,13 +34,26 @@ impl GrpcDataLoader {
             .max_batch_size(batch.max_size.unwrap_or_default())
     }
 
+    async fn execute(&self, request: reqwest::Request) -> Result<Response<async_graphql::Value>> {
+        let response = self
+            .resilience
+            .execute(
+                self.runtime.http2_only.as_ref(),
+                request,
+                Some(DeadlineHeader::Grpc),
+            )
+            .await?;
+
+        to_grpc_response(response, &self.operation)
+    }
+
     async fn load_dedupe_only(
         &self,
         keys: &[DataLoaderRequest],
     ) -> anyhow::Result<HashMap<DataLoaderRequest, Response<async_graphql::Value>>> {
         let results = keys.iter().map(|key| async {
             let result = match key.to_request() {
-                Ok(req) => execute_grpc_request(&self.runtime, &self.operation, req).await,
+                Ok(req) => self.execute(req).await,
                 Err(error) => Err(error),
             };
 
@@ -75,8 +90,7 @@ impl GrpcDataLoader {
             multiple_body,
         );
 
-        let response =
-            execute_grpc_request(&self.runtime, &self.operation, multiple_request).await?;
+        let response = self.execute(multiple_request).await?;
 
         let path = &group_by.path();
         let response_body = response.body.group_by(path);
@@ -1,5 +1,6 @@
 use anyhow::{bail, Result};
 use http::{HeaderMap, Method};
+use hyper::body::Bytes;
 use reqwest::Request;
 use url::Url;
 
@@ -23,7 +24,14 @@ pub async fn execute_grpc_request(
     request: Request,
 ) -> Result<Response<async_graphql::Value>> {
     let response = runtime.http2_only.execute(request).await?;
+    to_grpc_response(response, operation)
+}
 
+/// Decodes the response of an upstream gRPC call, failing on error statuses.
+pub fn to_grpc_response(
+    response: Response<Bytes>,
+    operation: &ProtobufOperation,
+) -> Result<Response<async_graphql::Value>> {
     let grpc_status = response
         .headers
         .get(GRPC_STATUS)
@@ -5,10 +5,12 @@ use std::time::Duration;
 use async_graphql::async_trait;
 use async_graphql::futures_util::future::join_all;
 use async_graphql_value::ConstValue;
+use hyper::body::Bytes;
 
 use crate::core::config::group_by::GroupBy;
 use crate::core::config::Batch;
 use crate::core::data_loader::{DataLoader, Loader};
+use crate::core::http::resilience::{DeadlineHeader, LoaderResilience};
 use crate::core::http::{DataLoaderRequest, Response};
 use crate::core::json::JsonLike;
 use crate::core::runtime::TargetRuntime;
@@ -36,6 +38,7 @@ pub struct HttpDataLoader {
     pub runtime: TargetRuntime,
     pub group_by: Option<GroupBy>,
     pub body: fn(&HashMap<String, Vec<&ConstValue>>, &str) -> ConstValue,
+    pub resilience: LoaderResilience,
 }
 impl HttpDataLoader {
     pub fn new(runtime: TargetRuntime, group_by: Option<GroupBy>, is_list: bool) -> Self {
@@ -47,9 +50,21 @@ impl HttpDataLoader {
             } else {
                 get_body_value_single
             },
+            resilience: LoaderResilience::default(),
         }
     }
 
+    pub fn resilience(self, resilience: LoaderResilience) -> Self {
+        Self { resilience, ..self }
+    }
+
+    async fn execute(&self, request: reqwest::Request) -> anyhow::Result<Response<Bytes>> {
+        let header = DeadlineHeader::millis(&self.resilience.resilience);
+        self.resilience
+            .execute(self.runtime.http.as_ref(), request, header)
+            .await
+    }
+
     pub fn to_data_loader(self, batch: Batch) -> DataLoader<DataLoaderRequest, HttpDataLoader> {
         DataLoader::new(self)
             .delay(Duration::from_millis(batch.delay as u64))
@@ -90,12 +105,7 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
             }
 
             // Dispatch request
-            let res = self
-                .runtime
-                .http
-                .execute(request)
-                .await?
-                .to_json::<ConstValue>()?;
+            let res = self.execute(request).await?.to_json::<ConstValue>()?;
 
             // Create a response HashMap
             #[allow(clippy::mutable_key_type)]
@@ -126,7 +136,7 @@ impl Loader<DataLoaderRequest> for HttpDataLoader {
             Ok(hashmap)
         } else {
             let results = keys.iter().map(|key| async {
-                let result = self.runtime.http.execute(key.to_request()).await;
+                let result = self.execute(key.to_request()).await;
                 (key.clone(), result)
             });
 
@@ -0,0 +1,113 @@
+use hyper::body::Bytes;
+
+/// Incrementally decodes a `text/event-stream` body.
+///
+/// Chunks received from the upstream can split events at arbitrary
+/// positions, so the decoder buffers the incomplete tail until the blank line
+/// terminating the event arrives.
+#[derive(Default, Debug)]
+pub struct EventStreamDecoder {
+    buffer: Vec<u8>,
+}
+
+impl EventStreamDecoder {
+    /// Feeds the next chunk of the body and returns the `data` of every event
+    /// completed by it. Events without any `data` field (e.g. keep-alive
+    /// comments) are skipped.
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer
+            .extend(chunk.iter().filter(|byte| **byte != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block = self.buffer.drain(..end + 2).collect::<Vec<_>>();
+            if let Some(data) = Self::data(&String::from_utf8_lossy(&block)) {
+                events.push(data);
+            }
+        }
+
+        events
+    }
+
+    fn data(block: &str) -> Option<String> {
+        let lines = block
+            .lines()
+            .filter_map(|line| line.strip_prefix("data"))
+            .filter_map(|value| match value.strip_prefix(':') {
+                Some(value) => Some(value.strip_prefix(' ').unwrap_or(value)),
+                // a line with the field name only has an empty value
+                None if value.is_empty() => Some(value),
+                None => None,
+            })
+            .collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+/// Encodes a single `text/event-stream` event with the given name.
+pub fn encode_event(event: &str, data: &str) -> Bytes {
+    let mut frame = format!("event: {event}\n");
+    for line in data.split('\n') {
+        frame.push_str("data: ");
+        frame.push_str(line);
+        frame.push('\n');
+    }
+    frame.push('\n');
+
+    Bytes::from(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_complete_events() {
+        let mut decoder = EventStreamDecoder::default();
+        let events = decoder.decode(b"data: {\"id\":1}\n\ndata: {\"id\":2}\n\n");
+
+        assert_eq!(events, vec![r#"{"id":1}"#, r#"{"id":2}"#]);
+    }
+
+    #[test]
+    fn test_decode_split_chunks() {
+        let mut decoder = EventStreamDecoder::default();
+
+        assert!(decoder.decode(b"event: update\ndata: {\"i").is_empty());
+        assert!(decoder.decode(b"d\":1}\r\n").is_empty());
+        assert_eq!(decoder.decode(b"\r\ndata: 2"), vec![r#"{"id":1}"#]);
+        assert_eq!(decoder.decode(b"\n\n"), vec!["2"]);
+    }
+
+    #[test]
+    fn test_decode_multiline_data() {
+        let mut decoder = EventStreamDecoder::default();
+        let events = decoder.decode(b"data: {\ndata:\"id\":1\ndata: }\n\n");
+
+        assert_eq!(events, vec!["{\n\"id\":1\n}"]);
+    }
+
+    #[test]
+    fn test_decode_skips_events_without_data() {
+        let mut decoder = EventStreamDecoder::default();
+        let events = decoder.decode(b": keep-alive\n\nid: 1\nretry: 100\n\ndatum: 1\n\n");
+
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_encode_event() {
+        let frame = encode_event("next", "{\"data\":null}");
+        assert_eq!(frame, Bytes::from("event: next\ndata: {\"data\":null}\n\n"));
+
+        let frame = encode_event("complete", "");
+        assert_eq!(frame, Bytes::from("event: complete\ndata: \n\n"));
+    }
+}
@@ -0,0 +1,340 @@
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use fnv::FnvHasher;
+use http::header::HeaderName;
+use hyper::body::Bytes;
+use reqwest::Request;
+use url::{Origin, Url};
+
+use super::resilience::{now, Outcome};
+use super::Response;
+use crate::core::blueprint::LoadBalancer;
+use crate::core::config::BalancingStrategy;
+use crate::core::{ByteStream, HttpIO};
+
+/// Carries the key rendered by the resolver for the `ConsistentHash`
+/// strategy. It's removed before the request is sent.
+pub static HASH_KEY: HeaderName = HeaderName::from_static("x-tailcall-hash-key");
+
+struct Endpoint {
+    url: Url,
+    in_flight: AtomicUsize,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    /// Consecutive failures since the last success or ejection
+    failures: u64,
+    /// Milliseconds since the epoch until which the endpoint is left out
+    ejected_until: i64,
+}
+
+/// Endpoints of an upstream, with the state of their requests.
+pub struct Balancer {
+    origin: Origin,
+    endpoints: Vec<Endpoint>,
+    strategy: BalancingStrategy,
+    max_failures: u64,
+    max_latency: Option<i64>,
+    ejection_time: i64,
+    next: AtomicUsize,
+}
+
+impl Balancer {
+    /// Returns `None` unless the base URL and some endpoints are valid, which
+    /// they are once the blueprint is built.
+    pub fn new(base_url: &str, load_balancer: &LoadBalancer) -> Option<Self> {
+        let origin = Url::parse(base_url).ok()?.origin();
+        let endpoints: Vec<_> = load_balancer
+            .endpoints
+            .iter()
+            .filter_map(|endpoint| Url::parse(endpoint).ok())
+            .map(|url| Endpoint {
+                url,
+                in_flight: AtomicUsize::new(0),
+                health: Default::default(),
+            })
+            .collect();
+
+        (!endpoints.is_empty()).then(|| Self {
+            origin,
+            endpoints,
+            strategy: load_balancer.strategy,
+            max_failures: load_balancer.max_failures,
+            max_latency: load_balancer.max_latency.map(|latency| latency as i64),
+            ejection_time: load_balancer.ejection_time as i64,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    fn is_healthy(&self, endpoint: &Endpoint, now: i64) -> bool {
+        endpoint.health.lock().unwrap().ejected_until <= now
+    }
+
+    /// Index of the endpoint for the next request. The ejected endpoints are
+    /// only picked when all of them are.
+    fn pick(&self, key: u64, now: i64) -> usize {
+        let mut candidates: Vec<usize> = (0..self.endpoints.len())
+            .filter(|index| self.is_healthy(&self.endpoints[*index], now))
+            .collect();
+        if candidates.is_empty() {
+            candidates = (0..self.endpoints.len()).collect();
+        }
+
+        match self.strategy {
+            BalancingStrategy::RoundRobin => {
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                candidates[next % candidates.len()]
+            }
+            BalancingStrategy::LeastInFlight => {
+                // the rotation spreads the requests between endpoints as busy
+                let offset = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..candidates.len())
+                    .map(|i| candidates[(offset + i) % candidates.len()])
+                    .min_by_key(|index| self.endpoints[*index].in_flight.load(Ordering::Relaxed))
+                    .unwrap_or_default()
+            }
+            // rendezvous hashing: the keys of an ejected endpoint move to the
+            // others, and come back once it recovers
+            BalancingStrategy::ConsistentHash => candidates
+                .into_iter()
+                .max_by_key(|index| {
+                    let mut hasher = FnvHasher::with_key(key);
+                    hasher.write(self.endpoints[*index].url.as_str().as_bytes());
+                    hasher.finish()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn record(&self, index: usize, failure: bool, latency: i64, now: i64) {
+        let endpoint = &self.endpoints[index];
+        let failure = failure || self.max_latency.is_some_and(|max| latency > max);
+        let mut health = endpoint.health.lock().unwrap();
+
+        if !failure {
+            health.failures = 0;
+            return;
+        }
+
+        health.failures += 1;
+        if health.failures >= self.max_failures && health.ejected_until <= now {
+            health.failures = 0;
+            health.ejected_until = now + self.ejection_time;
+            tracing::warn!(
+                endpoint = %endpoint.url,
+                duration = self.ejection_time,
+                "Ejected an unhealthy upstream endpoint"
+            );
+        }
+    }
+
+    /// Points the request at an endpoint, if it's sent to the balanced
+    /// origin, and returns the index of the endpoint.
+    fn route(&self, request: &mut Request) -> Option<usize> {
+        let key = request.headers_mut().remove(&HASH_KEY);
+        if request.url().origin() != self.origin {
+            return None;
+        }
+
+        let mut hasher = FnvHasher::default();
+        match &key {
+            Some(key) => hasher.write(key.as_bytes()),
+            None => hasher.write(request.url().as_str().as_bytes()),
+        }
+        let index = self.pick(hasher.finish(), now());
+
+        let endpoint = &self.endpoints[index].url;
+        let url = request.url_mut();
+        // both URLs are absolute, which the setters can't fail on
+        let _ = url.set_scheme(endpoint.scheme());
+        let _ = url.set_host(endpoint.host_str());
+        let _ = url.set_port(endpoint.port());
+
+        Some(index)
+    }
+}
+
+/// Decrements the requests in flight of an endpoint when the request ends,
+/// including when it's cancelled.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Client sending the requests of an upstream to the endpoints of its
+/// [Balancer].
+pub struct BalancedHttp {
+    http: Arc<dyn HttpIO>,
+    balancer: Arc<Balancer>,
+}
+
+impl BalancedHttp {
+    pub fn new(http: Arc<dyn HttpIO>, balancer: Arc<Balancer>) -> Self {
+        Self { http, balancer }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpIO for BalancedHttp {
+    async fn execute(&self, mut request: Request) -> anyhow::Result<Response<Bytes>> {
+        let Some(index) = self.balancer.route(&mut request) else {
+            return self.http.execute(request).await;
+        };
+
+        let _in_flight = InFlight::new(&self.balancer.endpoints[index].in_flight);
+        let start = now();
+        let result = self.http.execute(request).await;
+        let end = now();
+        let failure = Outcome::new(&result).is_failure();
+        self.balancer.record(index, failure, end - start, end);
+
+        result
+    }
+
+    async fn execute_stream(&self, mut request: Request) -> anyhow::Result<Response<ByteStream>> {
+        let Some(index) = self.balancer.route(&mut request) else {
+            return self.http.execute_stream(request).await;
+        };
+
+        // the latency of a stream is the one of its first response
+        let _in_flight = InFlight::new(&self.balancer.endpoints[index].in_flight);
+        let start = now();
+        let result = self.http.execute_stream(request).await;
+        let end = now();
+        let failure = Outcome::new(&result).is_failure();
+        self.balancer.record(index, failure, end - start, end);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use reqwest::{Method, StatusCode};
+
+    use super::*;
+
+    /// Responds with the status set for the host of the request.
+    #[derive(Default)]
+    struct TestHttp {
+        hosts: StdMutex<Vec<String>>,
+        failing: Option<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for TestHttp {
+        async fn execute(&self, request: Request) -> anyhow::Result<Response<Bytes>> {
+            let host = request.url().host_str().unwrap().to_owned();
+            let status = if self.failing.as_ref() == Some(&host) {
+                StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                StatusCode::OK
+            };
+            self.hosts.lock().unwrap().push(host);
+            Ok(Response { status, headers: Default::default(), body: Bytes::new() })
+        }
+    }
+
+    fn balanced(
+        strategy: BalancingStrategy,
+        failing: Option<&str>,
+    ) -> (Arc<TestHttp>, BalancedHttp) {
+        let load_balancer = LoadBalancer {
+            endpoints: vec![
+                "http://a:8080".into(),
+                "http://b:8080".into(),
+                "http://c:8080".into(),
+            ],
+            strategy,
+            max_failures: 2,
+            max_latency: None,
+            ejection_time: 60_000,
+        };
+        let balancer = Balancer::new("http://users.svc/api", &load_balancer).unwrap();
+        let http = Arc::new(TestHttp { failing: failing.map(String::from), ..Default::default() });
+
+        (http.clone(), BalancedHttp::new(http, Arc::new(balancer)))
+    }
+
+    fn request(url: &str, key: Option<&str>) -> Request {
+        let mut request = Request::new(Method::GET, url.parse().unwrap());
+        if let Some(key) = key {
+            request
+                .headers_mut()
+                .insert(HASH_KEY.clone(), key.parse().unwrap());
+        }
+        request
+    }
+
+    fn hosts(http: &TestHttp) -> Vec<String> {
+        std::mem::take(&mut *http.hosts.lock().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_round_robin() {
+        let (http, balanced) = balanced(BalancingStrategy::RoundRobin, None);
+        for _ in 0..4 {
+            balanced
+                .execute(request("http://users.svc/api/users/1", None))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(hosts(&http), ["a", "b", "c", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_other_origin() {
+        let (http, balanced) = balanced(BalancingStrategy::RoundRobin, None);
+        balanced
+            .execute(request("http://orders.svc/api/orders", None))
+            .await
+            .unwrap();
+
+        assert_eq!(hosts(&http), ["orders.svc"]);
+    }
+
+    #[tokio::test]
+    async fn test_consistent_hash() {
+        let (http, balanced) = balanced(BalancingStrategy::ConsistentHash, None);
+        for key in ["1", "2", "1", "2"] {
+            balanced
+                .execute(request("http://users.svc/api/users", Some(key)))
+                .await
+                .unwrap();
+        }
+
+        let hosts = hosts(&http);
+        assert_eq!(hosts[0], hosts[2]);
+        assert_eq!(hosts[1], hosts[3]);
+    }
+
+    #[tokio::test]
+    async fn test_ejection() {
+        let (http, balanced) = balanced(BalancingStrategy::RoundRobin, Some("b"));
+        for _ in 0..8 {
+            let _ = balanced
+                .execute(request("http://users.svc/api/users/1", None))
+                .await;
+        }
+
+        // b is ejected after its second failure
+        assert_eq!(hosts(&http), ["a", "b", "c", "a", "b", "c", "a", "c"]);
+    }
+}
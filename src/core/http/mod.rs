@@ -1,22 +1,27 @@
 pub use cache::*;
 pub use data_loader::*;
 pub use data_loader_request::*;
+pub use event_stream::*;
 use http::header::HeaderValue;
 pub use method::Method;
 pub use query_encoder::QueryEncoder;
 pub use request_context::RequestContext;
-pub use request_handler::{handle_request, API_URL_PREFIX};
+pub use request_handler::{client_ip, create_request_context, handle_request, API_URL_PREFIX};
 pub use request_template::RequestTemplate;
+pub use resilience::{CircuitBreakers, CircuitOpen};
 pub use response::*;
 
 mod cache;
 mod data_loader;
 mod data_loader_request;
+mod event_stream;
+pub mod load_balancer;
 mod method;
 mod query_encoder;
 mod request_context;
 mod request_handler;
 mod request_template;
+pub mod resilience;
 mod response;
 pub mod showcase;
 mod telemetry;
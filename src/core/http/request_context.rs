@@ -1,3 +1,4 @@
+use std::net::IpAddr;
 use std::num::NonZeroU64;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
@@ -13,7 +14,7 @@ use crate::core::blueprint::{Server, Upstream};
 use crate::core::data_loader::{DataLoader, DedupeResult};
 use crate::core::graphql::GraphqlDataLoader;
 use crate::core::grpc::data_loader::GrpcDataLoader;
-use crate::core::http::{DataLoaderRequest, HttpDataLoader};
+use crate::core::http::{CircuitBreakers, DataLoaderRequest, HttpDataLoader};
 use crate::core::ir::model::IoId;
 use crate::core::ir::Error;
 use crate::core::runtime::TargetRuntime;
@@ -28,15 +29,38 @@ pub struct RequestContext {
     // A subset of all the headers received in the GraphQL Request that will be sent to the
     // upstream.
     pub allowed_headers: HeaderMap,
+    // All the headers received in the GraphQL Request, read by the gateway itself, e.g. to find
+    // the API key of the client. They aren't sent to the upstream.
+    pub request_headers: HeaderMap,
+    // Query string of the GraphQL Request, auth providers can read credentials from it.
+    pub query: Option<String>,
+    // Address of the client that sent the GraphQL Request, when known.
+    pub client_ip: Option<IpAddr>,
     pub auth_ctx: AuthContext,
     pub http_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, HttpDataLoader>>>,
     pub gql_data_loaders: Arc<Vec<DataLoader<DataLoaderRequest, GraphqlDataLoader>>>,
     pub grpc_data_loaders: Arc<Vec<DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>>>,
     pub min_max_age: Arc<Mutex<Option<i32>>>,
     pub cache_public: Arc<Mutex<Option<bool>>>,
+    // Seconds after which a rate limited client can retry the request.
+    pub retry_after: Arc<Mutex<Option<u64>>>,
+    // Least maxAge, in milliseconds, of the @cache values used to resolve the request.
+    pub cache_max_age: Arc<Mutex<Option<NonZeroU64>>>,
+    // Tags of the @cache values used to resolve the request.
+    pub cache_tags: Arc<Mutex<Vec<String>>>,
+    // Whether the request read claims that the cached responses don't vary on.
+    pub reads_uncached_claims: Arc<Mutex<bool>>,
     pub runtime: TargetRuntime,
     pub cache: DedupeResult<IoId, ConstValue, Error>,
     pub dedupe_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    // Background refreshes of stale @cache values, shared by all the requests.
+    pub revalidate_handler: Arc<DedupeResult<IoId, ConstValue, Error>>,
+    // State of the circuit breakers of the upstream hosts, shared by all the
+    // requests.
+    pub circuit_breakers: Arc<CircuitBreakers>,
+    // Milliseconds since the epoch after which the response is no longer
+    // awaited, set by the globalResponseTimeout.
+    pub deadline: Option<i64>,
 }
 
 impl RequestContext {
@@ -51,10 +75,20 @@ impl RequestContext {
             grpc_data_loaders: Arc::new(vec![]),
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
+            retry_after: Arc::new(Mutex::new(None)),
+            cache_max_age: Arc::new(Mutex::new(None)),
+            cache_tags: Arc::new(Mutex::new(Vec::new())),
+            reads_uncached_claims: Arc::new(Mutex::new(false)),
             runtime: target_runtime,
             cache: DedupeResult::new(true),
             dedupe_handler: Arc::new(DedupeResult::new(false)),
+            revalidate_handler: Arc::new(DedupeResult::new(false)),
+            circuit_breakers: Arc::new(CircuitBreakers::default()),
+            deadline: None,
             allowed_headers: HeaderMap::new(),
+            request_headers: HeaderMap::new(),
+            query: None,
+            client_ip: None,
             auth_ctx: AuthContext::default(),
         }
     }
@@ -86,6 +120,86 @@ impl RequestContext {
         }
     }
 
+    /// Keeps the longest time to wait of all the rate limits the request
+    /// exceeded.
+    pub fn set_retry_after(&self, retry_after: u64) {
+        let mut current = self.retry_after.lock().unwrap();
+        *current = Some(current.map_or(retry_after, |current| current.max(retry_after)));
+    }
+
+    pub fn get_retry_after(&self) -> Option<u64> {
+        *self.retry_after.lock().unwrap()
+    }
+
+    pub fn set_cache_max_age(&self, max_age: NonZeroU64) {
+        let mut current = self.cache_max_age.lock().unwrap();
+        *current = Some(current.map_or(max_age, |current| current.min(max_age)));
+    }
+
+    pub fn get_cache_max_age(&self) -> Option<NonZeroU64> {
+        *self.cache_max_age.lock().unwrap()
+    }
+
+    /// A copy of the context that outlives the request, to resolve values once
+    /// the request is responded to. Nothing set on the copy is sent to the
+    /// client.
+    pub fn detach(&self) -> RequestContext {
+        RequestContext {
+            server: self.server.clone(),
+            upstream: self.upstream.clone(),
+            x_response_headers: Arc::new(Mutex::new(HeaderMap::new())),
+            cookie_headers: None,
+            allowed_headers: self.allowed_headers.clone(),
+            request_headers: self.request_headers.clone(),
+            query: self.query.clone(),
+            client_ip: self.client_ip,
+            auth_ctx: self.auth_ctx.detach(),
+            http_data_loaders: self.http_data_loaders.clone(),
+            gql_data_loaders: self.gql_data_loaders.clone(),
+            grpc_data_loaders: self.grpc_data_loaders.clone(),
+            min_max_age: Arc::new(Mutex::new(None)),
+            cache_public: Arc::new(Mutex::new(None)),
+            retry_after: Arc::new(Mutex::new(None)),
+            cache_max_age: Arc::new(Mutex::new(None)),
+            cache_tags: Arc::new(Mutex::new(Vec::new())),
+            reads_uncached_claims: Arc::new(Mutex::new(false)),
+            runtime: self.runtime.clone(),
+            cache: DedupeResult::new(true),
+            dedupe_handler: self.dedupe_handler.clone(),
+            revalidate_handler: self.revalidate_handler.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+            // no client awaits the values resolved once the request is responded to
+            deadline: None,
+        }
+    }
+
+    pub fn add_cache_tags(&self, tags: &[String]) {
+        let mut current = self.cache_tags.lock().unwrap();
+        for tag in tags {
+            if !current.contains(tag) {
+                current.push(tag.clone());
+            }
+        }
+    }
+
+    pub fn get_cache_tags(&self) -> Vec<String> {
+        self.cache_tags.lock().unwrap().clone()
+    }
+
+    /// Records that the claim at the given path was read to resolve the
+    /// request, whose response can't be cached unless it varies on the claim.
+    pub fn read_claim<T: AsRef<str>>(&self, path: &[T]) {
+        if let Some(response_cache) = self.server.response_cache.as_ref() {
+            if !response_cache.varies_on_claim(path) {
+                *self.reads_uncached_claims.lock().unwrap() = true;
+            }
+        }
+    }
+
+    pub fn has_read_uncached_claims(&self) -> bool {
+        *self.reads_uncached_claims.lock().unwrap()
+    }
+
     pub fn set_cache_visibility(&self, cachability: &Option<Cachability>) {
         if let Some(Cachability::Private) = cachability {
             self.set_cache_public_false()
@@ -190,21 +304,34 @@ impl From<&AppContext> for RequestContext {
         } else {
             None
         };
+        let global_response_timeout = app_ctx.blueprint.server.global_response_timeout;
+        let deadline = (global_response_timeout > 0)
+            .then(|| chrono::Utc::now().timestamp_millis() + global_response_timeout);
         Self {
             server: app_ctx.blueprint.server.clone(),
             upstream: app_ctx.blueprint.upstream.clone(),
             x_response_headers: Arc::new(Mutex::new(HeaderMap::new())),
             cookie_headers,
             allowed_headers: HeaderMap::new(),
+            request_headers: HeaderMap::new(),
+            query: None,
+            client_ip: None,
             auth_ctx: (&app_ctx.auth_ctx).into(),
             http_data_loaders: app_ctx.http_data_loaders.clone(),
             gql_data_loaders: app_ctx.gql_data_loaders.clone(),
             grpc_data_loaders: app_ctx.grpc_data_loaders.clone(),
             min_max_age: Arc::new(Mutex::new(None)),
             cache_public: Arc::new(Mutex::new(None)),
+            retry_after: Arc::new(Mutex::new(None)),
+            cache_max_age: Arc::new(Mutex::new(None)),
+            cache_tags: Arc::new(Mutex::new(Vec::new())),
+            reads_uncached_claims: Arc::new(Mutex::new(false)),
             runtime: app_ctx.runtime.clone(),
             cache: DedupeResult::new(true),
             dedupe_handler: app_ctx.dedupe_handler.clone(),
+            revalidate_handler: app_ctx.revalidate_handler.clone(),
+            circuit_breakers: app_ctx.circuit_breakers.clone(),
+            deadline,
         }
     }
 }
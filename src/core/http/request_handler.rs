@@ -1,4 +1,5 @@
 use std::collections::BTreeSet;
+use std::net::{IpAddr, SocketAddr};
 use std::ops::Deref;
 use std::sync::Arc;
 
@@ -19,10 +20,13 @@ use super::request_context::RequestContext;
 use super::telemetry::{get_response_status_code, RequestCounter};
 use super::{showcase, telemetry, TAILCALL_HTTPS_ORIGIN, TAILCALL_HTTP_ORIGIN};
 use crate::core::app_context::AppContext;
-use crate::core::async_graphql_hyper::{GraphQLRequestLike, GraphQLResponse};
+use crate::core::async_graphql_hyper::{
+    GraphQLQuery, GraphQLRequest, GraphQLRequestLike, GraphQLResponse,
+};
 use crate::core::blueprint::telemetry::TelemetryExporter;
 use crate::core::config::{PrometheusExporter, PrometheusFormat};
 use crate::core::jit::JITExecutor;
+use crate::core::{persisted_query, rate_limit, response_cache};
 
 pub const API_URL_PREFIX: &str = "/api";
 
@@ -54,13 +58,23 @@ fn not_found() -> Result<Response<Body>> {
         .body(Body::empty())?)
 }
 
-fn create_request_context(req: &Request<Body>, app_ctx: &AppContext) -> RequestContext {
+/// Creates the context of a single request, forwarding only the headers that
+/// are allowed by `@upstream(allowedHeaders: ...)`.
+pub fn create_request_context(headers: &HeaderMap, app_ctx: &AppContext) -> RequestContext {
     let upstream = app_ctx.blueprint.upstream.clone();
     let allowed = upstream.allowed_headers;
-    let allowed_headers = create_allowed_headers(req.headers(), &allowed);
+    let allowed_headers = create_allowed_headers(headers, &allowed);
 
     let _allowed = app_ctx.blueprint.server.get_experimental_headers();
-    RequestContext::from(app_ctx).allowed_headers(allowed_headers)
+    RequestContext::from(app_ctx)
+        .allowed_headers(allowed_headers)
+        .request_headers(headers.clone())
+}
+
+/// Address of the client, when the server put the remote address of the
+/// connection into the extensions of the request.
+pub fn client_ip<B>(req: &Request<B>) -> Option<IpAddr> {
+    req.extensions().get::<SocketAddr>().map(SocketAddr::ip)
 }
 
 fn update_cache_control_header(
@@ -95,6 +109,11 @@ pub fn update_response_headers(
 
     // Insert Experimental Headers
     req_ctx.extend_x_headers(resp.headers_mut());
+
+    if let Some(retry_after) = req_ctx.get_retry_after() {
+        resp.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
 }
 
 #[tracing::instrument(skip_all, fields(otel.name = "graphQL", otel.kind = ?SpanKind::Server))]
@@ -104,7 +123,11 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
     req_counter: &mut RequestCounter,
 ) -> Result<Response<Body>> {
     req_counter.set_http_route("/graphql");
-    let req_ctx = Arc::new(create_request_context(&req, app_ctx));
+    let req_ctx = Arc::new(
+        create_request_context(req.headers(), app_ctx)
+            .query(req.uri().query().map(str::to_owned))
+            .client_ip(client_ip(&req)),
+    );
     let (req, body) = req.into_parts();
     let bytes = hyper::body::to_bytes(body).await?;
     let graphql_request = serde_json::from_slice::<T>(&bytes);
@@ -129,12 +152,119 @@ pub async fn graphql_request<T: DeserializeOwned + GraphQLRequestLike>(
     }
 }
 
+async fn graphql_get_request(
+    req: Request<Body>,
+    app_ctx: &Arc<AppContext>,
+    req_counter: &mut RequestCounter,
+) -> Result<Response<Body>> {
+    req_counter.set_http_route("/graphql");
+    let req_ctx = Arc::new(
+        create_request_context(req.headers(), app_ctx)
+            .query(req.uri().query().map(str::to_owned))
+            .client_ip(client_ip(&req)),
+    );
+    let query = req.uri().query().unwrap_or_default();
+
+    // variables and extensions are JSON encoded within the query string
+    let request = serde_urlencoded::from_str::<GraphQLQuery>(query)
+        .map_err(|err| err.to_string())
+        .and_then(|query| GraphQLRequest::try_from(query).map_err(|err| err.to_string()));
+
+    match request {
+        Ok(request) => {
+            let (req, _) = req.into_parts();
+            execute_query(app_ctx, &req_ctx, request, req).await
+        }
+        Err(err) => {
+            let server_error =
+                ServerError::new(format!("Unexpected GraphQL Request: {}", err), None);
+            let response = async_graphql::Response::from_errors(vec![server_error]);
+
+            let mut resp = GraphQLResponse::from(response).into_response()?;
+            *resp.status_mut() = StatusCode::BAD_REQUEST;
+            Ok(resp)
+        }
+    }
+}
+
 async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
     app_ctx: &Arc<AppContext>,
     req_ctx: &Arc<RequestContext>,
     mut request: T,
     req: Parts,
 ) -> anyhow::Result<Response<Body>> {
+    if let Err(error) = rate_limit::check_request(req_ctx).await {
+        let response = async_graphql::Response::from_errors(vec![error.into()]);
+        let mut resp = GraphQLResponse::from(response).into_response()?;
+        update_response_headers(&mut resp, req_ctx, app_ctx);
+        return Ok(resp);
+    }
+    req_ctx.auth_ctx.verify_for_templates(req_ctx).await;
+
+    for operation in request.requests_mut() {
+        let resolved = match app_ctx.trusted_documents.as_ref() {
+            Some(documents) => documents.resolve(operation).map_err(ServerError::from),
+            None => persisted_query::resolve(operation, app_ctx.persisted_queries.as_ref())
+                .await
+                .map_err(ServerError::from),
+        };
+        if let Err(error) = resolved {
+            let response = async_graphql::Response::from_errors(vec![error]);
+            let mut resp = GraphQLResponse::from(response).into_response()?;
+            update_response_headers(&mut resp, req_ctx, app_ctx);
+            return Ok(resp);
+        }
+    }
+
+    // GET requests can be cached along the way, so they can't change any data
+    if req.method == Method::GET && !request.selects_queries() {
+        let response = async_graphql::Response::from_errors(vec![ServerError::new(
+            "Only queries can be sent with GET, use POST instead",
+            None,
+        )]);
+        let mut resp = GraphQLResponse::from(response).into_response()?;
+        *resp.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+        resp.headers_mut()
+            .insert(header::ALLOW, HeaderValue::from_static("POST"));
+        update_response_headers(&mut resp, req_ctx, app_ctx);
+        return Ok(resp);
+    }
+
+    let cache_key = match app_ctx.blueprint.server.response_cache.as_ref() {
+        Some(response_cache) => {
+            response_cache::key(response_cache, &mut request, &req.headers, req_ctx).await
+        }
+        None => None,
+    };
+    if let Some(key) = cache_key.as_ref() {
+        if let Some(cached) = response_cache::get(key, req_ctx).await {
+            req_ctx.set_min_max_age(i32::try_from(cached.max_age).unwrap_or(i32::MAX));
+            let response = GraphQLResponse::from(cached.response);
+            let mut resp =
+                update_cache_control_header(response, app_ctx, req_ctx.clone()).into_response()?;
+            resp.headers_mut()
+                .insert(header::AGE, HeaderValue::from(cached.age));
+            update_response_headers(&mut resp, req_ctx, app_ctx);
+            return Ok(resp);
+        }
+    }
+
+    // Subscriptions are only supported by the JIT engine and are always
+    // streamed back to the client as server-sent events.
+    if request.is_subscription() {
+        let operation_id = request.operation_id(&req.headers);
+        let mut resp = request
+            .execute_stream(&JITExecutor::new(
+                app_ctx.clone(),
+                req_ctx.clone(),
+                false,
+                operation_id,
+            ))
+            .into_response()?;
+        update_response_headers(&mut resp, req_ctx, app_ctx);
+        return Ok(resp);
+    }
+
     let mut response = if app_ctx.blueprint.server.enable_jit {
         let is_query = request.is_query();
         let operation_id = request.operation_id(&req.headers);
@@ -147,8 +277,21 @@ async fn execute_query<T: DeserializeOwned + GraphQLRequestLike>(
             ))
             .await
     } else {
+        for operation in request.requests_mut() {
+            if let Err(error) = rate_limit::check_operation(operation, app_ctx, req_ctx).await {
+                let response = async_graphql::Response::from_errors(vec![error.into()]);
+                let mut resp = GraphQLResponse::from(response).into_response()?;
+                update_response_headers(&mut resp, req_ctx, app_ctx);
+                return Ok(resp);
+            }
+        }
         request.data(req_ctx.clone()).execute(&app_ctx.schema).await
     };
+    if let (Some(key), Some(response_cache)) =
+        (cache_key, app_ctx.blueprint.server.response_cache.as_ref())
+    {
+        response_cache::set(key, response_cache, &response, req_ctx).await;
+    }
     response = update_cache_control_header(response, app_ctx, req_ctx.clone());
 
     let mut resp = response.into_response()?;
@@ -259,8 +402,13 @@ async fn handle_rest_apis(
     app_ctx: Arc<AppContext>,
     req_counter: &mut RequestCounter,
 ) -> Result<Response<Body>> {
+    let query = request.uri().query().map(str::to_owned);
     *request.uri_mut() = request.uri().path().replace(API_URL_PREFIX, "").parse()?;
-    let req_ctx = Arc::new(create_request_context(&request, app_ctx.as_ref()));
+    let req_ctx = Arc::new(
+        create_request_context(request.headers(), app_ctx.as_ref())
+            .query(query)
+            .client_ip(client_ip(&request)),
+    );
     if let Some(p_request) = app_ctx.endpoints.matches(&request) {
         let http_route = format!("{API_URL_PREFIX}{}", p_request.path.as_str());
         req_counter.set_http_route(&http_route);
@@ -272,7 +420,25 @@ async fn handle_rest_apis(
             { HTTP_ROUTE } = http_route
         );
         return async {
+            if let Err(error) = rate_limit::check_request(&req_ctx).await {
+                let response = async_graphql::Response::from_errors(vec![error.into()]);
+                let mut resp = GraphQLResponse::from(response).into_rest_response()?;
+                *resp.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+                update_response_headers(&mut resp, &req_ctx, &app_ctx);
+                return Ok(resp);
+            }
+            req_ctx.auth_ctx.verify_for_templates(&req_ctx).await;
+
             let graphql_request = p_request.into_request(request).await?;
+            if let Err(error) =
+                rate_limit::check_operation(&graphql_request.0, &app_ctx, &req_ctx).await
+            {
+                let response = async_graphql::Response::from_errors(vec![error.into()]);
+                let mut resp = GraphQLResponse::from(response).into_rest_response()?;
+                *resp.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+                update_response_headers(&mut resp, &req_ctx, &app_ctx);
+                return Ok(resp);
+            }
             let mut response = graphql_request
                 .data(req_ctx.clone())
                 .execute(&app_ctx.schema)
@@ -320,6 +486,9 @@ async fn handle_request_inner<T: DeserializeOwned + GraphQLRequestLike>(
 
             graphql_request::<T>(req, &Arc::new(app_ctx), req_counter).await
         }
+        Method::GET if req.uri().path() == graphql_endpoint => {
+            graphql_get_request(req, &app_ctx, req_counter).await
+        }
         Method::GET if req.uri().path() == health_check_endpoint => {
             let status_response = Response::builder()
                 .status(StatusCode::OK)
@@ -381,13 +550,18 @@ pub async fn handle_request<T: DeserializeOwned + GraphQLRequestLike>(
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use hyper::body::{Bytes, HttpBody};
+
     use super::*;
-    use crate::core::async_graphql_hyper::GraphQLRequest;
     use crate::core::blueprint::Blueprint;
     use crate::core::config::{Config, ConfigModule, Routes};
+    use crate::core::http::Response as UpstreamResponse;
     use crate::core::rest::EndpointSet;
     use crate::core::runtime::test::init;
     use crate::core::valid::Validator;
+    use crate::core::HttpIO;
 
     #[tokio::test]
     async fn test_health_endpoint() -> anyhow::Result<()> {
@@ -445,6 +619,130 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_graphql_get_endpoint() -> anyhow::Result<()> {
+        let sdl = tokio::fs::read_to_string(tailcall_fixtures::configs::JSONPLACEHOLDER).await?;
+        let config = Config::from_sdl(&sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let app_ctx = Arc::new(AppContext::new(
+            blueprint,
+            init(None),
+            EndpointSet::default(),
+        ));
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("http://localhost:8000/graphql?query=%7B__schema%7BqueryType%7Bname%7D%7D%7D")
+            .body(Body::empty())?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx.clone()).await?;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        let body_str = String::from_utf8(body.to_vec())?;
+        assert!(body_str.contains("queryType"));
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("http://localhost:8000/graphql?query=mutation%7Bfoo%7D")
+            .body(Body::empty())?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx.clone()).await?;
+
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "POST");
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("http://localhost:8000/graphql?query=%7Busers%7Bid%7D%7D&extensions=%7Bnot-json")
+            .body(Body::empty())?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx).await?;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(resp.into_body()).await?;
+        let body_str = String::from_utf8(body.to_vec())?;
+        assert!(body_str.contains("Unexpected GraphQL Request"));
+
+        Ok(())
+    }
+
+    /// Responds with the number of times it was polled.
+    #[derive(Default)]
+    struct PollHttp {
+        polls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for PollHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<UpstreamResponse<Bytes>> {
+            let count = self.polls.fetch_add(1, Ordering::Relaxed) + 1;
+            Ok(UpstreamResponse {
+                status: reqwest::StatusCode::OK,
+                headers: Default::default(),
+                body: Bytes::from(format!(r#"{{"count": {count}}}"#)),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polled_subscription() -> anyhow::Result<()> {
+        let sdl = r#"
+            schema @server @upstream(baseURL: "http://localhost:3000") {
+              query: Query
+              subscription: Subscription
+            }
+
+            type Query {
+              hello: String @expr(body: "world")
+            }
+
+            type Subscription {
+              counter: Counter @http(path: "/counter", pollInterval: 10)
+            }
+
+            type Counter {
+              count: Int
+            }
+        "#;
+        let config = Config::from_sdl(sdl).to_result()?;
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config))?;
+        let mut runtime = init(None);
+        runtime.http = Arc::new(PollHttp::default());
+        let app_ctx = Arc::new(AppContext::new(blueprint, runtime, EndpointSet::default()));
+
+        let query = r#"{"query": "subscription { counter { count } }"}"#;
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("http://localhost:8000/graphql")
+            .header("Content-Type", "application/json")
+            .body(Body::from(query))?;
+
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx).await?;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        // the stream doesn't end, so only the first events are read
+        let mut body = resp.into_body();
+        let mut events = String::new();
+        while events.matches("event: next").count() < 2 {
+            let chunk = body.data().await.unwrap()?;
+            events.push_str(std::str::from_utf8(&chunk)?);
+        }
+
+        assert_eq!(
+            events,
+            "event: next\ndata: {\"data\":{\"counter\":{\"count\":1}}}\n\n\
+             event: next\ndata: {\"data\":{\"counter\":{\"count\":2}}}\n\n"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_create_allowed_headers() {
         use std::collections::BTreeSet;
@@ -0,0 +1,660 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_timer::Delay;
+use futures_util::future::{select, Either};
+use http::header::{HeaderName, HeaderValue};
+use hyper::body::Bytes;
+use reqwest::{Request, StatusCode};
+
+use super::Response;
+use crate::core::blueprint::{CircuitBreaker, Resilience, Retry};
+use crate::core::ir::Error;
+use crate::core::HttpIO;
+
+static GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+
+#[derive(Debug, thiserror::Error)]
+#[error("Circuit breaker is open for {host}")]
+pub struct CircuitOpen {
+    pub host: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Closed,
+    /// Milliseconds since the epoch until which requests are rejected
+    Open {
+        until: i64,
+    },
+    /// A few requests probe the host before the circuit closes again
+    HalfOpen {
+        probes: u64,
+        successes: u64,
+        since: i64,
+    },
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Closed => write!(f, "closed"),
+            State::Open { .. } => write!(f, "open"),
+            State::HalfOpen { .. } => write!(f, "half-open"),
+        }
+    }
+}
+
+struct Circuit {
+    state: State,
+    /// Milliseconds since the epoch at which the current window started
+    window_start: i64,
+    requests: u64,
+    failures: u64,
+}
+
+impl Circuit {
+    fn new(now: i64) -> Self {
+        Self {
+            state: State::Closed,
+            window_start: now,
+            requests: 0,
+            failures: 0,
+        }
+    }
+
+    fn transition(&mut self, host: &str, state: State, now: i64) {
+        let from = self.state;
+        self.state = state;
+        self.window_start = now;
+        self.requests = 0;
+        self.failures = 0;
+
+        match state {
+            State::Open { .. } => {
+                tracing::warn!(host = %host, from = %from, to = %state, "Circuit breaker state changed")
+            }
+            _ => {
+                tracing::info!(host = %host, from = %from, to = %state, "Circuit breaker state changed")
+            }
+        }
+    }
+}
+
+/// Tracks the failures of the upstream hosts, so that the requests to a host
+/// failing too often are rejected for a while instead of piling up.
+///
+/// The state is kept in the memory of the process, per host.
+#[derive(Default)]
+pub struct CircuitBreakers {
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreakers {
+    fn acquire(&self, host: &str, breaker: &CircuitBreaker, now: i64) -> Result<(), CircuitOpen> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits
+            .entry(host.to_owned())
+            .or_insert_with(|| Circuit::new(now));
+        let open_duration = breaker.open_duration.as_millis() as i64;
+
+        match circuit.state {
+            State::Closed => Ok(()),
+            State::Open { until } if now >= until => {
+                let state = State::HalfOpen { probes: 1, successes: 0, since: now };
+                circuit.transition(host, state, now);
+                Ok(())
+            }
+            State::HalfOpen { probes, successes, since } if probes < breaker.half_open_requests => {
+                circuit.state = State::HalfOpen { probes: probes + 1, successes, since };
+                Ok(())
+            }
+            // the probes never completed, e.g. they were cancelled
+            State::HalfOpen { since, .. } if now - since >= open_duration => {
+                circuit.state = State::HalfOpen { probes: 1, successes: 0, since: now };
+                Ok(())
+            }
+            _ => Err(CircuitOpen { host: host.to_owned() }),
+        }
+    }
+
+    fn record(&self, host: &str, breaker: &CircuitBreaker, success: bool, now: i64) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let Some(circuit) = circuits.get_mut(host) else {
+            return;
+        };
+        let open = State::Open { until: now + breaker.open_duration.as_millis() as i64 };
+
+        match circuit.state {
+            State::Closed => {
+                if now - circuit.window_start >= breaker.window.as_millis() as i64 {
+                    circuit.window_start = now;
+                    circuit.requests = 0;
+                    circuit.failures = 0;
+                }
+                circuit.requests += 1;
+                circuit.failures += u64::from(!success);
+
+                if circuit.requests >= breaker.minimum_requests
+                    && circuit.failures * 100 >= breaker.failure_rate * circuit.requests
+                {
+                    circuit.transition(host, open, now);
+                }
+            }
+            State::HalfOpen { .. } if !success => circuit.transition(host, open, now),
+            State::HalfOpen { probes, successes, since } => {
+                if successes + 1 >= breaker.half_open_requests {
+                    circuit.transition(host, State::Closed, now);
+                } else {
+                    circuit.state = State::HalfOpen { probes, successes: successes + 1, since };
+                }
+            }
+            // responses to requests sent before the circuit opened
+            State::Open { .. } => {}
+        }
+    }
+}
+
+/// How a request to the upstream went
+pub(super) enum Outcome {
+    Success,
+    Status(StatusCode),
+    /// The request failed without a response, e.g. the connection was refused
+    Transport,
+}
+
+impl Outcome {
+    pub(super) fn new<Body>(result: &anyhow::Result<Response<Body>>) -> Self {
+        match result {
+            Ok(response) if response.status.is_success() => Outcome::Success,
+            Ok(response) => Outcome::Status(response.status),
+            // the native runtime turns the error statuses into errors
+            Err(error) => match error
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|error| error.status())
+            {
+                Some(status) => Outcome::Status(status),
+                None => Outcome::Transport,
+            },
+        }
+    }
+
+    pub(super) fn is_failure(&self) -> bool {
+        match self {
+            Outcome::Success => false,
+            Outcome::Status(status) => status.is_server_error(),
+            Outcome::Transport => true,
+        }
+    }
+
+    fn is_retryable(&self, retry: &Retry) -> bool {
+        match self {
+            Outcome::Success => false,
+            Outcome::Status(status) => retry.status_codes.contains(&status.as_u16()),
+            Outcome::Transport => true,
+        }
+    }
+}
+
+/// When the response of the upstream is due, and how the upstream is told
+/// about it.
+pub struct Deadline {
+    /// Milliseconds since the epoch
+    pub at: i64,
+    /// Time the upstream was given to respond, reported once it elapsed
+    pub timeout: Duration,
+    pub header: Option<DeadlineHeader>,
+}
+
+pub enum DeadlineHeader {
+    /// `grpc-timeout`, as defined by the gRPC protocol
+    Grpc,
+    /// The milliseconds left, in a custom header
+    Millis(HeaderName),
+}
+
+impl DeadlineHeader {
+    /// The custom header set on the resolver, if any
+    pub fn millis(resilience: &Resilience) -> Option<Self> {
+        resilience
+            .deadline_header
+            .as_deref()
+            .and_then(|name| HeaderName::try_from(name).ok())
+            .map(DeadlineHeader::Millis)
+    }
+}
+
+impl Deadline {
+    /// The earliest of the deadline of the request and the timeout of the
+    /// resolver, starting now.
+    pub fn new(
+        request_deadline: Option<i64>,
+        timeout: Option<Duration>,
+        header: Option<DeadlineHeader>,
+    ) -> Option<Self> {
+        let start = now();
+        let timeout = timeout.map(|timeout| start + timeout.as_millis() as i64);
+        let at = match (request_deadline, timeout) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b)?,
+        };
+        let timeout = Duration::from_millis((at - start).max(0) as u64);
+
+        Some(Self { at, timeout, header })
+    }
+
+    fn set_header(&self, request: &mut Request, now: i64) {
+        let left = (self.at - now).max(0);
+        let (name, value) = match &self.header {
+            // the value is limited to 8 digits
+            Some(DeadlineHeader::Grpc) => {
+                (GRPC_TIMEOUT.clone(), format!("{}m", left.min(99_999_999)))
+            }
+            Some(DeadlineHeader::Millis(name)) => (name.clone(), left.to_string()),
+            None => return,
+        };
+
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            request.headers_mut().insert(name, value);
+        }
+    }
+}
+
+/// Executes the request with the retry and circuit breaker policies of the
+/// resolver. Only idempotent requests are retried, since a failed attempt
+/// might have reached the upstream, and only while the deadline allows it.
+/// An attempt without a response by the deadline fails with a timeout.
+pub async fn execute(
+    http: &dyn HttpIO,
+    request: Request,
+    resilience: &Resilience,
+    breakers: &CircuitBreakers,
+    idempotent: bool,
+    deadline: Option<&Deadline>,
+) -> anyhow::Result<Response<Bytes>> {
+    let mut request = request;
+
+    if resilience.retry.is_none() && resilience.circuit_breaker.is_none() {
+        if let Some(deadline) = deadline {
+            deadline.set_header(&mut request, now());
+        }
+        return send(http, request, deadline).await;
+    }
+
+    let host = host(&request);
+    let max_attempts = match &resilience.retry {
+        Some(retry) if idempotent => retry.max_attempts,
+        _ => 1,
+    };
+    let mut attempt = 1;
+
+    loop {
+        // requests with a streamed body can't be cloned, nor retried
+        let next = if attempt < max_attempts {
+            request.try_clone()
+        } else {
+            None
+        };
+
+        if let Some(breaker) = &resilience.circuit_breaker {
+            breakers.acquire(&host, breaker, now())?;
+        }
+        if let Some(deadline) = deadline {
+            deadline.set_header(&mut request, now());
+        }
+
+        let result = send(http, request, deadline).await;
+        let outcome = Outcome::new(&result);
+
+        if let Some(breaker) = &resilience.circuit_breaker {
+            breakers.record(&host, breaker, !outcome.is_failure(), now());
+        }
+
+        match (next, &resilience.retry) {
+            (Some(next), Some(retry)) if outcome.is_retryable(retry) => {
+                let delay = retry.backoff(attempt);
+                let retry_at = now() + delay.as_millis() as i64;
+                if deadline.is_some_and(|deadline| retry_at >= deadline.at) {
+                    return result;
+                }
+
+                tracing::debug!(host = %host, attempt, ?delay, "Retrying the upstream request");
+                Delay::new(delay).await;
+                request = next;
+                attempt += 1;
+            }
+            _ => return result,
+        }
+    }
+}
+
+async fn send(
+    http: &dyn HttpIO,
+    request: Request,
+    deadline: Option<&Deadline>,
+) -> anyhow::Result<Response<Bytes>> {
+    let Some(deadline) = deadline else {
+        return http.execute(request).await;
+    };
+    let left = Duration::from_millis((deadline.at - now()).max(0) as u64);
+
+    // dropping the pending request cancels it, the timeout counts as a failure
+    // of the upstream
+    match select(pin!(http.execute(request)), Delay::new(left)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(Error::Timeout(deadline.timeout).into()),
+    }
+}
+
+/// The policies of a resolver whose requests are batched by a data loader.
+/// The circuit breakers are shared with the requests that aren't batched.
+#[derive(Clone, Default)]
+pub struct LoaderResilience {
+    pub resilience: Resilience,
+    pub circuit_breakers: Arc<CircuitBreakers>,
+}
+
+impl LoaderResilience {
+    /// Executes a request of the data loader, only queries being batched. The
+    /// data loader is shared by the requests, so the deadline is the timeout
+    /// of the resolver.
+    pub async fn execute(
+        &self,
+        http: &dyn HttpIO,
+        request: Request,
+        header: Option<DeadlineHeader>,
+    ) -> anyhow::Result<Response<Bytes>> {
+        let deadline = Deadline::new(None, self.resilience.timeout, header);
+        execute(
+            http,
+            request,
+            &self.resilience,
+            &self.circuit_breakers,
+            true,
+            deadline.as_ref(),
+        )
+        .await
+    }
+}
+
+fn host(request: &Request) -> String {
+    let url = request.url();
+    match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_owned(),
+    }
+}
+
+pub(super) fn now() -> i64 {
+    // chrono reads the clock on wasm targets as well
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use reqwest::Method;
+
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker {
+            failure_rate: 50,
+            minimum_requests: 4,
+            window: Duration::from_secs(10),
+            open_duration: Duration::from_secs(30),
+            half_open_requests: 2,
+        }
+    }
+
+    fn state(breakers: &CircuitBreakers, host: &str) -> State {
+        breakers.circuits.lock().unwrap()[host].state
+    }
+
+    #[test]
+    fn test_circuit_opens() {
+        let breakers = CircuitBreakers::default();
+        let breaker = breaker();
+
+        for success in [true, false, true] {
+            breakers.acquire("a:80", &breaker, 0).unwrap();
+            breakers.record("a:80", &breaker, success, 0);
+        }
+        assert_eq!(state(&breakers, "a:80"), State::Closed);
+
+        breakers.acquire("a:80", &breaker, 0).unwrap();
+        breakers.record("a:80", &breaker, false, 0);
+        assert_eq!(state(&breakers, "a:80"), State::Open { until: 30_000 });
+        assert!(breakers.acquire("a:80", &breaker, 1_000).is_err());
+
+        // circuits are kept per host
+        assert!(breakers.acquire("b:80", &breaker, 1_000).is_ok());
+    }
+
+    #[test]
+    fn test_window() {
+        let breakers = CircuitBreakers::default();
+        let breaker = breaker();
+
+        for now in [0, 1_000, 10_000, 11_000] {
+            breakers.acquire("a:80", &breaker, now).unwrap();
+            breakers.record("a:80", &breaker, false, now);
+        }
+
+        // the failures of the previous window aren't counted
+        assert_eq!(state(&breakers, "a:80"), State::Closed);
+    }
+
+    #[test]
+    fn test_half_open() {
+        let breakers = CircuitBreakers::default();
+        let breaker = breaker();
+
+        for _ in 0..4 {
+            breakers.acquire("a:80", &breaker, 0).unwrap();
+            breakers.record("a:80", &breaker, false, 0);
+        }
+
+        // a failed probe opens the circuit again
+        breakers.acquire("a:80", &breaker, 30_000).unwrap();
+        breakers.record("a:80", &breaker, false, 30_000);
+        assert_eq!(state(&breakers, "a:80"), State::Open { until: 60_000 });
+
+        breakers.acquire("a:80", &breaker, 60_000).unwrap();
+        breakers.acquire("a:80", &breaker, 60_000).unwrap();
+        assert!(breakers.acquire("a:80", &breaker, 60_000).is_err());
+
+        breakers.record("a:80", &breaker, true, 60_000);
+        breakers.record("a:80", &breaker, true, 60_000);
+        assert_eq!(state(&breakers, "a:80"), State::Closed);
+    }
+
+    struct TestHttp {
+        statuses: Mutex<VecDeque<StatusCode>>,
+    }
+
+    impl TestHttp {
+        fn new(statuses: &[u16]) -> Self {
+            let statuses = statuses
+                .iter()
+                .map(|status| StatusCode::from_u16(*status).unwrap());
+            Self { statuses: Mutex::new(statuses.collect()) }
+        }
+
+        fn remaining(&self) -> usize {
+            self.statuses.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for TestHttp {
+        async fn execute(&self, _request: Request) -> anyhow::Result<Response<Bytes>> {
+            let status = self.statuses.lock().unwrap().pop_front().unwrap();
+            Ok(Response { status, ..Default::default() })
+        }
+    }
+
+    fn retry() -> Resilience {
+        Resilience {
+            retry: Some(Retry {
+                max_attempts: 3,
+                backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                status_codes: vec![503],
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn request(method: Method) -> Request {
+        Request::new(method, "http://localhost:8080/users".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let http = TestHttp::new(&[503, 503, 200]);
+        let breakers = CircuitBreakers::default();
+
+        let response = execute(&http, request(Method::GET), &retry(), &breakers, true, None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(http.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_attempts() {
+        let http = TestHttp::new(&[503, 503, 503, 200]);
+        let breakers = CircuitBreakers::default();
+
+        let response = execute(&http, request(Method::GET), &retry(), &breakers, true, None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(http.remaining(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry() {
+        let breakers = CircuitBreakers::default();
+
+        // not a retryable status
+        let http = TestHttp::new(&[500, 200]);
+        let response = execute(&http, request(Method::GET), &retry(), &breakers, true, None)
+            .await
+            .unwrap();
+        assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        // not idempotent
+        let http = TestHttp::new(&[503, 200]);
+        let response = execute(
+            &http,
+            request(Method::POST),
+            &retry(),
+            &breakers,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_open() {
+        let http = TestHttp::new(&[500; 4]);
+        let breakers = CircuitBreakers::default();
+        let resilience = Resilience { circuit_breaker: Some(breaker()), ..Default::default() };
+
+        for _ in 0..4 {
+            execute(
+                &http,
+                request(Method::GET),
+                &resilience,
+                &breakers,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        let error = execute(
+            &http,
+            request(Method::GET),
+            &resilience,
+            &breakers,
+            true,
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Circuit breaker is open for localhost:8080"
+        );
+    }
+
+    struct SlowHttp;
+
+    #[async_trait::async_trait]
+    impl HttpIO for SlowHttp {
+        async fn execute(&self, _request: Request) -> anyhow::Result<Response<Bytes>> {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(Response::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deadline_elapsed() {
+        let breakers = CircuitBreakers::default();
+        let resilience = Resilience { circuit_breaker: Some(breaker()), ..Default::default() };
+        let deadline = Deadline::new(None, Some(Duration::from_millis(10)), None);
+
+        let error = execute(
+            &SlowHttp,
+            request(Method::GET),
+            &resilience,
+            &breakers,
+            true,
+            deadline.as_ref(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::Timeout(timeout)) if *timeout == Duration::from_millis(10)
+        ));
+        // the elapsed deadline is recorded as a failure of the host
+        let circuits = breakers.circuits.lock().unwrap();
+        assert_eq!(circuits["localhost:8080"].failures, 1);
+    }
+
+    #[test]
+    fn test_deadline() {
+        let deadline = Deadline::new(Some(now() + 60_000), Some(Duration::from_secs(1)), None);
+        let deadline = deadline.unwrap();
+        assert!(deadline.at <= now() + 1_000);
+        assert_eq!(deadline.timeout, Duration::from_secs(1));
+        assert!(Deadline::new(None, None, None).is_none());
+
+        let mut request = request(Method::GET);
+        let timeout = Duration::from_millis(1_500);
+        let deadline = Deadline { at: 2_500, timeout, header: Some(DeadlineHeader::Grpc) };
+        deadline.set_header(&mut request, 1_000);
+        assert_eq!(request.headers()["grpc-timeout"], "1500m");
+
+        let name = HeaderName::from_static("x-request-timeout");
+        let header = Some(DeadlineHeader::Millis(name));
+        let deadline = Deadline { at: 2_500, timeout, header };
+        deadline.set_header(&mut request, 3_000);
+        assert_eq!(request.headers()["x-request-timeout"], "0");
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_graphql::{ErrorExtensions, Value as ConstValue};
 use derive_more::From;
@@ -34,6 +35,9 @@ pub enum Error {
 
     #[from(ignore)]
     Entity(String),
+
+    #[from(ignore)]
+    Timeout(Duration),
 }
 
 impl Display for Error {
@@ -66,7 +70,9 @@ impl From<Error> for Errata {
             }
             Error::Worker(err) => Errata::new("Worker Error").description(err.to_string()),
             Error::Cache(err) => Errata::new("Cache Error").description(err.to_string()),
-            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message)
+            Error::Entity(message) => Errata::new("Entity Resolver Error").description(message),
+            Error::Timeout(timeout) => Errata::new("Timeout")
+                .description(format!("no response from the upstream within {}ms", timeout.as_millis()))
         }
     }
 }
@@ -1,12 +1,12 @@
 use std::future::Future;
-use std::ops::Deref;
 
 use async_graphql_value::ConstValue;
 use futures_util::future::join_all;
 use indexmap::IndexMap;
 
+use super::eval_cache::eval_cache;
 use super::eval_io::eval_io;
-use super::model::{Cache, CacheKey, Map, IR};
+use super::model::{Map, IR};
 use super::{Error, EvalContext, ResolverContextLike, TypedValue};
 use crate::core::json::{JsonLike, JsonLikeList, JsonObjectLike};
 use crate::core::serde_value_ext::ValueExt;
@@ -40,34 +40,16 @@ impl IR {
                         .clone())
                 }
                 IR::Dynamic(value) => Ok(value.render_value(ctx)),
-                IR::Protect(expr) => {
+                IR::Protect(requirements, expr) => {
                     ctx.request_ctx
                         .auth_ctx
-                        .validate(ctx.request_ctx)
+                        .authorize(ctx.request_ctx, requirements)
                         .await
                         .to_result()?;
                     expr.eval(ctx).await
                 }
                 IR::IO(io) => eval_io(io, ctx).await,
-                IR::Cache(Cache { max_age, io }) => {
-                    let io = io.deref();
-                    let key = io.cache_key(ctx);
-                    if let Some(key) = key {
-                        if let Some(val) = ctx.request_ctx.runtime.cache.get(&key).await? {
-                            Ok(val)
-                        } else {
-                            let val = eval_io(io, ctx).await?;
-                            ctx.request_ctx
-                                .runtime
-                                .cache
-                                .set(key, val.clone(), max_age.to_owned())
-                                .await?;
-                            Ok(val)
-                        }
-                    } else {
-                        eval_io(io, ctx).await
-                    }
-                }
+                IR::Cache(cache) => eval_cache(cache, ctx).await,
                 IR::Map(Map { input, map }) => {
                     let value = input.eval(ctx).await?;
                     if let ConstValue::String(key) = value {
@@ -152,6 +134,23 @@ impl IR {
 
                     Ok(ConstValue::object(obj))
                 }
+                IR::Invalidate(tags, expr) => {
+                    let value = expr.eval(ctx).await?;
+                    let tags = {
+                        let ctx = ctx.with_value(value.clone());
+                        tags.iter().map(|tag| tag.render(&ctx)).collect::<Vec<_>>()
+                    };
+                    // the mutation already happened, so its result is returned regardless
+                    for tag in tags.iter() {
+                        if let Err(error) = ctx.request_ctx.runtime.cache.purge(tag).await {
+                            tracing::warn!("Failed to purge the cache tag `{}`: {}", tag, error);
+                        }
+                    }
+                    Ok(value)
+                }
+                IR::Stream(_) => Err(Error::ExprEval(
+                    "Streams can only be resolved as part of a subscription".to_owned(),
+                )),
             }
         })
     }
@@ -0,0 +1,391 @@
+use std::num::NonZeroU64;
+
+use async_graphql_value::{ConstValue, Name};
+use indexmap::IndexMap;
+
+use super::eval_io::eval_io;
+use super::model::{Cache, CacheKey, CachePolicy, IoId};
+use super::{Error, EvalContext, ResolverContextLike};
+use crate::core::http::RequestContext;
+use crate::core::json::JsonLike;
+
+/// Value cached along with the time it was stored, kept once it expires when
+/// the policy allows stale values.
+struct Entry {
+    value: ConstValue,
+    /// Milliseconds since the epoch
+    stored_at: i64,
+}
+
+impl Entry {
+    fn new(value: ConstValue) -> Self {
+        Self { value, stored_at: now() }
+    }
+
+    fn from_value(value: ConstValue) -> Option<Self> {
+        let ConstValue::Object(mut entry) = value else {
+            return None;
+        };
+        let stored_at = entry.get("storedAt")?.as_i64()?;
+        let value = entry.swap_remove("value")?;
+
+        Some(Self { value, stored_at })
+    }
+
+    fn into_value(self) -> ConstValue {
+        ConstValue::Object(IndexMap::from([
+            (Name::new("value"), self.value),
+            (Name::new("storedAt"), ConstValue::from(self.stored_at)),
+        ]))
+    }
+
+    /// Whether the entry was stored less than `max_age`, extended by `extra`
+    /// milliseconds, ago.
+    fn younger_than(&self, max_age: NonZeroU64, extra: Option<NonZeroU64>) -> bool {
+        let age = u64::try_from(now() - self.stored_at).unwrap_or_default();
+        age < max_age
+            .get()
+            .saturating_add(extra.map_or(0, NonZeroU64::get))
+    }
+}
+
+pub async fn eval_cache<Ctx>(
+    cache: &Cache,
+    ctx: &mut EvalContext<'_, Ctx>,
+) -> Result<ConstValue, Error>
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let Cache { policy, io } = cache;
+    let key = io.cache_key(ctx);
+    let tags = policy
+        .tags
+        .iter()
+        .map(|tag| tag.render(&*ctx))
+        .collect::<Vec<_>>();
+    ctx.request_ctx.set_cache_max_age(policy.max_age);
+    ctx.request_ctx.add_cache_tags(&tags);
+
+    let Some(key) = key else {
+        return eval_io(io, ctx).await;
+    };
+
+    if !policy.allows_stale() {
+        return if let Some(val) = get(&key, ctx.request_ctx).await {
+            Ok(val)
+        } else {
+            let val = eval_io(io, ctx).await?;
+            set(policy, key, val.clone(), &tags, ctx.request_ctx).await;
+            Ok(val)
+        };
+    }
+
+    let entry = get(&key, ctx.request_ctx).await.and_then(Entry::from_value);
+
+    match entry {
+        Some(entry) if entry.younger_than(policy.max_age, None) => Ok(entry.value),
+        Some(entry) if entry.younger_than(policy.max_age, policy.stale_while_revalidate) => {
+            revalidate(cache, key, tags, ctx);
+            Ok(entry.value)
+        }
+        entry => match eval_io(io, ctx).await {
+            Ok(val) => {
+                set(policy, key, val.clone(), &tags, ctx.request_ctx).await;
+                Ok(val)
+            }
+            Err(error) => match entry {
+                Some(entry) if entry.younger_than(policy.max_age, policy.stale_if_error) => {
+                    tracing::warn!("Serving a stale cached value: {}", error);
+                    Ok(entry.value)
+                }
+                _ => Err(error),
+            },
+        },
+    }
+}
+
+/// Reads the cached value, the errors of the cache being treated as misses
+/// so that the value is resolved instead.
+async fn get(key: &IoId, req_ctx: &RequestContext) -> Option<ConstValue> {
+    match req_ctx.runtime.cache.get(key).await {
+        Ok(value) => value,
+        Err(error) => {
+            tracing::warn!("Failed to read a cached value: {}", error);
+            None
+        }
+    }
+}
+
+/// Caches the value, along with the time it's stored at when the policy
+/// allows stale values, so that it's kept once `max_age` expires. The value
+/// is resolved already, so failing to cache it isn't an error.
+async fn set(
+    policy: &CachePolicy,
+    key: IoId,
+    value: ConstValue,
+    tags: &[String],
+    req_ctx: &RequestContext,
+) {
+    let cache = &req_ctx.runtime.cache;
+    let cached = if policy.allows_stale() {
+        let stale = policy
+            .stale_while_revalidate
+            .max(policy.stale_if_error)
+            .map_or(0, NonZeroU64::get);
+        let ttl = policy.max_age.saturating_add(stale);
+        cache
+            .set(key.clone(), Entry::new(value).into_value(), ttl)
+            .await
+    } else {
+        cache.set(key.clone(), value, policy.max_age).await
+    };
+    let cached = match cached {
+        Ok(()) => cache.tag(&key, tags).await,
+        Err(error) => Err(error),
+    };
+
+    if let Err(error) = cached {
+        tracing::warn!("Failed to cache a value: {}", error);
+    }
+}
+
+/// Refreshes a stale value in the background, once for all the requests that
+/// are served the stale value meanwhile.
+fn revalidate<Ctx>(cache: &Cache, key: IoId, tags: Vec<String>, ctx: &EvalContext<'_, Ctx>)
+where
+    Ctx: ResolverContextLike + Sync,
+{
+    let req_ctx = ctx.request_ctx.detach();
+    let graphql_ctx = ctx.detach();
+    let cache = cache.clone();
+
+    let task = async move {
+        let ctx = &mut EvalContext::new(&req_ctx, &graphql_ctx);
+        let (req_ctx, cache, key, tags) = (&req_ctx, &cache, &key, &tags);
+        let result = req_ctx
+            .revalidate_handler
+            .dedupe(key, move || async move {
+                let val = eval_io(&cache.io, ctx).await?;
+                set(&cache.policy, key.clone(), val.clone(), tags, req_ctx).await;
+                Ok(val)
+            })
+            .await;
+
+        if let Err(error) = result {
+            tracing::warn!("Failed to refresh a stale cached value: {}", error);
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(task);
+    #[cfg(target_arch = "wasm32")]
+    async_std::task::spawn_local(task);
+}
+
+/// Milliseconds since the epoch, chrono reads the clock on wasm targets as
+/// well.
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use hyper::body::Bytes;
+    use hyper::{Body, Request};
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::app_context::AppContext;
+    use crate::core::async_graphql_hyper::GraphQLRequest;
+    use crate::core::blueprint::Blueprint;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::http::{handle_request, Response as UpstreamResponse};
+    use crate::core::rest::EndpointSet;
+    use crate::core::runtime::test::init;
+    use crate::core::valid::Validator;
+    use crate::core::{cache, HttpIO};
+
+    /// Responds with the number of times it was called, or fails once it's
+    /// down.
+    #[derive(Default)]
+    struct CounterHttp {
+        calls: AtomicUsize,
+        down: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpIO for CounterHttp {
+        async fn execute(&self, _: reqwest::Request) -> anyhow::Result<UpstreamResponse<Bytes>> {
+            let count = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.down.load(Ordering::SeqCst) {
+                anyhow::bail!("Upstream is down");
+            }
+            Ok(UpstreamResponse {
+                status: reqwest::StatusCode::OK,
+                headers: Default::default(),
+                body: Bytes::from(json!({ "count": count }).to_string()),
+            })
+        }
+    }
+
+    /// Cache whose server is unreachable.
+    struct DownCache;
+
+    #[async_trait::async_trait]
+    impl crate::core::Cache for DownCache {
+        type Key = IoId;
+        type Value = ConstValue;
+
+        async fn set<'a>(&'a self, _: IoId, _: ConstValue, _: NonZeroU64) -> cache::Result<()> {
+            Err(cache::Error::Redis("Connection refused".to_string()))
+        }
+
+        async fn get<'a>(&'a self, _: &'a IoId) -> cache::Result<Option<ConstValue>> {
+            Err(cache::Error::Redis("Connection refused".to_string()))
+        }
+
+        async fn tag<'a>(&'a self, _: &'a IoId, _: &'a [String]) -> cache::Result<()> {
+            Err(cache::Error::Redis("Connection refused".to_string()))
+        }
+
+        async fn purge<'a>(&'a self, _: &'a str) -> cache::Result<()> {
+            Err(cache::Error::Redis("Connection refused".to_string()))
+        }
+
+        fn hit_rate(&self) -> Option<f64> {
+            None
+        }
+
+        fn stats(&self) -> Option<cache::Stats> {
+            None
+        }
+    }
+
+    fn app_ctx(cache: &str, http: Arc<CounterHttp>) -> Arc<AppContext> {
+        let sdl = format!(
+            r#"
+            schema @upstream(baseURL: "http://localhost:3000") {{
+              query: Query
+            }}
+
+            type Query {{
+              counter: Counter @http(path: "/counter") @cache({cache})
+            }}
+
+            type Counter {{
+              count: Int
+            }}
+            "#
+        );
+        let config = Config::from_sdl(&sdl).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+        let mut runtime = init(None);
+        runtime.http = http;
+
+        Arc::new(AppContext::new(blueprint, runtime, EndpointSet::default()))
+    }
+
+    async fn count(app_ctx: &Arc<AppContext>) -> serde_json::Value {
+        let req = Request::builder()
+            .method(hyper::Method::POST)
+            .uri("http://localhost:8000/graphql")
+            .body(Body::from(
+                json!({ "query": "{ counter { count } }" }).to_string(),
+            ))
+            .unwrap();
+        let resp = handle_request::<GraphQLRequest>(req, app_ctx.clone())
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn data(count: usize) -> serde_json::Value {
+        json!({ "data": { "counter": { "count": count } } })
+    }
+
+    #[tokio::test]
+    async fn test_cache_down() {
+        for cache in ["maxAge: 60000", "maxAge: 60000, staleIfError: 60000"] {
+            let http = Arc::new(CounterHttp::default());
+            let app_ctx = app_ctx(cache, http.clone());
+            let mut app_ctx = Arc::into_inner(app_ctx).unwrap();
+            app_ctx.runtime.cache = Arc::new(DownCache);
+            let app_ctx = Arc::new(app_ctx);
+
+            // the values are resolved every time instead
+            assert_eq!(count(&app_ctx).await, data(1));
+            assert_eq!(count(&app_ctx).await, data(2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let http = Arc::new(CounterHttp::default());
+        let app_ctx = app_ctx("maxAge: 100, staleWhileRevalidate: 60000", http.clone());
+
+        assert_eq!(count(&app_ctx).await, data(1));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        // the stale value is served while it's refreshed in the background
+        assert_eq!(count(&app_ctx).await, data(1));
+
+        let mut attempts = 0;
+        while count(&app_ctx).await != data(2) {
+            attempts += 1;
+            assert!(attempts < 100, "the stale value wasn't refreshed");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // the refreshed value is served from the cache afterwards
+        assert_eq!(count(&app_ctx).await, data(2));
+        assert_eq!(http.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_if_error() {
+        let http = Arc::new(CounterHttp::default());
+        let app_ctx = app_ctx("maxAge: 100, staleIfError: 60000", http.clone());
+
+        assert_eq!(count(&app_ctx).await, data(1));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        http.down.store(true, Ordering::SeqCst);
+
+        // the upstream is called again, but its error is replaced by the stale value
+        assert_eq!(count(&app_ctx).await, data(1));
+        assert_eq!(http.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_if_error_expired() {
+        let http = Arc::new(CounterHttp::default());
+        let app_ctx = app_ctx("maxAge: 100, staleIfError: 1", http.clone());
+
+        assert_eq!(count(&app_ctx).await, data(1));
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        http.down.store(true, Ordering::SeqCst);
+
+        let response = count(&app_ctx).await;
+        assert_eq!(response["data"]["counter"], serde_json::Value::Null);
+        assert!(response["errors"]
+            .as_array()
+            .is_some_and(|errors| !errors.is_empty()));
+    }
+
+    #[test]
+    fn test_entry_age() {
+        let max_age = NonZeroU64::new(1000).unwrap();
+        let entry = Entry { value: ConstValue::from(1), stored_at: now() - 1500 };
+        let entry = Entry::from_value(entry.into_value()).unwrap();
+
+        assert_eq!(entry.value, ConstValue::from(1));
+        assert!(!entry.younger_than(max_age, None));
+        assert!(!entry.younger_than(max_age, NonZeroU64::new(100)));
+        assert!(entry.younger_than(max_age, NonZeroU64::new(1000)));
+    }
+}
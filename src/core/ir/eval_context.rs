@@ -1,11 +1,15 @@
 use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use async_graphql::{ServerError, Value};
 use http::header::HeaderMap;
 
-use super::{GraphQLOperationContext, RelatedFields, ResolverContextLike, SelectionField};
+use super::{
+    DetachedResolverContext, GraphQLOperationContext, RelatedFields, ResolverContextLike,
+    SelectionField,
+};
 use crate::core::document::print_directives;
 use crate::core::http::RequestContext;
 
@@ -79,6 +83,17 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
         }
     }
 
+    pub fn path_claim<T: AsRef<str>>(&self, path: &[T]) -> Option<Value> {
+        self.request_ctx.read_claim(path);
+        let claim = self.request_ctx.auth_ctx.claim(path)?;
+
+        Value::from_json(claim).ok()
+    }
+
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.request_ctx.client_ip
+    }
+
     pub fn headers(&self) -> &HeaderMap {
         &self.request_ctx.allowed_headers
     }
@@ -106,6 +121,21 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
     pub fn add_error(&self, error: ServerError) {
         self.graphql_ctx.add_error(error)
     }
+
+    /// Copies the value, arguments and selection the context resolves with.
+    pub fn detach(&self) -> DetachedResolverContext {
+        let args = match self.path_arg::<&str>(&[]).map(Cow::into_owned) {
+            Some(Value::Object(args)) => Some(args),
+            _ => None,
+        };
+
+        DetachedResolverContext {
+            value: self.path_value::<&str>(&[]).map(Cow::into_owned),
+            args,
+            field: self.graphql_ctx.field(),
+            is_query: self.is_query(),
+        }
+    }
 }
 
 impl<'a, Ctx: ResolverContextLike> GraphQLOperationContext for EvalContext<'a, Ctx> {
@@ -1,16 +1,20 @@
 use std::sync::Arc;
 
 use async_graphql::from_value;
+use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::Request;
 
 use super::model::DataLoaderId;
 use super::{EvalContext, ResolverContextLike};
+use crate::core::blueprint::Resilience;
 use crate::core::data_loader::{DataLoader, Loader};
 use crate::core::grpc::protobuf::ProtobufOperation;
-use crate::core::grpc::request::execute_grpc_request;
+use crate::core::grpc::request::to_grpc_response;
 use crate::core::grpc::request_template::RenderedRequestTemplate;
+use crate::core::http::resilience::{self, Deadline, DeadlineHeader};
 use crate::core::http::{
-    cache_policy, DataLoaderRequest, HttpDataLoader, HttpFilter, RequestTemplate, Response,
+    cache_policy, load_balancer, DataLoaderRequest, HttpDataLoader, HttpFilter, RequestTemplate,
+    Response,
 };
 use crate::core::ir::Error;
 use crate::core::json::JsonLike;
@@ -26,6 +30,8 @@ pub struct EvalHttp<'a, 'ctx, Context: ResolverContextLike + Sync> {
     evaluation_ctx: &'ctx EvalContext<'a, Context>,
     data_loader: Option<&'a DataLoader<DataLoaderRequest, HttpDataLoader>>,
     request_template: &'a http::RequestTemplate,
+    resilience: &'a Resilience,
+    upstream: Option<&'a str>,
 }
 
 impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context> {
@@ -33,6 +39,8 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
         evaluation_ctx: &'ctx EvalContext<'a, Context>,
         request_template: &'a RequestTemplate,
         id: &Option<DataLoaderId>,
+        resilience: &'a Resilience,
+        upstream: Option<&'a str>,
     ) -> Self {
         let data_loader = if evaluation_ctx.request_ctx.is_batching_enabled() {
             id.and_then(|id| {
@@ -45,21 +53,30 @@ impl<'a, 'ctx, Context: ResolverContextLike + Sync> EvalHttp<'a, 'ctx, Context>
             None
         };
 
-        Self { evaluation_ctx, data_loader, request_template }
+        Self {
+            evaluation_ctx,
+            data_loader,
+            request_template,
+            resilience,
+            upstream,
+        }
     }
 
     pub fn init_request(&self) -> Result<Request, Error> {
-        Ok(self.request_template.to_request(self.evaluation_ctx)?)
+        let mut request = self.request_template.to_request(self.evaluation_ctx)?;
+        set_hash_key(self.evaluation_ctx, request.headers_mut(), self.resilience);
+        Ok(request)
     }
 
     pub async fn execute(&self, req: Request) -> Result<Response<async_graphql::Value>, Error> {
         let ctx = &self.evaluation_ctx;
         let is_get = req.method() == reqwest::Method::GET;
+        let is_idempotent = req.method().is_idempotent();
         let dl = &self.data_loader;
         let response = if is_get && dl.is_some() {
             execute_request_with_dl(ctx, req, self.data_loader).await?
         } else {
-            execute_raw_request(ctx, req).await?
+            execute_raw_request(ctx, req, self.upstream, self.resilience, is_idempotent).await?
         };
 
         if ctx.request_ctx.server.get_enable_http_validation() {
@@ -139,6 +156,18 @@ pub async fn execute_request_with_dl<
         .unwrap_or_default())
 }
 
+/// Tells the load balancer of the upstream which key to hash the request by.
+pub fn set_hash_key<Ctx: ResolverContextLike>(
+    ctx: &EvalContext<'_, Ctx>,
+    headers: &mut HeaderMap,
+    resilience: &Resilience,
+) {
+    let key = resilience.hash_key.as_ref().map(|key| key.render(ctx));
+    if let Some(value) = key.and_then(|key| HeaderValue::from_str(&key).ok()) {
+        headers.insert(load_balancer::HASH_KEY.clone(), value);
+    }
+}
+
 pub fn set_headers<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     res: &Response<async_graphql::Value>,
@@ -175,18 +204,30 @@ fn set_cookie_headers<Ctx: ResolverContextLike>(
     }
 }
 
+/// Executes the request, retrying it if it's idempotent and the resolver has
+/// a retry policy.
 pub async fn execute_raw_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: Request,
+    upstream: Option<&str>,
+    resilience: &Resilience,
+    is_idempotent: bool,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    let response = ctx
-        .request_ctx
-        .runtime
-        .http
-        .execute(req)
-        .await
-        .map_err(Error::from)?
-        .to_json()?;
+    let request_ctx = &ctx.request_ctx;
+    let header = DeadlineHeader::millis(resilience);
+    let deadline = Deadline::new(request_ctx.deadline, resilience.timeout, header);
+
+    let response = resilience::execute(
+        request_ctx.runtime.http_of(upstream).as_ref(),
+        req,
+        resilience,
+        &request_ctx.circuit_breakers,
+        is_idempotent,
+        deadline.as_ref(),
+    )
+    .await
+    .map_err(Error::from)?
+    .to_json()?;
 
     Ok(response)
 }
@@ -195,10 +236,29 @@ pub async fn execute_raw_grpc_request<Ctx: ResolverContextLike>(
     ctx: &EvalContext<'_, Ctx>,
     req: Request,
     operation: &ProtobufOperation,
+    upstream: Option<&str>,
+    resilience: &Resilience,
+    is_idempotent: bool,
 ) -> Result<Response<async_graphql::Value>, Error> {
-    execute_grpc_request(&ctx.request_ctx.runtime, operation, req)
-        .await
-        .map_err(Error::from)
+    let request_ctx = &ctx.request_ctx;
+    let deadline = Deadline::new(
+        request_ctx.deadline,
+        resilience.timeout,
+        Some(DeadlineHeader::Grpc),
+    );
+
+    let response = resilience::execute(
+        request_ctx.runtime.http2_only_of(upstream).as_ref(),
+        req,
+        resilience,
+        &request_ctx.circuit_breakers,
+        is_idempotent,
+        deadline.as_ref(),
+    )
+    .await
+    .map_err(Error::from)?;
+
+    to_grpc_response(response, operation).map_err(Error::from)
 }
 
 pub async fn execute_grpc_request_with_dl<
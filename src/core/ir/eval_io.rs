@@ -2,7 +2,7 @@ use async_graphql_value::ConstValue;
 
 use super::eval_http::{
     execute_grpc_request_with_dl, execute_raw_grpc_request, execute_raw_request,
-    execute_request_with_dl, parse_graphql_response, set_headers, EvalHttp,
+    execute_request_with_dl, parse_graphql_response, set_hash_key, set_headers, EvalHttp,
 };
 use super::model::{CacheKey, IO};
 use super::{EvalContext, ResolverContextLike};
@@ -45,9 +45,10 @@ where
     Ctx: ResolverContextLike + Sync,
 {
     match io {
-        IO::Http { req_template, dl_id, http_filter, .. } => {
+        IO::Http { req_template, dl_id, http_filter, resilience, upstream, .. } => {
             let worker = &ctx.request_ctx.runtime.cmd_worker;
-            let eval_http = EvalHttp::new(ctx, req_template, dl_id);
+            let eval_http =
+                EvalHttp::new(ctx, req_template, dl_id, resilience, upstream.as_deref());
             let request = eval_http.init_request()?;
             let response = match (&worker, http_filter) {
                 (Some(worker), Some(http_filter)) => {
@@ -60,35 +61,38 @@ where
 
             Ok(response.body)
         }
-        IO::GraphQL { req_template, field_name, dl_id, .. } => {
-            let req = req_template.to_request(ctx)?;
+        IO::GraphQL { req_template, field_name, dl_id, resilience, upstream, .. } => {
+            let mut req = req_template.to_request(ctx)?;
+            set_hash_key(ctx, req.headers_mut(), resilience);
+            let is_query = matches!(req_template.operation_type, GraphQLOperationType::Query);
 
-            let res = if ctx.request_ctx.upstream.batch.is_some()
-                && matches!(req_template.operation_type, GraphQLOperationType::Query)
-            {
+            let res = if ctx.request_ctx.upstream.batch.is_some() && is_query {
                 let data_loader: Option<&DataLoader<DataLoaderRequest, GraphqlDataLoader>> =
                     dl_id.and_then(|dl| ctx.request_ctx.gql_data_loaders.get(dl.as_usize()));
                 execute_request_with_dl(ctx, req, data_loader).await?
             } else {
-                execute_raw_request(ctx, req).await?
+                execute_raw_request(ctx, req, upstream.as_deref(), resilience, is_query).await?
             };
 
             set_headers(ctx, &res);
             parse_graphql_response(ctx, res, field_name)
         }
-        IO::Grpc { req_template, dl_id, .. } => {
-            let rendered = req_template.render(ctx)?;
+        IO::Grpc { req_template, dl_id, resilience, upstream, .. } => {
+            let mut rendered = req_template.render(ctx)?;
+            set_hash_key(ctx, &mut rendered.headers, resilience);
+            // TODO: share check for operation_type for resolvers
+            let is_query = matches!(req_template.operation_type, GraphQLOperationType::Query);
 
-            let res = if ctx.request_ctx.upstream.batch.is_some() &&
-                    // TODO: share check for operation_type for resolvers
-                    matches!(req_template.operation_type, GraphQLOperationType::Query)
-            {
+            let res = if ctx.request_ctx.upstream.batch.is_some() && is_query {
                 let data_loader: Option<&DataLoader<grpc::DataLoaderRequest, GrpcDataLoader>> =
                     dl_id.and_then(|index| ctx.request_ctx.grpc_data_loaders.get(index.as_usize()));
                 execute_grpc_request_with_dl(ctx, rendered, data_loader).await?
             } else {
                 let req = rendered.to_request()?;
-                execute_raw_grpc_request(ctx, req, &req_template.operation).await?
+                let operation = &req_template.operation;
+                let upstream = upstream.as_deref();
+                execute_raw_grpc_request(ctx, req, operation, upstream, resilience, is_query)
+                    .await?
             };
 
             set_headers(ctx, &res);
@@ -0,0 +1,105 @@
+use async_graphql_value::ConstValue;
+use futures_timer::Delay;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use reqwest::header::{HeaderValue, ACCEPT};
+
+use super::model::{Stream, StreamSource, IR};
+use super::{Error, EvalContext, ResolverContextLike};
+use crate::core::http::EventStreamDecoder;
+use crate::core::HttpIO;
+
+pub type EventStream = BoxStream<'static, Result<ConstValue, Error>>;
+
+impl IR {
+    /// Opens the upstream stream that feeds a subscription field. Only
+    /// [IR::Stream], optionally guarded by [IR::Protect], can be subscribed to.
+    pub async fn subscribe<Ctx>(&self, ctx: &EvalContext<'_, Ctx>) -> Result<EventStream, Error>
+    where
+        Ctx: ResolverContextLike + Sync,
+    {
+        let mut ir = self;
+        loop {
+            match ir {
+                IR::Protect(requirements, expr) => {
+                    ctx.request_ctx
+                        .auth_ctx
+                        .authorize(ctx.request_ctx, requirements)
+                        .await
+                        .to_result()?;
+                    ir = expr.as_ref();
+                }
+                IR::Stream(stream) => return stream.subscribe(ctx).await,
+                _ => {
+                    return Err(Error::ExprEval(
+                        "Subscription fields must be resolved by a stream".to_owned(),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Stream {
+    async fn subscribe<Ctx>(&self, ctx: &EvalContext<'_, Ctx>) -> Result<EventStream, Error>
+    where
+        Ctx: ResolverContextLike + Sync,
+    {
+        let mut request = self.req_template.to_request(ctx)?;
+        let http = ctx
+            .request_ctx
+            .runtime
+            .http_of(self.upstream.as_deref())
+            .clone();
+
+        match self.source {
+            StreamSource::ServerSentEvents => {
+                request
+                    .headers_mut()
+                    .insert(ACCEPT, HeaderValue::from_static("text/event-stream"));
+                let response = http.execute_stream(request).await?;
+                let mut decoder = EventStreamDecoder::default();
+
+                Ok(response
+                    .body
+                    .flat_map(move |chunk| {
+                        let events = match chunk {
+                            Ok(chunk) => decoder
+                                .decode(&chunk)
+                                .into_iter()
+                                .map(|data| {
+                                    serde_json::from_str::<ConstValue>(&data)
+                                        .map_err(|err| Error::Deserialize(err.to_string()))
+                                })
+                                .collect(),
+                            Err(err) => vec![Err(Error::from(err))],
+                        };
+                        stream::iter(events)
+                    })
+                    .boxed())
+            }
+            StreamSource::Poll(interval) => {
+                let state = (http, request, true);
+                Ok(
+                    stream::unfold(state, move |(http, request, first)| async move {
+                        if !first {
+                            Delay::new(interval).await;
+                        }
+                        let event = poll(http.as_ref(), &request).await;
+                        Some((event, (http, request, false)))
+                    })
+                    .boxed(),
+                )
+            }
+        }
+    }
+}
+
+async fn poll(http: &dyn HttpIO, request: &reqwest::Request) -> Result<ConstValue, Error> {
+    let request = request
+        .try_clone()
+        .ok_or(Error::IO("Unable to repeat a streaming request".to_owned()))?;
+    let response = http.execute(request).await?;
+
+    Ok(response.to_json::<ConstValue>()?.body)
+}
@@ -1,16 +1,18 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::num::NonZeroU64;
+use std::time::Duration;
 
 use async_graphql::Value;
 use strum_macros::Display;
 
 use super::discriminator::Discriminator;
 use super::{EvalContext, ResolverContextLike};
-use crate::core::blueprint::DynamicValue;
+use crate::core::blueprint::{AuthRequirement, DynamicValue, Resilience};
 use crate::core::config::group_by::GroupBy;
 use crate::core::graphql::{self};
 use crate::core::http::HttpFilter;
+use crate::core::mustache::Mustache;
 use crate::core::{grpc, http};
 
 #[derive(Clone, Debug, Display)]
@@ -22,7 +24,9 @@ pub enum IR {
     // TODO: Path can be implement using Pipe
     Path(Box<IR>, Vec<String>),
     ContextPath(Vec<String>),
-    Protect(Box<IR>),
+    /// Resolves the inner IR only if the request satisfies every one of the
+    /// requirements.
+    Protect(Vec<AuthRequirement>, Box<IR>),
     Map(Map),
     Pipe(Box<IR>, Box<IR>),
     Discriminate(Discriminator, Box<IR>),
@@ -30,6 +34,11 @@ pub enum IR {
     Entity(HashMap<String, IR>),
     /// Apollo Federation _service resolver
     Service(String),
+    /// Source of events for a subscription field
+    Stream(Stream),
+    /// Purges the cached values with the tags, rendered with the result of
+    /// the inner IR, once it's resolved.
+    Invalidate(Vec<Mustache>, Box<IR>),
 }
 
 #[derive(Clone, Debug)]
@@ -48,6 +57,8 @@ pub enum IO {
         http_filter: Option<HttpFilter>,
         is_list: bool,
         dedupe: bool,
+        resilience: Resilience,
+        upstream: Option<String>,
     },
     GraphQL {
         req_template: graphql::RequestTemplate,
@@ -55,12 +66,16 @@ pub enum IO {
         batch: bool,
         dl_id: Option<DataLoaderId>,
         dedupe: bool,
+        resilience: Resilience,
+        upstream: Option<String>,
     },
     Grpc {
         req_template: grpc::RequestTemplate,
         group_by: Option<GroupBy>,
         dl_id: Option<DataLoaderId>,
         dedupe: bool,
+        resilience: Resilience,
+        upstream: Option<String>,
     },
     Js {
         name: String,
@@ -76,6 +91,61 @@ impl IO {
             IO::Js { .. } => false,
         }
     }
+
+    /// Whether the templates of the request read the claims of the verified
+    /// identity, with `{{.auth.claims}}`.
+    pub fn reads_claims(&self) -> bool {
+        match self {
+            IO::Http { req_template, .. } => http_reads_claims(req_template),
+            IO::GraphQL { req_template, .. } => req_template
+                .operation_arguments
+                .iter()
+                .flatten()
+                .map(|(_, value)| value)
+                .chain(req_template.headers.iter().map(|(_, value)| value))
+                .any(|value| value.expression_starts_with("auth")),
+            IO::Grpc { req_template, .. } => std::iter::once(&req_template.url)
+                .chain(req_template.headers.iter().map(|(_, value)| value))
+                .chain(req_template.body.iter().flat_map(|body| &body.mustache))
+                .any(|value| value.expression_starts_with("auth")),
+            IO::Js { .. } => false,
+        }
+    }
+}
+
+fn http_reads_claims(req_template: &http::RequestTemplate) -> bool {
+    std::iter::once(&req_template.root_url)
+        .chain(req_template.query.iter().map(|query| &query.value))
+        .chain(req_template.headers.iter().map(|(_, value)| value))
+        .chain(req_template.body_path.iter())
+        .any(|value| value.expression_starts_with("auth"))
+}
+
+fn dynamic_reads_claims(value: &DynamicValue<Value>) -> bool {
+    match value {
+        DynamicValue::Value(_) => false,
+        DynamicValue::Mustache(value) => value.expression_starts_with("auth"),
+        DynamicValue::Object(values) => values.values().any(dynamic_reads_claims),
+        DynamicValue::Array(values) => values.iter().any(dynamic_reads_claims),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Stream {
+    pub req_template: http::RequestTemplate,
+    pub source: StreamSource,
+    pub upstream: Option<String>,
+}
+
+/// Describes how the events of a [Stream] are received from the upstream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamSource {
+    /// The upstream responds with `text/event-stream` and every `data` frame
+    /// is an event.
+    ServerSentEvents,
+    /// The upstream is called again after every interval and every response
+    /// is an event.
+    Poll(Duration),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -110,18 +180,41 @@ pub trait CacheKey<Ctx> {
 
 #[derive(Clone, Debug)]
 pub struct Cache {
-    pub max_age: NonZeroU64,
+    pub policy: CachePolicy,
     pub io: Box<IO>,
 }
 
+#[derive(Clone, Debug)]
+pub struct CachePolicy {
+    pub max_age: NonZeroU64,
+    /// Milliseconds after `max_age` during which the expired value is served
+    /// while it's refreshed in the background
+    pub stale_while_revalidate: Option<NonZeroU64>,
+    /// Milliseconds after `max_age` during which the expired value is served
+    /// when refreshing it fails
+    pub stale_if_error: Option<NonZeroU64>,
+    /// Tags of the cached values, to purge them with `@invalidate`
+    pub tags: Vec<Mustache>,
+}
+
+impl CachePolicy {
+    /// Whether the values are kept once they expire.
+    pub fn allows_stale(&self) -> bool {
+        self.stale_while_revalidate.is_some() || self.stale_if_error.is_some()
+    }
+}
+
 impl Cache {
     ///
     /// Wraps an expression with the cache primitive.
     /// Performance DFS on the cache on the expression and identifies all the IO
     /// nodes. Then wraps each IO node with the cache primitive.
-    pub fn wrap(max_age: NonZeroU64, expr: IR) -> IR {
+    pub fn wrap(policy: CachePolicy, expr: IR) -> IR {
         expr.modify(&mut move |expr| match expr {
-            IR::IO(io) => Some(IR::Cache(Cache { max_age, io: Box::new(io.to_owned()) })),
+            IR::IO(io) => Some(IR::Cache(Cache {
+                policy: policy.clone(),
+                io: Box::new(io.to_owned()),
+            })),
             _ => None,
         })
     }
@@ -132,6 +225,17 @@ impl IR {
         IR::Pipe(Box::new(self), Box::new(next))
     }
 
+    /// Whether the templates of the expression, leaving out the nested ones,
+    /// read the claims of the verified identity.
+    pub fn reads_claims(&self) -> bool {
+        match self {
+            IR::Dynamic(value) => dynamic_reads_claims(value),
+            IR::IO(io) => io.reads_claims(),
+            IR::Stream(stream) => http_reads_claims(&stream.req_template),
+            _ => false,
+        }
+    }
+
     pub fn modify<F: FnMut(&IR) -> Option<IR>>(self, modifier: &mut F) -> IR {
         self.modify_inner(modifier)
     }
@@ -153,15 +257,17 @@ impl IR {
                     IR::ContextPath(path) => IR::ContextPath(path),
                     IR::Dynamic(_) => expr,
                     IR::IO(_) => expr,
-                    IR::Cache(Cache { io, max_age }) => {
+                    IR::Cache(Cache { io, policy }) => {
                         let expr = *IR::IO(*io).modify_box(modifier);
                         match expr {
-                            IR::IO(io) => IR::Cache(Cache { io: Box::new(io), max_age }),
+                            IR::IO(io) => IR::Cache(Cache { io: Box::new(io), policy }),
                             expr => expr,
                         }
                     }
                     IR::Path(expr, path) => IR::Path(expr.modify_box(modifier), path),
-                    IR::Protect(expr) => IR::Protect(expr.modify_box(modifier)),
+                    IR::Protect(requirements, expr) => {
+                        IR::Protect(requirements, expr.modify_box(modifier))
+                    }
                     IR::Map(Map { input, map }) => {
                         IR::Map(Map { input: input.modify_box(modifier), map })
                     }
@@ -174,6 +280,8 @@ impl IR {
                             .collect(),
                     ),
                     IR::Service(sdl) => IR::Service(sdl),
+                    IR::Invalidate(tags, expr) => IR::Invalidate(tags, expr.modify_box(modifier)),
+                    IR::Stream(stream) => IR::Stream(stream),
                 }
             }
         }
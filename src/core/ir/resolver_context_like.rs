@@ -38,6 +38,36 @@ impl ResolverContextLike for EmptyResolverContext {
     fn add_error(&self, _: ServerError) {}
 }
 
+/// Copy of the value, arguments and selection of a field, to resolve it apart
+/// from the request.
+#[derive(Clone)]
+pub struct DetachedResolverContext {
+    pub(super) value: Option<Value>,
+    pub(super) args: Option<IndexMap<Name, Value>>,
+    pub(super) field: Option<SelectionField>,
+    pub(super) is_query: bool,
+}
+
+impl ResolverContextLike for DetachedResolverContext {
+    fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    fn args(&self) -> Option<&IndexMap<Name, Value>> {
+        self.args.as_ref()
+    }
+
+    fn field(&self) -> Option<SelectionField> {
+        self.field.clone()
+    }
+
+    fn is_query(&self) -> bool {
+        self.is_query
+    }
+
+    fn add_error(&self, _: ServerError) {}
+}
+
 #[derive(Clone)]
 pub struct ResolverContext<'a> {
     inner: Arc<async_graphql::dynamic::ResolverContext<'a>>,
@@ -71,7 +101,7 @@ impl<'a> ResolverContextLike for ResolverContext<'a> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SelectionField {
     name: String,
     args: Vec<(String, String)>,
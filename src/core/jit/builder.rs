@@ -11,8 +11,8 @@ use async_graphql_value::{ConstValue, Value};
 
 use super::input_resolver::InputResolver;
 use super::model::{Directive as JitDirective, *};
-use super::BuildError;
-use crate::core::blueprint::{Blueprint, Index, QueryField};
+use super::{BuildError, LimitError};
+use crate::core::blueprint::{Blueprint, Index, QueryField, QueryLimits};
 use crate::core::counter::{Count, Counter};
 use crate::core::jit::model::OperationPlan;
 use crate::core::merge_right::MergeRight;
@@ -71,6 +71,19 @@ pub struct Builder {
     pub arg_id: Counter<usize>,
     pub field_id: Counter<usize>,
     pub document: ExecutableDocument,
+    pub limits: QueryLimits,
+}
+
+/// Fails with the position of the first field past the limit.
+fn check_limit<'a>(
+    mut fields: impl Iterator<Item = &'a Field<Flat, Value>>,
+    max: Option<usize>,
+    error: fn(usize) -> LimitError,
+) -> Result<(), BuildError> {
+    match max.and_then(|max| Some((max, fields.nth(max)?))) {
+        Some((max, field)) => Err(BuildError::LimitExceeded { error: error(max), pos: field.pos }),
+        None => Ok(()),
+    }
 }
 
 // TODO: make generic over Value (Input) type
@@ -82,6 +95,7 @@ impl Builder {
             index,
             arg_id: Counter::default(),
             field_id: Counter::default(),
+            limits: blueprint.server.query_limits.clone(),
         }
     }
 
@@ -134,7 +148,8 @@ impl Builder {
         type_condition: &str,
         exts: Option<Flat>,
         fragments: &HashMap<&str, &FragmentDefinition>,
-    ) -> Vec<Field<Flat, Value>> {
+        depth: usize,
+    ) -> Result<Vec<Field<Flat, Value>>, BuildError> {
         let mut fields = vec![];
         for selection in &selection.items {
             match &selection.node {
@@ -173,6 +188,15 @@ impl Builder {
                         .collect::<HashMap<_, _>>();
 
                     if let Some(field_def) = self.index.get_field(type_condition, field_name) {
+                        // checked before planning the nested fields, so that queries that
+                        // are too deep aren't walked any further
+                        if let Some(max) = self.limits.max_depth.filter(|max| depth > *max) {
+                            return Err(BuildError::LimitExceeded {
+                                error: LimitError::Depth(max),
+                                pos: selection.pos.into(),
+                            });
+                        }
+
                         let mut args = Vec::with_capacity(request_args.len());
                         if let QueryField::Field((_, schema_args)) = field_def {
                             for (arg_name, arg_value) in schema_args {
@@ -206,7 +230,8 @@ impl Builder {
                             type_of.name(),
                             Some(Flat::new(id.clone())),
                             fragments,
-                        );
+                            depth + 1,
+                        )?;
                         let ir = match field_def {
                             QueryField::Field((field_def, _)) => field_def.resolver.clone(),
                             _ => None,
@@ -262,7 +287,8 @@ impl Builder {
                             fragment.type_condition.node.on.node.as_str(),
                             exts.clone(),
                             fragments,
-                        ));
+                            depth,
+                        )?);
                     }
                 }
                 Selection::InlineFragment(Positioned { node: fragment, .. }) => {
@@ -277,12 +303,13 @@ impl Builder {
                         type_of,
                         exts.clone(),
                         fragments,
-                    ));
+                        depth,
+                    )?);
                 }
             }
         }
 
-        fields
+        Ok(fields)
     }
 
     #[inline(always)]
@@ -290,7 +317,7 @@ impl Builder {
         match ty {
             OperationType::Query => Some(self.index.get_query()),
             OperationType::Mutation => self.index.get_mutation(),
-            OperationType::Subscription => None,
+            OperationType::Subscription => self.index.get_subscription(),
         }
     }
 
@@ -321,12 +348,8 @@ impl Builder {
         }
     }
 
-    #[inline(always)]
-    pub fn build(
-        &self,
-        variables: &Variables<ConstValue>,
-        operation_name: Option<&str>,
-    ) -> Result<OperationPlan<ConstValue>, BuildError> {
+    /// Plans the operation as far as it can be done without the variables.
+    pub fn prepare(&self, operation_name: Option<&str>) -> Result<PreparedOperation, BuildError> {
         let mut fields = Vec::new();
         let mut fragments: HashMap<&str, &FragmentDefinition> = HashMap::new();
 
@@ -339,10 +362,7 @@ impl Builder {
         let name = self
             .get_type(operation.ty)
             .ok_or(BuildError::RootOperationTypeNotDefined { operation: operation.ty })?;
-        fields.extend(self.iter(&operation.selection_set.node, name, None, &fragments));
-
-        // skip the fields depending on variables.
-        fields.retain(|f| !f.skip(variables));
+        fields.extend(self.iter(&operation.selection_set.node, name, None, &fragments, 1)?);
 
         let is_introspection_query = operation.selection_set.node.items.iter().any(|f| {
             if let Selection::Field(Positioned { node: gql_field, .. }) = &f.node {
@@ -353,20 +373,109 @@ impl Builder {
             }
         });
 
+        Ok(PreparedOperation {
+            name: name.to_string(),
+            fields,
+            operation_type: operation.ty,
+            index: self.index.clone(),
+            is_introspection_query,
+            limits: self.limits.clone(),
+        })
+    }
+
+    #[inline(always)]
+    pub fn build(
+        &self,
+        variables: &Variables<ConstValue>,
+        operation_name: Option<&str>,
+    ) -> Result<OperationPlan<ConstValue>, BuildError> {
+        self.prepare(operation_name)?.resolve(variables)
+    }
+}
+
+/// An operation planned up to the point where its variables are needed, so
+/// that the work can be shared by all the requests executing it.
+#[derive(Clone)]
+pub struct PreparedOperation {
+    name: String,
+    fields: Vec<Field<Flat, Value>>,
+    operation_type: OperationType,
+    index: Arc<Index>,
+    is_introspection_query: bool,
+    limits: QueryLimits,
+}
+
+impl PreparedOperation {
+    pub fn try_new(
+        blueprint: &Blueprint,
+        query: &str,
+        operation_name: Option<&str>,
+    ) -> super::Result<Self> {
+        let doc = async_graphql::parser::parse_query(query)?;
+        Ok(Builder::new(blueprint, doc).prepare(operation_name)?)
+    }
+
+    /// Completes the plan with the variables of a request.
+    pub fn resolve(
+        &self,
+        variables: &Variables<ConstValue>,
+    ) -> Result<OperationPlan<ConstValue>, BuildError> {
+        let mut fields = self.fields.clone();
+
+        // skip the fields depending on variables.
+        fields.retain(|f| !f.skip(variables));
+
+        self.check_limits(&fields)?;
+
+        if self.operation_type == OperationType::Subscription
+            && fields.iter().filter(|f| f.extensions.is_none()).count() != 1
+        {
+            return Err(BuildError::SubscriptionRootField);
+        }
+
         let plan = OperationPlan::new(
-            name,
+            &self.name,
             fields,
-            operation.ty,
+            self.operation_type,
             self.index.clone(),
-            is_introspection_query,
+            self.is_introspection_query,
         );
 
         // TODO: operation from [ExecutableDocument] could contain definitions for
         // default values of arguments. That info should be passed to
         // [InputResolver] to resolve defaults properly
         let input_resolver = InputResolver::new(plan);
+        let mut plan = input_resolver.resolve_input(variables)?;
+
+        // the cost depends on the values of the arguments, so it's only known once
+        // they are resolved
+        if let Some(max) = self.limits.max_cost {
+            let cost = plan.estimate_cost();
+            if cost > max {
+                return Err(BuildError::CostExceeded { cost, max });
+            }
+            plan.cost = Some(cost);
+        }
 
-        Ok(input_resolver.resolve_input(variables)?)
+        Ok(plan)
+    }
+
+    /// Checks the number of fields, aliases and root fields of the query
+    /// against the limits.
+    fn check_limits(&self, fields: &[Field<Flat, Value>]) -> Result<(), BuildError> {
+        check_limit(fields.iter(), self.limits.max_fields, LimitError::Fields)?;
+        check_limit(
+            fields
+                .iter()
+                .filter(|field| field.output_name != field.name),
+            self.limits.max_aliases,
+            LimitError::Aliases,
+        )?;
+        check_limit(
+            fields.iter().filter(|field| field.extensions.is_none()),
+            self.limits.max_root_fields,
+            LimitError::RootFields,
+        )
     }
 }
 
@@ -694,6 +803,86 @@ mod tests {
         insta::assert_debug_snapshot!(plan.into_nested());
     }
 
+    #[test]
+    fn test_query_limits() {
+        let config = Config::from_sdl(CONFIG).to_result().unwrap();
+        let mut blueprint = Blueprint::try_from(&config.into()).unwrap();
+        blueprint.server.query_limits = QueryLimits {
+            max_aliases: Some(1),
+            max_depth: Some(2),
+            max_fields: Some(4),
+            max_root_fields: Some(1),
+        };
+        let build = |query: &str| {
+            let document = async_graphql::parser::parse_query(query).unwrap();
+            Builder::new(&blueprint, document).build(&Variables::new(), None)
+        };
+
+        assert!(build("{ posts { id title } }").is_ok());
+
+        let limit_error = |query: &str| match build(query) {
+            Err(BuildError::LimitExceeded { error, pos }) => (error, pos),
+            result => panic!("expected a limit error, got {:?}", result.map(|_| ())),
+        };
+
+        assert_eq!(
+            limit_error("{ posts { id user { id } } }"),
+            (LimitError::Depth(2), Pos { line: 1, column: 21 })
+        );
+        assert_eq!(
+            limit_error("{ posts { id userId title body } }"),
+            (LimitError::Fields(4), Pos { line: 1, column: 27 })
+        );
+        assert_eq!(
+            limit_error("{ posts { id } users { id } }"),
+            (LimitError::RootFields(1), Pos { line: 1, column: 16 })
+        );
+        assert_eq!(
+            limit_error("{ posts { a: id b: title } }").0,
+            LimitError::Aliases(1)
+        );
+    }
+
+    #[test]
+    fn test_max_cost() {
+        let config = Config::from_sdl(
+            r#"
+            schema
+              @server(maxCost: 50)
+              @upstream(baseURL: "http://jsonplaceholder.typicode.com") {
+              query: Query
+            }
+
+            type Query {
+              posts(first: Int = 10): [Post] @http(path: "/posts") @cost(multipliers: ["first"])
+            }
+
+            type Post {
+              id: Int!
+              user: User @http(path: "/users/{{.value.id}}")
+            }
+
+            type User @cost(weight: 2) {
+              id: Int!
+            }
+            "#,
+        )
+        .to_result()
+        .unwrap();
+        let blueprint = Blueprint::try_from(&config.into()).unwrap();
+        let build = |query: &str| {
+            let document = async_graphql::parser::parse_query(query).unwrap();
+            Builder::new(&blueprint, document).build(&Variables::new(), None)
+        };
+
+        // 1 for posts and 10 times 1 for user, plus 2 for its id
+        let plan = build("{ posts { id user { id } } }").unwrap();
+        assert_eq!(plan.cost, Some(31));
+
+        let error = build("{ posts(first: 20) { id user { id } } }").unwrap_err();
+        assert_eq!(error, BuildError::CostExceeded { cost: 61, max: 50 });
+    }
+
     #[test]
     fn test_directives() {
         let mut variables = Variables::new();
@@ -2,6 +2,8 @@ use async_graphql::parser::types::OperationType;
 use async_graphql::{ErrorExtensions, ServerError};
 use thiserror::Error;
 
+use super::Pos;
+
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 #[error("Error while building the plan")]
 pub enum BuildError {
@@ -13,6 +15,25 @@ pub enum BuildError {
     OperationNotFound(String),
     #[error("Operation name required in request")]
     OperationNameRequired,
+    #[error("Subscription operations must select exactly one root field")]
+    SubscriptionRootField,
+    #[error("{error}")]
+    LimitExceeded { error: LimitError, pos: Pos },
+    #[error("Operation costs {cost}, more than the maximum cost of {max}")]
+    CostExceeded { cost: u64, max: u64 },
+}
+
+/// Limits of `@server` on the shape of the queries
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    #[error("Query is nested deeper than the maximum depth of {0}")]
+    Depth(usize),
+    #[error("Query selects more than the maximum of {0} fields")]
+    Fields(usize),
+    #[error("Query uses more than the maximum of {0} aliases")]
+    Aliases(usize),
+    #[error("Query selects more than the maximum of {0} root fields")]
+    RootFields(usize),
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -74,8 +95,14 @@ impl From<Error> for ServerError {
             return error.into();
         }
 
+        // errors on the shape of the query point to the field that exceeded the limit
+        let pos = match &val {
+            Error::BuildError(BuildError::LimitExceeded { pos, .. }) => Some((*pos).into()),
+            _ => None,
+        };
+
         let extensions = val.extend().extensions;
-        let mut server_error = ServerError::new(val.to_string(), None);
+        let mut server_error = ServerError::new(val.to_string(), pos);
 
         server_error.extensions = extensions;
 
@@ -2,14 +2,17 @@ use std::sync::Arc;
 
 use async_graphql_value::ConstValue;
 use futures_util::future::join_all;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{FutureExt, StreamExt};
 
-use super::context::Context;
+use super::context::{self, Context};
 use super::exec::{Executor, IRExecutor};
-use super::{Error, OperationPlan, Request, Response, Result};
+use super::{BuildError, Error, OperationPlan, Pos, Positioned, Request, Response, Result};
 use crate::core::app_context::AppContext;
+use crate::core::blueprint::DynamicValue;
 use crate::core::http::RequestContext;
 use crate::core::ir::model::IR;
-use crate::core::ir::{self, EvalContext};
+use crate::core::ir::{self, EvalContext, EventStream};
 use crate::core::jit::synth::Synth;
 use crate::core::json::{JsonLike, JsonLikeList};
 
@@ -20,7 +23,16 @@ pub struct ConstValueExecutor {
 
 impl ConstValueExecutor {
     pub fn new(request: &Request<ConstValue>, app_ctx: &Arc<AppContext>) -> Result<Self> {
-        Ok(Self { plan: request.create_plan(&app_ctx.blueprint)? })
+        let trusted = app_ctx
+            .trusted_documents
+            .as_ref()
+            .and_then(|documents| documents.get(&request.query, request.operation_name.as_deref()));
+        let plan = match trusted {
+            Some(operation) => operation.resolve(&request.variables)?,
+            None => request.create_plan(&app_ctx.blueprint)?,
+        };
+
+        Ok(Self { plan })
     }
 
     pub async fn execute(
@@ -37,6 +49,70 @@ impl ConstValueExecutor {
         let synth = Synth::new(plan, store, vars);
         exe.execute(synth).await
     }
+
+    /// Subscribes to the stream behind the root field of a subscription and
+    /// executes the rest of the plan against every event it emits.
+    pub fn subscribe(
+        self,
+        req_ctx: Arc<RequestContext>,
+        request: Request<ConstValue>,
+    ) -> BoxStream<'static, Response<ConstValue, Error>> {
+        let plan = self.plan;
+
+        async move {
+            let events = open_stream(&plan, &req_ctx).await;
+            match events {
+                Ok((events, pos)) => events
+                    .then(move |event| {
+                        let plan = plan.clone();
+                        let req_ctx = req_ctx.clone();
+                        let request = request.clone();
+
+                        async move {
+                            match event {
+                                Ok(value) => {
+                                    let ir = IR::Dynamic(DynamicValue::Value(value));
+                                    let exec = ConstValueExecutor { plan: plan.with_root_ir(ir) };
+                                    exec.execute(&req_ctx, &request).await
+                                }
+                                Err(error) => {
+                                    Response::new(Err(Positioned::new(error.into(), pos)))
+                                }
+                            }
+                        }
+                    })
+                    .boxed(),
+                Err(error) => stream::once(async move { Response::new(Err(error)) }).boxed(),
+            }
+        }
+        .flatten_stream()
+        .boxed()
+    }
+}
+
+async fn open_stream(
+    plan: &OperationPlan<ConstValue>,
+    req_ctx: &RequestContext,
+) -> std::result::Result<(EventStream, Pos), Positioned<Error>> {
+    let field = plan
+        .as_nested()
+        .first()
+        .ok_or(BuildError::SubscriptionRootField)
+        .map_err(|error| Positioned::new(Error::from(error), Pos::default()))?;
+    let ir = field.ir.as_ref().ok_or_else(|| {
+        let error =
+            ir::Error::ExprEval("Subscription fields must be resolved by a stream".to_owned());
+        Positioned::new(Error::IR(error), field.pos)
+    })?;
+
+    let request = context::RequestContext::new(plan.clone());
+    let ctx: Context<ConstValue, ConstValue> = Context::new(field, &request);
+    let eval_ctx = EvalContext::new(req_ctx, &ctx);
+
+    ir.subscribe(&eval_ctx)
+        .await
+        .map(|events| (events, field.pos))
+        .map_err(|error| Positioned::new(error.into(), field.pos))
 }
 
 struct ConstValueExec<'a> {
@@ -2,16 +2,18 @@ use std::collections::BTreeMap;
 use std::future::Future;
 use std::sync::Arc;
 
+use async_graphql::parser::types::OperationType;
 use async_graphql::{Data, Executor, Response, ServerError, Value};
 use async_graphql_value::{ConstValue, Extensions};
 use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
 
 use crate::core::app_context::AppContext;
 use crate::core::async_graphql_hyper::OperationId;
 use crate::core::http::RequestContext;
-use crate::core::jit;
 use crate::core::jit::ConstValueExecutor;
 use crate::core::merge_right::MergeRight;
+use crate::core::{jit, rate_limit};
 
 #[derive(Clone)]
 pub struct JITExecutor {
@@ -38,12 +40,19 @@ impl JITExecutor {
     ) -> Response {
         let is_introspection_query = self.app_ctx.blueprint.server.get_enable_introspection()
             && exec.plan.is_introspection_query;
+        let cost = exec.plan.cost;
 
-        let jit_resp = exec
+        let mut jit_resp = exec
             .execute(&self.req_ctx, &jit_request)
             .await
             .into_async_graphql();
 
+        if let Some(cost) = cost {
+            jit_resp
+                .extensions
+                .insert("cost".to_string(), Value::from(cost));
+        }
+
         if is_introspection_query {
             let async_req = async_graphql::Request::from(jit_request).only_introspection();
             let async_resp = self.app_ctx.execute(async_req).await;
@@ -99,6 +108,10 @@ impl Executor for JITExecutor {
         async move {
             match ConstValueExecutor::new(&jit_request, &self.app_ctx) {
                 Ok(exec) => {
+                    if let Err(error) = rate_limit::check_plan(&exec.plan, &self.req_ctx).await {
+                        return Response::from_errors(vec![error.into()]);
+                    }
+
                     if self.is_query && exec.plan.dedupe {
                         self.dedupe_and_exec(exec, jit_request).await
                     } else {
@@ -112,9 +125,50 @@ impl Executor for JITExecutor {
 
     fn execute_stream(
         &self,
-        _: async_graphql::Request,
+        request: async_graphql::Request,
         _: Option<Arc<Data>>,
     ) -> BoxStream<'static, Response> {
-        unimplemented!("streaming not supported")
+        let jit_request = jit::Request::from(request);
+
+        match ConstValueExecutor::new(&jit_request, &self.app_ctx) {
+            Ok(exec) if exec.plan.operation_type() == OperationType::Subscription => {
+                let req_ctx = self.req_ctx.clone();
+                futures_util::stream::once(async move {
+                    match rate_limit::check_plan(&exec.plan, &req_ctx).await {
+                        Ok(()) => exec
+                            .subscribe(req_ctx, jit_request)
+                            .map(|response| response.into_async_graphql())
+                            .boxed(),
+                        Err(error) => {
+                            let response = Response::from_errors(vec![error.into()]);
+                            futures_util::stream::once(async move { response }).boxed()
+                        }
+                    }
+                })
+                .flatten()
+                .boxed()
+            }
+            // queries and mutations sent over a streaming transport resolve to a
+            // single response
+            Ok(exec) => {
+                let this = self.clone();
+                futures_util::stream::once(async move {
+                    if let Err(error) = rate_limit::check_plan(&exec.plan, &this.req_ctx).await {
+                        return Response::from_errors(vec![error.into()]);
+                    }
+
+                    if this.is_query && exec.plan.dedupe {
+                        this.dedupe_and_exec(exec, jit_request).await
+                    } else {
+                        this.exec(exec, jit_request).await
+                    }
+                })
+                .boxed()
+            }
+            Err(error) => {
+                let response = Response::from_errors(vec![error.into()]);
+                futures_util::stream::once(async move { response }).boxed()
+            }
+        }
     }
 }
@@ -18,6 +18,7 @@ pub mod common;
 mod graphql_executor;
 
 // Public Exports
+pub use builder::PreparedOperation;
 pub use error::*;
 pub use exec_const::*;
 pub use graphql_executor::*;
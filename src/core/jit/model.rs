@@ -8,7 +8,7 @@ use async_graphql_value::ConstValue;
 use serde::{Deserialize, Serialize};
 
 use super::Error;
-use crate::core::blueprint::Index;
+use crate::core::blueprint::{Index, QueryField};
 use crate::core::ir::model::IR;
 use crate::core::ir::TypedValue;
 use crate::core::json::JsonLike;
@@ -353,6 +353,8 @@ pub struct OperationPlan<Input> {
     pub index: Arc<Index>,
     pub is_introspection_query: bool,
     pub dedupe: bool,
+    /// Cost of the operation, computed when `maxCost` is set
+    pub cost: Option<u64>,
 }
 
 impl<Input> std::fmt::Debug for OperationPlan<Input> {
@@ -388,6 +390,7 @@ impl<Input> OperationPlan<Input> {
             index: self.index,
             is_introspection_query: self.is_introspection_query,
             dedupe: self.dedupe,
+            cost: self.cost,
         })
     }
 }
@@ -430,9 +433,21 @@ impl<Input> OperationPlan<Input> {
             index,
             is_introspection_query,
             dedupe,
+            cost: None,
         }
     }
 
+    /// Replaces the resolvers of the root fields with the given [IR]
+    pub fn with_root_ir(mut self, ir: IR) -> Self {
+        for field in self.flat.iter_mut().filter(|f| f.extensions.is_none()) {
+            field.ir = Some(ir.clone());
+        }
+        for field in self.nested.iter_mut() {
+            field.ir = Some(ir.clone());
+        }
+        self
+    }
+
     /// Returns the name of the root type
     pub fn root_name(&self) -> &str {
         &self.root_name
@@ -529,6 +544,43 @@ impl<Input> OperationPlan<Input> {
     }
 }
 
+impl OperationPlan<ConstValue> {
+    /// Estimates the cost of executing the plan from the `@cost` of the
+    /// selected fields.
+    pub fn estimate_cost(&self) -> u64 {
+        self.nested
+            .iter()
+            .map(|field| self.field_cost(field))
+            .fold(0, u64::saturating_add)
+    }
+
+    fn field_cost(&self, field: &Field<Nested<ConstValue>, ConstValue>) -> u64 {
+        let definition = field
+            .type_condition
+            .as_deref()
+            .and_then(|type_name| self.index.get_field(type_name, &field.name));
+        let Some(QueryField::Field((definition, _))) = definition else {
+            return 0;
+        };
+        let cost = &definition.cost;
+
+        let multiplier = cost
+            .multipliers
+            .iter()
+            .filter_map(|name| field.args.iter().find(|arg| arg.name == *name))
+            .filter_map(|arg| arg.value.as_ref().or(arg.default_value.as_ref()))
+            .filter_map(|value| value.as_u64())
+            .fold(1, u64::saturating_mul);
+        let nested = field
+            .iter()
+            .map(|field| self.field_cost(field))
+            .fold(0, u64::saturating_add);
+
+        cost.weight
+            .saturating_add(multiplier.saturating_mul(nested))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Directive<Input> {
     pub name: String,
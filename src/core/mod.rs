@@ -27,10 +27,13 @@ mod lift;
 pub mod merge_right;
 pub mod mustache;
 pub mod path;
+pub mod persisted_query;
 pub mod primitive;
 pub mod print_schema;
 pub mod proto_reader;
+pub mod rate_limit;
 pub mod resource_reader;
+pub mod response_cache;
 pub mod rest;
 pub mod runtime;
 pub mod scalar;
@@ -38,6 +41,7 @@ pub mod schema_extension;
 mod serde_value_ext;
 pub mod tracing;
 mod transform;
+pub mod trusted_documents;
 pub mod try_fold;
 pub mod valid;
 mod variance;
@@ -48,10 +52,13 @@ mod wrapping_type;
 use std::borrow::Cow;
 use std::hash::Hash;
 use std::num::NonZeroU64;
+use std::time::Duration;
 
 use async_graphql_value::ConstValue;
 pub use errata::Errata;
 pub use error::{Error, Result};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
 use http::Response;
 use ir::model::IoId;
 pub use mustache::Mustache;
@@ -68,12 +75,31 @@ pub trait EnvIO: Send + Sync + 'static {
     fn get(&self, key: &str) -> Option<Cow<'_, str>>;
 }
 
+pub type ByteStream = BoxStream<'static, anyhow::Result<hyper::body::Bytes>>;
+
 #[async_trait::async_trait]
 pub trait HttpIO: Sync + Send + 'static {
     async fn execute(
         &self,
         request: reqwest::Request,
     ) -> anyhow::Result<Response<hyper::body::Bytes>>;
+
+    /// Executes the request without waiting for the complete body, which is
+    /// required for long-lived responses such as `text/event-stream`.
+    /// Runtimes that can't stream fall back to a body with a single chunk.
+    async fn execute_stream(
+        &self,
+        request: reqwest::Request,
+    ) -> anyhow::Result<Response<ByteStream>> {
+        let response = self.execute(request).await?;
+        let body = response.body;
+
+        Ok(Response {
+            status: response.status,
+            headers: response.headers,
+            body: futures_util::stream::once(async move { Ok(body) }).boxed(),
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -93,12 +119,31 @@ pub trait Cache: Send + Sync {
         ttl: NonZeroU64,
     ) -> Result<(), cache::Error>;
     async fn get<'a>(&'a self, key: &'a Self::Key) -> Result<Option<Self::Value>, cache::Error>;
+    /// Associates the key with the tags, so that purging any of them removes
+    /// its value.
+    async fn tag<'a>(&'a self, key: &'a Self::Key, tags: &'a [String]) -> Result<(), cache::Error>;
+    /// Removes the values of all the keys associated with the tag.
+    async fn purge<'a>(&'a self, tag: &'a str) -> Result<(), cache::Error>;
 
     fn hit_rate(&self) -> Option<f64>;
+    /// Counters of the cache, when it keeps track of them.
+    fn stats(&self) -> Option<cache::Stats>;
 }
 
 pub type EntityCache = dyn Cache<Key = IoId, Value = ConstValue>;
 
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from the bucket stored under `key`, after refilling it
+    /// for the time elapsed since it was last used. When the bucket is empty,
+    /// returns how long it takes until the next token is available.
+    async fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limit: &'a blueprint::RateLimit,
+    ) -> anyhow::Result<Option<Duration>>;
+}
+
 #[async_trait::async_trait]
 pub trait WorkerIO<In, Out>: Send + Sync + 'static {
     /// Calls a global JS function
@@ -43,6 +43,14 @@ impl Mustache {
             .collect()
     }
 
+    /// Checks if any expression of the mustache template starts with the
+    /// given part, e.g. `auth` for `{{.auth.claims.sub}}`
+    pub fn expression_starts_with(&self, head: &str) -> bool {
+        self.expression_segments()
+            .iter()
+            .any(|parts| parts.first().is_some_and(|part| part == head))
+    }
+
     /// Checks if the mustache template contains the given expression
     pub fn expression_contains(&self, expression: &str) -> bool {
         self.segments()
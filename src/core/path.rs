@@ -95,6 +95,18 @@ impl<'a, Ctx: ResolverContextLike> EvalContext<'a, Ctx> {
                     ctx.var(tail[0].as_ref())?,
                 ))),
                 "env" => Some(ValueString::String(ctx.env_var(tail[0].as_ref())?)),
+                "auth" => match tail.split_first() {
+                    Some((claims, path)) if claims.as_ref() == "claims" => {
+                        Some(ValueString::Value(Cow::Owned(ctx.path_claim(path)?)))
+                    }
+                    _ => None,
+                },
+                "client" => match tail[0].as_ref() {
+                    "ip" => Some(ValueString::String(Cow::Owned(
+                        ctx.client_ip()?.to_string(),
+                    ))),
+                    _ => None,
+                },
                 _ => None,
             })
     }
@@ -134,13 +146,16 @@ mod tests {
     mod evaluation_context {
         use std::borrow::Cow;
         use std::collections::BTreeMap;
+        use std::net::IpAddr;
         use std::sync::Arc;
 
         use async_graphql_value::{ConstValue as Value, Name, Number};
         use http::header::{HeaderMap, HeaderValue};
         use indexmap::IndexMap;
         use once_cell::sync::Lazy;
+        use serde_json::json;
 
+        use crate::core::auth::jwt::jwt_verify::JwtClaim;
         use crate::core::http::RequestContext;
         use crate::core::ir::{EvalContext, ResolverContextLike, SelectionField};
         use crate::core::path::{PathGraphql, PathString, PathValue, ValueString};
@@ -245,6 +260,15 @@ mod tests {
 
             req_ctx.server.vars = TEST_VARS.clone();
             req_ctx.runtime.env = Arc::new(Env::init(TEST_ENV_VARS.clone()));
+            req_ctx.client_ip = Some(IpAddr::from([127, 0, 0, 1]));
+
+            let claims = json!({ "sub": "user-1", "org": { "id": 42 } });
+            let serde_json::Value::Object(claims) = claims else {
+                unreachable!()
+            };
+            req_ctx
+                .auth_ctx
+                .set_claims(None, JwtClaim::try_from(claims).unwrap());
 
             req_ctx
         });
@@ -374,6 +398,22 @@ mod tests {
             );
             assert_eq!(EVAL_CTX.raw_value(&["env", "x-missing"]), None);
 
+            // auth
+            assert_eq!(
+                EVAL_CTX.raw_value(&["auth", "claims", "sub"]),
+                Some(ValueString::Value(Cow::Owned(
+                    async_graphql::Value::String("user-1".into())
+                )))
+            );
+            assert_eq!(
+                EVAL_CTX.raw_value(&["auth", "claims", "org", "id"]),
+                Some(ValueString::Value(Cow::Owned(
+                    async_graphql::Value::Number(42.into())
+                )))
+            );
+            assert_eq!(EVAL_CTX.raw_value(&["auth", "claims", "missing"]), None);
+            assert_eq!(EVAL_CTX.raw_value(&["auth", "sub"]), None);
+
             // other value types
             assert_eq!(EVAL_CTX.raw_value(&["foo", "key"]), None);
             assert_eq!(EVAL_CTX.raw_value(&["bar", "key"]), None);
@@ -451,6 +491,24 @@ mod tests {
             );
             assert_eq!(EVAL_CTX.path_string(&["env", "x-missing"]), None);
 
+            // auth
+            assert_eq!(
+                EVAL_CTX.path_string(&["auth", "claims", "sub"]),
+                Some(Cow::Borrowed("user-1"))
+            );
+            assert_eq!(
+                EVAL_CTX.path_string(&["auth", "claims", "org"]),
+                Some(Cow::Borrowed(r#"{"id":42}"#))
+            );
+            assert_eq!(EVAL_CTX.path_string(&["auth", "claims", "missing"]), None);
+
+            // client
+            assert_eq!(
+                EVAL_CTX.path_string(&["client", "ip"]),
+                Some(Cow::Borrowed("127.0.0.1"))
+            );
+            assert_eq!(EVAL_CTX.path_string(&["client", "port"]), None);
+
             // other value types
             assert_eq!(EVAL_CTX.path_string(&["foo", "key"]), None);
             assert_eq!(EVAL_CTX.path_string(&["bar", "key"]), None);
@@ -512,6 +570,16 @@ mod tests {
             );
             assert_eq!(EVAL_CTX.path_graphql(&["env", "x-missing"]), None);
 
+            // auth
+            assert_eq!(
+                EVAL_CTX.path_graphql(&["auth", "claims", "sub"]),
+                Some("\"user-1\"".to_owned())
+            );
+            assert_eq!(
+                EVAL_CTX.path_graphql(&["auth", "claims", "org", "id"]),
+                Some("42".to_owned())
+            );
+
             // other value types
             assert_eq!(EVAL_CTX.path_graphql(&["foo", "key"]), None);
             assert_eq!(EVAL_CTX.path_graphql(&["bar", "key"]), None);
@@ -0,0 +1,228 @@
+use std::num::NonZeroU64;
+
+use async_graphql::{ErrorExtensionValues, ServerError};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::core::cache::{Eviction, InMemoryCache};
+use crate::core::{blueprint, Cache};
+
+/// How long a registered document is kept, in milliseconds
+const TTL: u64 = 24 * 60 * 60 * 1000;
+
+/// `extensions.persistedQuery` of a request following the Apollo automatic
+/// persisted queries protocol.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedQuery {
+    version: u64,
+    sha256_hash: String,
+}
+
+/// Error of a request that could not be resolved to a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    NotFound,
+    NotSupported,
+    HashMismatch,
+}
+
+impl From<Error> for ServerError {
+    fn from(error: Error) -> Self {
+        let (message, code) = match error {
+            Error::NotFound => ("PersistedQueryNotFound", "PERSISTED_QUERY_NOT_FOUND"),
+            Error::NotSupported => (
+                "PersistedQueryNotSupported",
+                "PERSISTED_QUERY_NOT_SUPPORTED",
+            ),
+            Error::HashMismatch => ("provided sha does not match query", "BAD_REQUEST"),
+        };
+
+        let mut extensions = ErrorExtensionValues::default();
+        extensions.set("code", code);
+
+        let mut server_error = ServerError::new(message, None);
+        server_error.extensions = Some(extensions);
+        server_error
+    }
+}
+
+/// Documents registered by the clients, by the sha256 hash of the document.
+pub struct PersistedQueries {
+    documents: InMemoryCache<String, String>,
+    max_document_size: usize,
+}
+
+impl PersistedQueries {
+    pub fn new(options: &blueprint::PersistedQueries) -> Self {
+        Self {
+            documents: InMemoryCache::with_capacity(options.max_entries, Eviction::Lru),
+            max_document_size: options.max_document_size,
+        }
+    }
+
+    /// Fills in the query of a request that only carries the hash of its
+    /// document. Requests carrying both get their document registered, so
+    /// later ones can leave it out.
+    async fn resolve(
+        &self,
+        request: &mut async_graphql::Request,
+        hash: String,
+    ) -> Result<(), Error> {
+        if request.query.is_empty() {
+            match self.documents.get(&hash).await {
+                Ok(Some(query)) => {
+                    request.query = query;
+                    Ok(())
+                }
+                _ => Err(Error::NotFound),
+            }
+        } else if sha256(&request.query) != hash {
+            Err(Error::HashMismatch)
+        } else if request.query.len() > self.max_document_size {
+            // the document is executed, but not kept
+            Ok(())
+        } else {
+            let query = request.query.clone();
+            if let Err(error) = self
+                .documents
+                .set(hash, query, NonZeroU64::new(TTL).unwrap())
+                .await
+            {
+                tracing::warn!("Failed to register persisted query: {}", error);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Resolves the document of a request following the automatic persisted
+/// queries protocol. Requests that only carry the hash of their document fail
+/// with `PersistedQueryNotSupported` when the persisted queries are disabled,
+/// so that clients fall back to sending the document.
+pub async fn resolve(
+    request: &mut async_graphql::Request,
+    persisted_queries: Option<&PersistedQueries>,
+) -> Result<(), Error> {
+    let Some(hash) = requested_hash(request)? else {
+        return Ok(());
+    };
+
+    match persisted_queries {
+        Some(persisted_queries) => persisted_queries.resolve(request, hash).await,
+        None if request.query.is_empty() => Err(Error::NotSupported),
+        None => Ok(()),
+    }
+}
+
+/// The hash of the document sent in `extensions.persistedQuery`, if any.
+pub fn requested_hash(request: &async_graphql::Request) -> Result<Option<String>, Error> {
+    let Some(value) = request.extensions.get("persistedQuery") else {
+        return Ok(None);
+    };
+    let persisted_query = async_graphql::from_value::<PersistedQuery>(value.clone())
+        .map_err(|_| Error::NotSupported)?;
+    if persisted_query.version != 1 {
+        return Err(Error::NotSupported);
+    }
+
+    Ok(Some(persisted_query.sha256_hash.to_lowercase()))
+}
+
+pub fn sha256(query: &str) -> String {
+    format!("{:x}", Sha256::digest(query.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Value;
+
+    use super::*;
+
+    const QUERY: &str = "{ user { id } }";
+
+    fn request(query: &str, hash: &str) -> async_graphql::Request {
+        let mut request = async_graphql::Request::new(query);
+        let persisted_query = serde_json::json!({ "version": 1, "sha256Hash": hash });
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            Value::from_json(persisted_query).unwrap(),
+        );
+        request
+    }
+
+    fn persisted_queries(max_document_size: usize) -> PersistedQueries {
+        PersistedQueries::new(&blueprint::PersistedQueries { max_document_size, max_entries: 10 })
+    }
+
+    #[tokio::test]
+    async fn test_register_and_lookup() {
+        let persisted_queries = persisted_queries(1024);
+        let persisted_queries = Some(&persisted_queries);
+        let hash = sha256(QUERY);
+
+        let mut lookup = request("", &hash);
+        assert_eq!(
+            resolve(&mut lookup, persisted_queries).await,
+            Err(Error::NotFound)
+        );
+
+        let mut register = request(QUERY, &hash);
+        assert_eq!(resolve(&mut register, persisted_queries).await, Ok(()));
+
+        let mut lookup = request("", &hash);
+        assert_eq!(resolve(&mut lookup, persisted_queries).await, Ok(()));
+        assert_eq!(lookup.query, QUERY);
+    }
+
+    #[tokio::test]
+    async fn test_hash_mismatch() {
+        let persisted_queries = persisted_queries(1024);
+        let mut request = request(QUERY, &sha256("{ posts { id } }"));
+
+        assert_eq!(
+            resolve(&mut request, Some(&persisted_queries)).await,
+            Err(Error::HashMismatch)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_too_large() {
+        let persisted_queries = persisted_queries(QUERY.len() - 1);
+        let persisted_queries = Some(&persisted_queries);
+        let hash = sha256(QUERY);
+
+        let mut register = request(QUERY, &hash);
+        assert_eq!(resolve(&mut register, persisted_queries).await, Ok(()));
+
+        let mut lookup = request("", &hash);
+        assert_eq!(
+            resolve(&mut lookup, persisted_queries).await,
+            Err(Error::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let hash = sha256(QUERY);
+
+        let mut lookup = request("", &hash);
+        assert_eq!(resolve(&mut lookup, None).await, Err(Error::NotSupported));
+
+        let mut request = request(QUERY, &hash);
+        assert_eq!(resolve(&mut request, None).await, Ok(()));
+        assert_eq!(request.query, QUERY);
+    }
+
+    #[tokio::test]
+    async fn test_without_persisted_query() {
+        let persisted_queries = persisted_queries(1024);
+        let mut request = async_graphql::Request::new(QUERY);
+
+        assert_eq!(
+            resolve(&mut request, Some(&persisted_queries)).await,
+            Ok(())
+        );
+        assert_eq!(request.query, QUERY);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::core::blueprint::RateLimit;
+use crate::core::RateLimitStore;
+
+/// Full buckets are dropped, but only looked for once there are this many
+/// buckets.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    /// Seconds since the epoch at which the bucket was last used
+    updated_at: f64,
+    /// Seconds since the epoch at which the bucket is full again
+    full_at: f64,
+}
+
+/// Keeps the buckets in the memory of the process, so each instance of the
+/// server enforces the limits on its own.
+#[derive(Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl InMemoryRateLimitStore {
+    fn take(&self, key: &str, limit: &RateLimit, now: f64) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            // a full bucket behaves the same as a missing one
+            buckets.retain(|_, bucket| bucket.full_at > now);
+        }
+
+        let capacity = limit.capacity as f64;
+        // tokens per second
+        let rate = limit.refill_rate as f64 / limit.refill_interval.as_secs_f64();
+
+        let bucket = buckets.entry(key.to_owned()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+            full_at: now,
+        });
+
+        let elapsed = (now - bucket.updated_at).max(0.0);
+        let tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        if tokens >= 1.0 {
+            bucket.tokens = tokens - 1.0;
+            bucket.full_at = now + (capacity - bucket.tokens) / rate;
+            None
+        } else {
+            bucket.tokens = tokens;
+            Some(Duration::from_secs_f64((1.0 - tokens) / rate))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limit: &'a RateLimit,
+    ) -> anyhow::Result<Option<Duration>> {
+        // chrono reads the clock on wasm targets as well
+        let now = chrono::Utc::now().timestamp_micros() as f64 / 1_000_000.0;
+
+        Ok(self.take(key, limit, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Mustache;
+
+    fn limit(capacity: u64, refill_rate: u64) -> RateLimit {
+        RateLimit {
+            scope: "Query".to_owned(),
+            key: Mustache::parse("{{.client.ip}}"),
+            capacity,
+            refill_rate,
+            refill_interval: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn test_empty_bucket() {
+        let store = InMemoryRateLimitStore::default();
+        let limit = limit(2, 1);
+
+        assert_eq!(store.take("a", &limit, 0.0), None);
+        assert_eq!(store.take("a", &limit, 0.0), None);
+        assert_eq!(
+            store.take("a", &limit, 0.5),
+            Some(Duration::from_millis(500))
+        );
+
+        // buckets are kept per key
+        assert_eq!(store.take("b", &limit, 0.5), None);
+    }
+
+    #[test]
+    fn test_refill() {
+        let store = InMemoryRateLimitStore::default();
+        let limit = limit(2, 2);
+
+        assert_eq!(store.take("a", &limit, 0.0), None);
+        assert_eq!(store.take("a", &limit, 0.0), None);
+        assert!(store.take("a", &limit, 0.0).is_some());
+
+        assert_eq!(store.take("a", &limit, 1.0), None);
+        assert_eq!(store.take("a", &limit, 1.0), None);
+        assert!(store.take("a", &limit, 1.0).is_some());
+
+        // the bucket never holds more than its capacity
+        assert_eq!(store.take("a", &limit, 60.0), None);
+        assert_eq!(store.take("a", &limit, 60.0), None);
+        assert!(store.take("a", &limit, 60.0).is_some());
+    }
+}
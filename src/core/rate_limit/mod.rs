@@ -0,0 +1,253 @@
+mod in_memory;
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_graphql::{ErrorExtensionValues, ServerError};
+use http::HeaderMap;
+pub use in_memory::InMemoryRateLimitStore;
+
+use crate::core::app_context::AppContext;
+use crate::core::blueprint::{QueryField, RateLimit};
+use crate::core::http::RequestContext;
+use crate::core::ir::{EmptyResolverContext, EvalContext};
+use crate::core::jit::{self, OperationPlan};
+use crate::core::path::PathString;
+
+/// Error of a request that exceeded one of its rate limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimited {
+    /// Seconds until the client can try again
+    pub retry_after: u64,
+}
+
+impl From<RateLimited> for ServerError {
+    fn from(error: RateLimited) -> Self {
+        let mut extensions = ErrorExtensionValues::default();
+        extensions.set("code", "RATE_LIMITED");
+        extensions.set("retryAfter", error.retry_after);
+
+        let mut server_error = ServerError::new(
+            format!(
+                "Rate limit exceeded, retry after {} seconds",
+                error.retry_after
+            ),
+            None,
+        );
+        server_error.extensions = Some(extensions);
+        server_error
+    }
+}
+
+/// Takes a token from the bucket of the client for the limit set on the
+/// server.
+pub async fn check_request(req_ctx: &RequestContext) -> Result<(), RateLimited> {
+    match req_ctx.server.rate_limit.as_ref() {
+        Some(limit) => acquire(limit, req_ctx).await,
+        None => Ok(()),
+    }
+}
+
+/// Takes a token from the bucket of the client for every limit of the fields
+/// selected by the operation. Fields sharing the limit of their type take a
+/// single token.
+pub async fn check_plan<Input>(
+    plan: &OperationPlan<Input>,
+    req_ctx: &RequestContext,
+) -> Result<(), RateLimited> {
+    let mut scopes = HashSet::new();
+
+    for field in plan.as_parent() {
+        let Some(type_name) = field.type_condition.as_deref() else {
+            continue;
+        };
+        let Some(QueryField::Field((definition, _))) = plan.index.get_field(type_name, &field.name)
+        else {
+            continue;
+        };
+        let Some(limit) = definition.rate_limit.as_ref() else {
+            continue;
+        };
+
+        if scopes.insert(limit.scope.as_str()) {
+            acquire(limit, req_ctx).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes the tokens of the limits of the fields selected by a request that's
+/// executed without the JIT engine, planning it the way the engine does.
+/// Requests that can't be planned are left for the executor to reject.
+pub async fn check_operation(
+    request: &async_graphql::Request,
+    app_ctx: &AppContext,
+    req_ctx: &RequestContext,
+) -> Result<(), RateLimited> {
+    if !app_ctx.has_field_rate_limits {
+        return Ok(());
+    }
+
+    let request = jit::Request {
+        query: request.query.clone(),
+        operation_name: request.operation_name.clone(),
+        variables: jit::Variables::from_iter(
+            request
+                .variables
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone())),
+        ),
+        extensions: Default::default(),
+    };
+
+    match request.create_plan(&app_ctx.blueprint) {
+        Ok(plan) => check_plan(&plan, req_ctx).await,
+        Err(_) => Ok(()),
+    }
+}
+
+async fn acquire(limit: &RateLimit, req_ctx: &RequestContext) -> Result<(), RateLimited> {
+    if limit.uses_claims() {
+        // claims are only known once the credentials of the request are verified,
+        // clients that fail the verification share the bucket of the empty key
+        let _ = req_ctx.auth_ctx.validate(req_ctx).await;
+    }
+
+    let key = limit.bucket(&limit.key.render(&KeyContext {
+        eval_ctx: EvalContext::new(req_ctx, &EmptyResolverContext),
+        headers: &req_ctx.request_headers,
+    }));
+
+    match req_ctx.runtime.rate_limit.acquire(&key, limit).await {
+        Ok(None) => Ok(()),
+        Ok(Some(retry_after)) => {
+            let retry_after = to_secs(retry_after);
+            req_ctx.set_retry_after(retry_after);
+
+            Err(RateLimited { retry_after })
+        }
+        Err(err) => {
+            // requests are let through rather than failed while the store is unavailable
+            tracing::warn!("Failed to check the rate limit of `{}`: {}", key, err);
+            Ok(())
+        }
+    }
+}
+
+/// Context the key of a limit is rendered with. Headers are read from the
+/// request received by the gateway, so that the ones identifying clients don't
+/// have to be forwarded to the upstreams.
+struct KeyContext<'a> {
+    eval_ctx: EvalContext<'a, EmptyResolverContext>,
+    headers: &'a HeaderMap,
+}
+
+impl PathString for KeyContext<'_> {
+    fn path_string<'a, T: AsRef<str>>(&'a self, path: &'a [T]) -> Option<Cow<'a, str>> {
+        match path {
+            [head, name] if head.as_ref() == "headers" => self
+                .headers
+                .get(name.as_ref())
+                .and_then(|value| value.to_str().ok())
+                .map(Cow::Borrowed),
+            [head, claims, path @ ..] if head.as_ref() == "auth" && claims.as_ref() == "claims" => {
+                // read without recording it, the limits don't change the responses
+                match self.eval_ctx.request_ctx.auth_ctx.claim(path)? {
+                    serde_json::Value::Null => None,
+                    serde_json::Value::String(claim) => Some(Cow::Owned(claim)),
+                    claim => Some(Cow::Owned(claim.to_string())),
+                }
+            }
+            _ => self.eval_ctx.path_string(path),
+        }
+    }
+}
+
+/// Rounds up, so that clients don't retry before a token is available.
+fn to_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::num::NonZeroU64;
+
+    use super::*;
+    use crate::core::config;
+    use crate::core::valid::Validator;
+
+    fn request(rate_limit: &config::RateLimit, ip: [u8; 4]) -> RequestContext {
+        let mut request = RequestContext::new(crate::core::runtime::test::init(None))
+            .client_ip(Some(IpAddr::V4(Ipv4Addr::from(ip))));
+        request.server.rate_limit = Some(
+            RateLimit::make(RateLimit::SERVER_SCOPE, rate_limit)
+                .to_result()
+                .unwrap(),
+        );
+
+        request
+    }
+
+    #[tokio::test]
+    async fn test_check_request() {
+        let rate_limit = config::RateLimit {
+            capacity: NonZeroU64::new(1).unwrap(),
+            key: None,
+            refill_interval: NonZeroU64::new(60_000),
+            refill_rate: None,
+        };
+
+        // the runtime, and with it the store, is shared by the requests
+        let client_1 = request(&rate_limit, [10, 0, 0, 1]);
+        let mut client_2 = request(&rate_limit, [10, 0, 0, 2]);
+        client_2.runtime = client_1.runtime.clone();
+
+        assert_eq!(check_request(&client_1).await, Ok(()));
+        assert_eq!(
+            check_request(&client_1).await,
+            Err(RateLimited { retry_after: 60 })
+        );
+        assert_eq!(client_1.get_retry_after(), Some(60));
+
+        assert_eq!(check_request(&client_2).await, Ok(()));
+        assert_eq!(client_2.get_retry_after(), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_request_by_header() {
+        let rate_limit = config::RateLimit {
+            capacity: NonZeroU64::new(1).unwrap(),
+            key: Some("{{.headers.x-api-key}}".to_string()),
+            refill_interval: NonZeroU64::new(60_000),
+            refill_rate: None,
+        };
+
+        // the header identifies the client without being forwarded upstream
+        let mut client_1 = request(&rate_limit, [10, 0, 0, 1]);
+        client_1
+            .request_headers
+            .insert("x-api-key", "one".parse().unwrap());
+        let mut client_2 = request(&rate_limit, [10, 0, 0, 1]);
+        client_2
+            .request_headers
+            .insert("x-api-key", "two".parse().unwrap());
+        client_2.runtime = client_1.runtime.clone();
+
+        assert!(client_1.allowed_headers.is_empty());
+        assert_eq!(check_request(&client_1).await, Ok(()));
+        assert_eq!(
+            check_request(&client_1).await,
+            Err(RateLimited { retry_after: 60 })
+        );
+        assert_eq!(check_request(&client_2).await, Ok(()));
+    }
+
+    #[test]
+    fn test_to_secs() {
+        assert_eq!(to_secs(Duration::from_millis(1500)), 2);
+        assert_eq!(to_secs(Duration::from_secs(3)), 3);
+    }
+}
@@ -103,6 +103,11 @@ impl ResourceReader<Cached> {
     pub fn cached(runtime: TargetRuntime) -> Self {
         ResourceReader(Cached::init(runtime))
     }
+
+    /// Returns the paths of all the resources that have been read so far
+    pub fn paths(&self) -> Vec<String> {
+        self.0.cache.lock().unwrap().keys().cloned().collect()
+    }
 }
 
 impl std::fmt::Display for Resource {
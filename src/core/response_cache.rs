@@ -0,0 +1,236 @@
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroU64;
+
+use async_graphql::BatchResponse;
+use async_graphql_value::{ConstValue, Name};
+use http::HeaderMap;
+use indexmap::IndexMap;
+use tailcall_hasher::TailcallHasher;
+
+use crate::core::async_graphql_hyper::{GraphQLRequestLike, GraphQLResponse};
+use crate::core::blueprint::ResponseCache;
+use crate::core::http::RequestContext;
+use crate::core::ir::model::IoId;
+use crate::core::json::JsonLike;
+
+/// A response served from the cache.
+pub struct CachedResponse {
+    pub response: async_graphql::Response,
+    /// Seconds since the response was cached
+    pub age: u64,
+    /// Seconds the response is cached for
+    pub max_age: u64,
+}
+
+/// Identifies the cached response of a request, only single queries are
+/// cached.
+pub async fn key<T: GraphQLRequestLike>(
+    response_cache: &ResponseCache,
+    request: &mut T,
+    headers: &HeaderMap,
+    req_ctx: &RequestContext,
+) -> Option<IoId> {
+    if !request.is_query() {
+        return None;
+    }
+    let [request] = request.requests_mut().try_into().ok()?;
+
+    if !response_cache.claims.is_empty() {
+        // claims are only known once the credentials of the request are verified
+        let _ = req_ctx.auth_ctx.validate(req_ctx).await;
+    }
+
+    let mut hasher = TailcallHasher::default();
+    "responseCache".hash(&mut hasher);
+    normalize(&request.query).hash(&mut hasher);
+    request.operation_name.hash(&mut hasher);
+    serde_json::to_string(&request.variables)
+        .ok()?
+        .hash(&mut hasher);
+    for name in response_cache.headers.iter() {
+        headers
+            .get(name)
+            .map(|value| value.as_bytes())
+            .hash(&mut hasher);
+    }
+    for path in response_cache.claims.iter() {
+        req_ctx
+            .auth_ctx
+            .claim(path.as_slice())
+            .map(|value| value.to_string())
+            .hash(&mut hasher);
+    }
+
+    Some(IoId::new(hasher.finish()))
+}
+
+pub async fn get(key: &IoId, req_ctx: &RequestContext) -> Option<CachedResponse> {
+    let ConstValue::Object(mut entry) = req_ctx.runtime.cache.get(key).await.ok()?? else {
+        return None;
+    };
+    let data = entry.swap_remove("data")?;
+    let stored_at = entry.get("storedAt")?.as_i64()?;
+    let max_age = entry.get("maxAge")?.as_u64()?;
+    let age = u64::try_from(now() - stored_at).unwrap_or_default();
+
+    Some(CachedResponse { response: async_graphql::Response::new(data), age, max_age })
+}
+
+/// Caches the response for the least `maxAge` of the `@cache` values that
+/// were used to resolve it, bounded by the `max-age` of the upstream
+/// responses. Responses with errors, with protected data, that upstream
+/// services don't allow to share, or that were resolved from headers or claims
+/// the key doesn't vary on, aren't cached.
+pub async fn set(
+    key: IoId,
+    response_cache: &ResponseCache,
+    response: &GraphQLResponse,
+    req_ctx: &RequestContext,
+) {
+    let BatchResponse::Single(response) = &response.0 else {
+        return;
+    };
+    if !response.errors.is_empty()
+        || req_ctx.auth_ctx.is_authorized()
+        || req_ctx.is_cache_public() == Some(false)
+        || req_ctx.has_read_uncached_claims()
+        // the headers sent to the upstreams can change their responses
+        || req_ctx
+            .allowed_headers
+            .keys()
+            .any(|name| !response_cache.headers.contains(name))
+    {
+        return;
+    }
+    let Some(mut ttl) = req_ctx.get_cache_max_age() else {
+        return;
+    };
+    if let Some(upstream_max_age) = req_ctx.get_min_max_age() {
+        match u64::try_from(upstream_max_age)
+            .ok()
+            .and_then(NonZeroU64::new)
+        {
+            Some(upstream_max_age) => ttl = ttl.min(upstream_max_age.saturating_mul(1000)),
+            None => return,
+        }
+    }
+
+    let entry = IndexMap::from([
+        (Name::new("data"), response.data.clone()),
+        (Name::new("storedAt"), ConstValue::from(now())),
+        (Name::new("maxAge"), ConstValue::from(ttl.get() / 1000)),
+    ]);
+
+    let cache = &req_ctx.runtime.cache;
+    let cached = match cache.set(key.clone(), ConstValue::Object(entry), ttl).await {
+        // the response is purged along with the values it was resolved from
+        Ok(()) => cache.tag(&key, &req_ctx.get_cache_tags()).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = cached {
+        tracing::warn!("Failed to cache the response: {}", error);
+    }
+}
+
+/// Seconds since the epoch, chrono reads the clock on wasm targets as well.
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Strips the comments and the insignificant whitespaces and commas of a
+/// document, so that the documents differing only in their formatting share
+/// their cached responses.
+fn normalize(query: &str) -> String {
+    let mut normalized = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut separated = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                while chars.next_if(|c| *c != '\n' && *c != '\r').is_some() {}
+                separated = true;
+            }
+            ',' | ' ' | '\t' | '\n' | '\r' | '\u{feff}' => separated = true,
+            _ => {
+                // names and numbers are the only tokens that need to be separated
+                let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+                if separated && is_name(c) && normalized.ends_with(is_name) {
+                    normalized.push(' ');
+                }
+                separated = false;
+                normalized.push(c);
+
+                if c == '"' {
+                    copy_string(&mut chars, &mut normalized);
+                }
+            }
+        }
+    }
+
+    normalized
+}
+
+/// Copies a string, block strings included, as is.
+fn copy_string(chars: &mut std::iter::Peekable<std::str::Chars>, normalized: &mut String) {
+    let mut quotes = 1;
+    while quotes < 3 && chars.next_if_eq(&'"').is_some() {
+        normalized.push('"');
+        quotes += 1;
+    }
+    if quotes == 2 {
+        // empty string
+        return;
+    }
+
+    let mut closing = 0;
+    while let Some(c) = chars.next() {
+        normalized.push(c);
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    normalized.push(c);
+                }
+                closing = 0;
+            }
+            '"' => {
+                closing += 1;
+                if closing == quotes {
+                    return;
+                }
+            }
+            _ => closing = 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn test_normalize() {
+        let query = r#"
+            # the posts of a user
+            query Posts($id: Int!, $full: Boolean) {
+                user(id: $id) {
+                    posts(filter: "a,  b") @include(if: $full) { id , title }
+                    ...Details
+                }
+            }
+        "#;
+
+        assert_eq!(
+            normalize(query),
+            r#"query Posts($id:Int!$full:Boolean){user(id:$id){posts(filter:"a,  b")@include(if:$full){id title}...Details}}"#
+        );
+    }
+
+    #[test]
+    fn test_normalize_block_string() {
+        assert_eq!(
+            normalize(r#"{ a(b: """ x "y" """, c: "") }"#),
+            r#"{a(b:""" x "y" """c:"")}"#
+        );
+    }
+}
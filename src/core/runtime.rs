@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_graphql_value::ConstValue;
@@ -5,7 +6,7 @@ use async_graphql_value::ConstValue;
 use super::ir::model::IoId;
 use crate::core::schema_extension::SchemaExtension;
 use crate::core::worker::{Command, Event};
-use crate::core::{Cache, EnvIO, FileIO, HttpIO, WorkerIO};
+use crate::core::{Cache, EnvIO, FileIO, HttpIO, RateLimitStore, WorkerIO};
 
 /// The TargetRuntime struct unifies the available runtime-specific
 /// IO implementations. This is used to reduce piping IO structs all
@@ -16,6 +17,9 @@ pub struct TargetRuntime {
     pub http: Arc<dyn HttpIO>,
     /// HTTP client optimized for HTTP/2 requests.
     pub http2_only: Arc<dyn HttpIO>,
+    /// HTTP clients of the upstreams with a name, configured separately from
+    /// the default ones.
+    pub upstreams: Arc<HashMap<String, UpstreamIO>>,
     /// Interface for accessing environment variables specific to the target
     /// environment.
     pub env: Arc<dyn EnvIO>,
@@ -25,6 +29,8 @@ pub struct TargetRuntime {
     /// Cache for storing and retrieving entity data, improving performance and
     /// reducing external calls.
     pub cache: Arc<dyn Cache<Key = IoId, Value = ConstValue>>,
+    /// Store of the token buckets of rate limited clients.
+    pub rate_limit: Arc<dyn RateLimitStore>,
     /// A list of extensions that can be used to extend the runtime's
     /// functionality or integrate additional features.
     pub extensions: Arc<Vec<SchemaExtension>>,
@@ -34,10 +40,42 @@ pub struct TargetRuntime {
     pub worker: Option<Arc<dyn WorkerIO<ConstValue, ConstValue>>>,
 }
 
+/// The HTTP clients of a named upstream.
+#[derive(Clone)]
+pub struct UpstreamIO {
+    pub http: Arc<dyn HttpIO>,
+    pub http2_only: Arc<dyn HttpIO>,
+}
+
 impl TargetRuntime {
     pub fn add_extensions(&mut self, extensions: Vec<SchemaExtension>) {
         self.extensions = Arc::new(extensions);
     }
+
+    /// The HTTP client of the given upstream, or the default one.
+    pub fn http_of(&self, upstream: Option<&str>) -> &Arc<dyn HttpIO> {
+        match upstream.and_then(|name| self.upstreams.get(name)) {
+            Some(upstream) => &upstream.http,
+            None => &self.http,
+        }
+    }
+
+    /// The HTTP/2 client of the given upstream, or the default one.
+    pub fn http2_only_of(&self, upstream: Option<&str>) -> &Arc<dyn HttpIO> {
+        match upstream.and_then(|name| self.upstreams.get(name)) {
+            Some(upstream) => &upstream.http2_only,
+            None => &self.http2_only,
+        }
+    }
+
+    /// A runtime sending the requests through the clients of the given
+    /// upstream, e.g. for the data loaders of its resolvers.
+    pub fn with_upstream(&self, upstream: Option<&str>) -> Self {
+        let mut runtime = self.clone();
+        runtime.http = self.http_of(upstream).clone();
+        runtime.http2_only = self.http2_only_of(upstream).clone();
+        runtime
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +98,7 @@ pub mod test {
     use crate::core::blueprint::Upstream;
     use crate::core::cache::InMemoryCache;
     use crate::core::http::Response;
+    use crate::core::rate_limit::InMemoryRateLimitStore;
     use crate::core::runtime::TargetRuntime;
     use crate::core::worker::{Command, Event};
     use crate::core::{blueprint, EnvIO, FileIO, HttpIO};
@@ -184,9 +223,11 @@ pub mod test {
         TargetRuntime {
             http,
             http2_only: http2,
+            upstreams: Default::default(),
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limit: Arc::new(InMemoryRateLimitStore::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: match &script {
                 Some(script) => Some(init_worker_io::<Event, Command>(script.to_owned())),
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use async_graphql::{ErrorExtensionValues, ServerError};
+
+use crate::core::blueprint::Blueprint;
+use crate::core::jit::PreparedOperation;
+use crate::core::persisted_query::{self, sha256};
+
+/// Error of a request for an operation that isn't part of the trusted
+/// documents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotTrusted;
+
+impl From<NotTrusted> for ServerError {
+    fn from(_: NotTrusted) -> Self {
+        let mut extensions = ErrorExtensionValues::default();
+        extensions.set("code", "OPERATION_NOT_TRUSTED");
+
+        let mut server_error = ServerError::new("Only trusted operations can be executed", None);
+        server_error.extensions = Some(extensions);
+        server_error
+    }
+}
+
+struct TrustedDocument {
+    query: String,
+    /// Plans of the operations of the document, `None` standing for the only
+    /// operation of the document when it's selected without a name.
+    operations: HashMap<Option<String>, PreparedOperation>,
+}
+
+/// The operations of the linked operation files, the only ones that can be
+/// executed when `trustedDocuments` is enabled. The operations are planned
+/// once, when the documents are loaded.
+#[derive(Default)]
+pub struct TrustedDocuments {
+    /// Documents by the sha256 hash of their text
+    documents: HashMap<String, TrustedDocument>,
+    /// Hash of the document of each named operation
+    names: HashMap<String, String>,
+}
+
+impl TrustedDocuments {
+    pub fn new(blueprint: &Blueprint, documents: &[String]) -> Self {
+        let mut trusted = Self::default();
+
+        for query in documents {
+            let names = match async_graphql::parser::parse_query(query) {
+                Ok(doc) => doc
+                    .operations
+                    .iter()
+                    .map(|(name, _)| name.map(|name| name.to_string()))
+                    .collect::<Vec<_>>(),
+                Err(error) => {
+                    tracing::warn!("Failed to parse trusted document: {}", error);
+                    continue;
+                }
+            };
+            let hash = sha256(query);
+            let mut operations = HashMap::new();
+
+            for name in names.iter() {
+                match PreparedOperation::try_new(blueprint, query, name.as_deref()) {
+                    Ok(operation) => {
+                        if let Some(name) = name {
+                            trusted.names.insert(name.clone(), hash.clone());
+                        }
+                        if names.len() == 1 {
+                            operations.insert(None, operation.clone());
+                        }
+                        operations.insert(name.clone(), operation);
+                    }
+                    Err(error) => tracing::warn!(
+                        "Operation `{}` is not trusted: {}",
+                        name.as_deref().unwrap_or_default(),
+                        error
+                    ),
+                }
+            }
+
+            trusted
+                .documents
+                .insert(hash, TrustedDocument { query: query.clone(), operations });
+        }
+
+        trusted
+    }
+
+    /// Replaces the query of the request with the trusted document it refers
+    /// to, by the hash of the document or the name of the operation.
+    pub fn resolve(&self, request: &mut async_graphql::Request) -> Result<(), NotTrusted> {
+        let hash = match persisted_query::requested_hash(request) {
+            Ok(Some(hash)) => hash,
+            Ok(None) if !request.query.is_empty() => sha256(&request.query),
+            Ok(None) => request
+                .operation_name
+                .as_ref()
+                .and_then(|name| self.names.get(name))
+                .cloned()
+                .ok_or(NotTrusted)?,
+            Err(_) => return Err(NotTrusted),
+        };
+        let document = self.documents.get(&hash).ok_or(NotTrusted)?;
+
+        request.query.clone_from(&document.query);
+        Ok(())
+    }
+
+    /// The prepared plan of an operation of a trusted document.
+    pub fn get(&self, query: &str, operation_name: Option<&str>) -> Option<&PreparedOperation> {
+        self.documents
+            .get(&sha256(query))?
+            .operations
+            .get(&operation_name.map(str::to_owned))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{Config, ConfigModule};
+    use crate::core::valid::Validator;
+
+    const SCHEMA: &str = r#"
+        schema @server @upstream(baseURL: "http://localhost") {
+            query: Query
+        }
+        type Query {
+            user(id: Int!): User @http(path: "/users/{{.args.id}}")
+            posts: [Post] @http(path: "/posts")
+        }
+        type User {
+            id: Int
+            name: String
+        }
+        type Post {
+            id: Int
+            title: String
+        }
+    "#;
+
+    const DOCUMENT: &str = r#"
+        query GetUser($id: Int!) { user(id: $id) { id name } }
+        query GetPosts { posts { id title } }
+    "#;
+
+    fn trusted_documents() -> TrustedDocuments {
+        let config = Config::from_sdl(SCHEMA).to_result().unwrap();
+        let blueprint = Blueprint::try_from(&ConfigModule::from(config)).unwrap();
+
+        TrustedDocuments::new(&blueprint, &[DOCUMENT.to_string()])
+    }
+
+    #[test]
+    fn test_resolve_by_name() {
+        let trusted = trusted_documents();
+        let mut request = async_graphql::Request::new("").operation_name("GetPosts");
+
+        assert_eq!(trusted.resolve(&mut request), Ok(()));
+        assert_eq!(request.query, DOCUMENT);
+        assert!(trusted.get(&request.query, Some("GetPosts")).is_some());
+    }
+
+    #[test]
+    fn test_resolve_by_query() {
+        let trusted = trusted_documents();
+        let mut request = async_graphql::Request::new(DOCUMENT).operation_name("GetUser");
+
+        assert_eq!(trusted.resolve(&mut request), Ok(()));
+        assert!(trusted.get(&request.query, None).is_none());
+    }
+
+    #[test]
+    fn test_reject_ad_hoc_query() {
+        let trusted = trusted_documents();
+        let mut request = async_graphql::Request::new("{ posts { id } }");
+        assert_eq!(trusted.resolve(&mut request), Err(NotTrusted));
+
+        let mut request = async_graphql::Request::new("").operation_name("Unknown");
+        assert_eq!(trusted.resolve(&mut request), Err(NotTrusted));
+    }
+}
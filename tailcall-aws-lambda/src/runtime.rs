@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use tailcall::core::cache::InMemoryCache;
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EntityCache, EnvIO, FileIO};
 use tokio::io::AsyncReadExt;
@@ -56,9 +57,11 @@ pub fn init_runtime() -> TargetRuntime {
     TargetRuntime {
         http: http.clone(),
         http2_only: http,
+        upstreams: Default::default(),
         file: init_file(),
         env: init_env(),
         cache: init_cache(),
+        rate_limit: Arc::new(InMemoryRateLimitStore::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
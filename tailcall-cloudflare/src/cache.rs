@@ -5,7 +5,7 @@ use async_graphql_value::ConstValue;
 use serde_json::Value;
 use tailcall::core::ir::model::IoId;
 use tailcall::core::{cache, Cache};
-use worker::kv::KvStore;
+use worker::kv::{Key, KvStore};
 
 pub struct CloudflareChronoCache {
     env: Rc<worker::Env>,
@@ -25,6 +25,36 @@ impl CloudflareChronoCache {
             .map_err(|e| cache::Error::Kv(e.to_string()))
     }
 }
+
+/// Prefix of the KV keys of the keys associated with a tag, one KV key per
+/// key so that concurrent requests tagging keys don't overwrite each other.
+fn tag_prefix(tag: &str) -> String {
+    format!("tag:{}:", tag)
+}
+
+/// Lists the KV keys starting with the prefix, following the pages of the
+/// listing. Listing is eventually consistent, so recently tagged keys can be
+/// missed by a purge.
+async fn list(kv_store: &KvStore, prefix: &str) -> Result<Vec<Key>, cache::Error> {
+    let mut keys = vec![];
+    let mut cursor = None;
+    loop {
+        let mut list = kv_store.list().prefix(prefix.to_owned());
+        if let Some(cursor) = cursor {
+            list = list.cursor(cursor);
+        }
+        let page = list
+            .execute()
+            .await
+            .map_err(|e| cache::Error::Kv(e.to_string()))?;
+        keys.extend(page.keys);
+        if page.list_complete || page.cursor.is_none() {
+            return Ok(keys);
+        }
+        cursor = page.cursor;
+    }
+}
+
 // TODO: Needs fix
 #[async_trait::async_trait]
 impl Cache for CloudflareChronoCache {
@@ -64,7 +94,72 @@ impl Cache for CloudflareChronoCache {
         .await
     }
 
+    async fn tag<'a>(&'a self, key: &'a IoId, tags: &'a [String]) -> Result<(), cache::Error> {
+        let kv_store = self.get_kv()?;
+        let key = key.as_u64().to_string();
+        let tags = tags.to_vec();
+        async_std::task::spawn_local(async move {
+            // the tags expire along with the value, which is only listed with its
+            // expiration, first of the keys it prefixes
+            let expiration = kv_store
+                .list()
+                .prefix(key.clone())
+                .limit(1)
+                .execute()
+                .await
+                .map_err(|e| cache::Error::Kv(e.to_string()))?
+                .keys
+                .into_iter()
+                .find(|entry| entry.name == key)
+                .and_then(|entry| entry.expiration);
+            let Some(expiration) = expiration else {
+                return Ok(());
+            };
+
+            for tag in tags {
+                kv_store
+                    .put(&format!("{}{}", tag_prefix(&tag), key), String::new())
+                    .map_err(|e| cache::Error::Kv(e.to_string()))?
+                    .expiration(expiration)
+                    .execute()
+                    .await
+                    .map_err(|e| cache::Error::Kv(e.to_string()))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn purge<'a>(&'a self, tag: &'a str) -> Result<(), cache::Error> {
+        let kv_store = self.get_kv()?;
+        let prefix = tag_prefix(tag);
+        async_std::task::spawn_local(async move {
+            for tag_key in list(&kv_store, &prefix).await? {
+                // the prefix of a tag is also the prefix of the longer tags it starts
+                let Some(key) = tag_key
+                    .name
+                    .strip_prefix(&prefix)
+                    .filter(|key| key.parse::<u64>().is_ok())
+                else {
+                    continue;
+                };
+                for name in [key, tag_key.name.as_str()] {
+                    kv_store
+                        .delete(name)
+                        .await
+                        .map_err(|e| cache::Error::Kv(e.to_string()))?;
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
     fn hit_rate(&self) -> Option<f64> {
         None
     }
+
+    fn stats(&self) -> Option<cache::Stats> {
+        None
+    }
 }
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use async_graphql_value::ConstValue;
 use tailcall::core::ir::model::IoId;
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 
@@ -37,9 +38,11 @@ pub fn init(env: Rc<worker::Env>) -> anyhow::Result<TargetRuntime> {
     Ok(TargetRuntime {
         http: http.clone(),
         http2_only: http.clone(),
+        upstreams: Default::default(),
         env: init_env(env.clone()),
         file: init_file(env.clone(), &bucket_id)?,
         cache: init_cache(env),
+        rate_limit: Arc::new(InMemoryRateLimitStore::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
@@ -8,8 +8,12 @@ use std::sync::Arc;
 use moka::future::Cache;
 use moka::policy::EvictionPolicy;
 
+use crate::disk::DiskStore;
+
 pub struct HttpCacheManager {
     pub cache: Arc<Cache<String, Store>>,
+    /// Slower store behind the in-memory cache, that outlives the process
+    disk: Option<DiskStore>,
 }
 
 impl Default for HttpCacheManager {
@@ -30,14 +34,33 @@ impl HttpCacheManager {
             .eviction_policy(EvictionPolicy::lru())
             .max_capacity(cache_size)
             .build();
-        Self { cache: Arc::new(cache) }
+        Self { cache: Arc::new(cache), disk: None }
+    }
+
+    pub fn with_disk(self, disk: DiskStore) -> Self {
+        Self { disk: Some(disk), ..self }
     }
 
     pub async fn clear(&self) -> Result<()> {
         self.cache.invalidate_all();
         self.cache.run_pending_tasks().await;
+        if let Some(disk) = &self.disk {
+            disk.clear().await?;
+        }
         Ok(())
     }
+
+    /// Reads the response from disk, keeping it in memory for the next reads.
+    async fn get_from_disk(&self, disk: &DiskStore, cache_key: &str) -> Result<Option<Store>> {
+        let Some(bytes) = disk.get(cache_key).await? else {
+            return Ok(None);
+        };
+        let store: Store = bincode::deserialize(&bytes)?;
+        self.cache
+            .insert(cache_key.to_string(), store.clone())
+            .await;
+        Ok(Some(store))
+    }
 }
 
 #[async_trait::async_trait]
@@ -45,7 +68,18 @@ impl CacheManager for HttpCacheManager {
     async fn get(&self, cache_key: &str) -> Result<Option<(HttpResponse, CachePolicy)>> {
         let store: Store = match self.cache.get(cache_key).await {
             Some(d) => d,
-            None => return Ok(None),
+            None => match &self.disk {
+                Some(disk) => match self.get_from_disk(disk, cache_key).await {
+                    Ok(Some(d)) => d,
+                    Ok(None) => return Ok(None),
+                    Err(e) => {
+                        // served as a miss, the response being fetched again
+                        tracing::warn!("Failed to read the HTTP cache from disk: {}", e);
+                        return Ok(None);
+                    }
+                },
+                None => return Ok(None),
+            },
         };
         Ok(Some((store.response, store.policy)))
     }
@@ -57,6 +91,15 @@ impl CacheManager for HttpCacheManager {
         policy: CachePolicy,
     ) -> Result<HttpResponse> {
         let data = Store { response: response.clone(), policy };
+        if let Some(disk) = &self.disk {
+            let written = match bincode::serialize(&data) {
+                Ok(bytes) => disk.put(&cache_key, &bytes).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = written {
+                tracing::warn!("Failed to write the HTTP cache to disk: {}", e);
+            }
+        }
         self.cache.insert(cache_key, data).await;
         self.cache.run_pending_tasks().await;
         Ok(response)
@@ -65,6 +108,9 @@ impl CacheManager for HttpCacheManager {
     async fn delete(&self, cache_key: &str) -> Result<()> {
         self.cache.invalidate(cache_key).await;
         self.cache.run_pending_tasks().await;
+        if let Some(disk) = &self.disk {
+            disk.delete(cache_key).await?;
+        }
         Ok(())
     }
 }
@@ -155,6 +201,23 @@ mod tests {
         assert!(manager.cache.iter().count() as i32 == 0);
     }
 
+    #[tokio::test]
+    async fn test_get_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = HttpCacheManager::new(2).with_disk(DiskStore::new(dir.path(), 1024).unwrap());
+        insert_key_into_cache(&manager, "test").await;
+
+        // e.g. after a restart
+        let manager = HttpCacheManager::new(2).with_disk(DiskStore::new(dir.path(), 1024).unwrap());
+        let (response, _) = manager.get("test").await.unwrap().unwrap();
+        assert_eq!(response.body, vec![1, 2, 3]);
+        assert!(manager.cache.contains_key("test"));
+
+        manager.delete("test").await.unwrap();
+        let manager = HttpCacheManager::new(2).with_disk(DiskStore::new(dir.path(), 1024).unwrap());
+        assert!(manager.get("test").await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_lru_eviction_policy() {
         let manager = HttpCacheManager::new(2);
@@ -0,0 +1,349 @@
+use std::fs;
+use std::hash::Hasher;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use fnv::FnvHasher;
+
+use crate::cache::Result;
+
+const TMP_EXTENSION: &str = "tmp";
+
+/// Temporary files older than this are left over by writers that crashed.
+const TMP_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Cached responses stored as files in a directory, one per key, that several
+/// processes can share.
+///
+/// Files are written under a temporary name and then renamed, so readers
+/// never see a partial write. The least recently used files are removed in
+/// the background once the files outgrow `max_size`. Other files in the
+/// directory are neither counted nor removed.
+#[derive(Clone)]
+pub struct DiskStore {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    dir: PathBuf,
+    max_size: u64,
+    /// Size of the files as of the last compaction, plus the size written
+    /// since then.
+    size: AtomicU64,
+    /// Whether the size of the files present on startup is still unknown
+    scanned: AtomicBool,
+    compacting: AtomicBool,
+}
+
+impl DiskStore {
+    pub fn new(dir: impl Into<PathBuf>, max_size: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                dir,
+                max_size,
+                size: AtomicU64::new(0),
+                scanned: AtomicBool::new(false),
+                compacting: AtomicBool::new(false),
+            }),
+        })
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.inner.path(key);
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let Some(value) = decode(key, &bytes) else {
+            // written for another key with the same hash
+            return Ok(None);
+        };
+
+        // the modification time tracks the last access for the compaction
+        tokio::task::spawn_blocking(move || touch(&path));
+
+        Ok(Some(value.to_vec()))
+    }
+
+    pub async fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        let path = self.inner.path(key);
+        let tmp = path.with_extension(format!("{}.{}", unique_suffix(), TMP_EXTENSION));
+        let bytes = encode(key, value);
+        let len = bytes.len() as u64;
+
+        tokio::fs::write(&tmp, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e.into());
+        }
+
+        let size = self.inner.size.fetch_add(len, Ordering::Relaxed) + len;
+        if size > self.inner.max_size || !self.inner.scanned.load(Ordering::Relaxed) {
+            self.compact();
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.inner.path(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            for entry in fs::read_dir(&inner.dir)? {
+                let path = entry?.path();
+                if is_entry(&path) {
+                    remove(&path);
+                }
+            }
+            inner.size.store(0, Ordering::Relaxed);
+            Ok::<_, std::io::Error>(())
+        })
+        .await??;
+
+        Ok(())
+    }
+
+    /// Removes the least recently used files in the background, once at a
+    /// time, until they fit in 90% of `max_size`.
+    fn compact(&self) {
+        if self.inner.compacting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = inner.compact() {
+                tracing::warn!("Failed to compact the HTTP cache: {}", e);
+            }
+            inner.scanned.store(true, Ordering::Relaxed);
+            inner.compacting.store(false, Ordering::Release);
+        });
+    }
+
+    /// Waits for the compaction in progress, if any.
+    #[cfg(test)]
+    async fn compacted(&self) {
+        while self.inner.compacting.load(Ordering::Acquire) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+}
+
+impl Inner {
+    fn path(&self, key: &str) -> PathBuf {
+        let mut hasher = FnvHasher::default();
+        hasher.write(key.as_bytes());
+        self.dir.join(format!("{:016x}", hasher.finish()))
+    }
+
+    fn compact(&self) -> std::io::Result<()> {
+        let now = SystemTime::now();
+        let mut files = Vec::new();
+        let mut size = 0;
+
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let tmp = is_tmp(&path);
+            if !tmp && !is_entry(&path) {
+                // the directory may hold files the store didn't write
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                // removed meanwhile
+                continue;
+            };
+            let modified = metadata.modified()?;
+
+            if tmp {
+                let age = now.duration_since(modified).unwrap_or_default();
+                if age > TMP_MAX_AGE {
+                    remove(&path);
+                }
+                continue;
+            }
+
+            size += metadata.len();
+            files.push((modified, metadata.len(), path));
+        }
+
+        if size > self.max_size {
+            files.sort_unstable_by_key(|(modified, ..)| *modified);
+            let target = self.max_size / 10 * 9;
+            for (_, len, path) in files {
+                if size <= target {
+                    break;
+                }
+                remove(&path);
+                size -= len;
+            }
+        }
+
+        self.size.store(size, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// The key is stored along with the value, its length first.
+fn encode(key: &str, value: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + key.len() + value.len());
+    bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(key.as_bytes());
+    bytes.extend_from_slice(value);
+    bytes
+}
+
+fn decode<'a>(key: &str, bytes: &'a [u8]) -> Option<&'a [u8]> {
+    let (len, bytes) = bytes.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    let (stored_key, value) = bytes.split_at_checked(len)?;
+
+    (stored_key == key.as_bytes()).then_some(value)
+}
+
+/// Files holding an entry are named after the hash of its key, as written by
+/// `Inner::path`.
+fn is_entry(path: &Path) -> bool {
+    file_name(path).is_some_and(is_hash)
+}
+
+/// Temporary files are named after the hash of the key they're written for,
+/// followed by a unique suffix.
+fn is_tmp(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == TMP_EXTENSION)
+        && file_name(path)
+            .and_then(|name| name.split_once('.'))
+            .is_some_and(|(hash, _)| is_hash(hash))
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()
+}
+
+fn is_hash(name: &str) -> bool {
+    name.len() == 16 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn remove(path: &Path) {
+    // other processes sharing the directory may have removed it already
+    let _ = fs::remove_file(path);
+}
+
+/// Distinguishes the temporary files of concurrent writers, across processes
+fn unique_suffix() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(max_size: u64) -> (tempfile::TempDir, DiskStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskStore::new(dir.path(), max_size).unwrap();
+        (dir, store)
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let (_dir, store) = store(1024);
+        store
+            .put("GET:http://localhost/1", &[1, 2, 3])
+            .await
+            .unwrap();
+
+        let value = store.get("GET:http://localhost/1").await.unwrap();
+        assert_eq!(value, Some(vec![1, 2, 3]));
+        assert_eq!(store.get("GET:http://localhost/2").await.unwrap(), None);
+
+        store.delete("GET:http://localhost/1").await.unwrap();
+        assert_eq!(store.get("GET:http://localhost/1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_shared_directory() {
+        let (dir, store) = store(1024);
+        store
+            .put("GET:http://localhost/1", &[1, 2, 3])
+            .await
+            .unwrap();
+
+        // e.g. after a restart, or from another worker
+        let other = DiskStore::new(dir.path(), 1024).unwrap();
+        let value = other.get("GET:http://localhost/1").await.unwrap();
+        assert_eq!(value, Some(vec![1, 2, 3]));
+
+        other.clear().await.unwrap();
+        assert_eq!(store.get("GET:http://localhost/1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_compaction() {
+        let (_dir, store) = store(300);
+        for i in 0..4 {
+            let key = format!("GET:http://localhost/{}", i);
+            store.compacted().await;
+            store.put(&key, &[0; 100]).await.unwrap();
+            // distinct modification times
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        store.compacted().await;
+
+        // the oldest files are removed until the others fit in 270 bytes
+        assert_eq!(store.get("GET:http://localhost/0").await.unwrap(), None);
+        assert_eq!(store.get("GET:http://localhost/1").await.unwrap(), None);
+        assert!(store.get("GET:http://localhost/2").await.unwrap().is_some());
+        assert!(store.get("GET:http://localhost/3").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_other_files() {
+        let (dir, store) = store(100);
+        let other = dir.path().join("config.json");
+        fs::write(&other, [0; 200]).unwrap();
+
+        store.put("GET:http://localhost/1", &[0; 50]).await.unwrap();
+        store.compacted().await;
+        assert!(store.get("GET:http://localhost/1").await.unwrap().is_some());
+
+        store.clear().await.unwrap();
+        assert_eq!(store.get("GET:http://localhost/1").await.unwrap(), None);
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn test_decode_other_key() {
+        let bytes = encode("GET:http://localhost/1", &[1]);
+        assert_eq!(
+            decode("GET:http://localhost/1", &bytes),
+            Some([1].as_slice())
+        );
+        assert_eq!(decode("GET:http://localhost/2", &bytes), None);
+        assert_eq!(decode("GET", &bytes[..2]), None);
+    }
+}
@@ -1,3 +1,5 @@
 mod cache;
+mod disk;
 
 pub use cache::HttpCacheManager;
+pub use disk::DiskStore;
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use async_graphql_value::ConstValue;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::ir::model::IoId;
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::{EnvIO, FileIO, HttpIO};
 
@@ -35,9 +36,11 @@ pub fn init_rt() -> TargetRuntime {
     TargetRuntime {
         http,
         http2_only,
+        upstreams: Default::default(),
         env,
         file,
         cache,
+        rate_limit: Arc::new(InMemoryRateLimitStore::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: None,
         worker: None,
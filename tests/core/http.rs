@@ -4,6 +4,7 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use http::header::{HeaderName, HeaderValue};
@@ -103,6 +104,10 @@ impl HttpIO for Http {
 
         execution_mock.actual_hits.fetch_add(1, Ordering::Relaxed);
 
+        if let Some(delay) = execution_mock.mock.delay {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+
         // Clone the response from the mock to avoid borrowing issues.
         let mock_response = execution_mock.mock.response.clone();
 
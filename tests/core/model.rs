@@ -42,6 +42,9 @@ pub struct Mock {
     pub assert_hits: bool,
     #[serde(default = "default::expected_hits")]
     pub expected_hits: usize,
+    /// Milliseconds the response is delayed by.
+    #[serde(default)]
+    pub delay: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
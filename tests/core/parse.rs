@@ -16,6 +16,7 @@ use tailcall::core::app_context::AppContext;
 use tailcall::core::blueprint::Blueprint;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::{ConfigModule, Source};
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::worker::{Command, Event};
 use tailcall::core::{EnvIO, WorkerIO};
@@ -300,9 +301,11 @@ impl ExecutionSpec {
         let runtime = TargetRuntime {
             http,
             http2_only,
+            upstreams: Default::default(),
             file: Arc::new(File::new(self.clone())),
             env: Arc::new(Env::init(env)),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limit: Arc::new(InMemoryRateLimitStore::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: http_worker,
             worker,
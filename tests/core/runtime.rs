@@ -11,6 +11,7 @@ use tailcall::cli::javascript::init_worker_io;
 use tailcall::core::blueprint::Script;
 use tailcall::core::cache::InMemoryCache;
 use tailcall::core::config::Source;
+use tailcall::core::rate_limit::InMemoryRateLimitStore;
 use tailcall::core::runtime::TargetRuntime;
 use tailcall::core::worker::{Command, Event};
 
@@ -77,9 +78,11 @@ pub fn create_runtime(
     TargetRuntime {
         http,
         http2_only: http2,
+        upstreams: Default::default(),
         env: Arc::new(env),
         file: Arc::new(file),
         cache: Arc::new(InMemoryCache::new()),
+        rate_limit: Arc::new(InMemoryRateLimitStore::default()),
         extensions: Arc::new(vec![]),
         cmd_worker: match &script {
             Some(script) => Some(init_worker_io::<Event, Command>(script.to_owned())),
@@ -15,6 +15,7 @@ pub mod test {
     use tailcall::core::blueprint::{Script, Upstream};
     use tailcall::core::cache::InMemoryCache;
     use tailcall::core::http::Response;
+    use tailcall::core::rate_limit::InMemoryRateLimitStore;
     use tailcall::core::runtime::TargetRuntime;
     use tailcall::core::worker::{Command, Event};
     use tailcall::core::{EnvIO, FileIO, HttpIO};
@@ -141,9 +142,11 @@ pub mod test {
         TargetRuntime {
             http,
             http2_only: http2,
+            upstreams: Default::default(),
             env: Arc::new(env),
             file: Arc::new(file),
             cache: Arc::new(InMemoryCache::new()),
+            rate_limit: Arc::new(InMemoryRateLimitStore::default()),
             extensions: Arc::new(vec![]),
             cmd_worker: match &script {
                 Some(script) => Some(init_worker_io::<Event, Command>(script.to_owned())),